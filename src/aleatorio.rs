@@ -0,0 +1,43 @@
+use crate::defs::{NumReal, Ponto, DIM};
+
+// Gerador pseudoaleatorio xorshift64*, pequeno e reproduzivel a partir da
+// semente, suficiente para sortear pontos de teste
+// (Vigna, 2016, An experimental exploration of Marsaglia's xorshift generators)
+#[derive(Debug, Clone)]
+pub struct GeradorAleatorio {
+    estado: u64,
+}
+
+impl GeradorAleatorio {
+    pub fn novo(semente: u64) -> Self {
+        // O estado do xorshift não pode ser nulo
+        Self {
+            estado: if semente == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                semente
+            },
+        }
+    }
+
+    pub fn proximo(&mut self) -> u64 {
+        self.estado ^= self.estado >> 12;
+        self.estado ^= self.estado << 25;
+        self.estado ^= self.estado >> 27;
+        self.estado.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Numero uniforme em [0, 1), pelos 53 bits mais altos
+    pub fn uniforme(&mut self) -> NumReal {
+        (self.proximo() >> 11) as NumReal / (1u64 << 53) as NumReal
+    }
+
+    // Ponto uniforme na caixa [inferior, superior]
+    pub fn ponto_na_caixa(&mut self, inferior: &Ponto, superior: &Ponto) -> Ponto {
+        let mut ponto = [0.0; DIM];
+        for i in 0..DIM {
+            ponto[i] = inferior[i] + self.uniforme() * (superior[i] - inferior[i]);
+        }
+        ponto
+    }
+}
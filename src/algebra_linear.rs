@@ -0,0 +1,749 @@
+use crate::defs::NumReal;
+
+use std::ops::{Index, IndexMut};
+
+// Tolerancia relativa padrão para considerar um elemento da diagonal de R
+// nulo, relativa ao maior elemento da matriz
+const TOL_POSTO: NumReal = 1e-10;
+
+// Tolerancia para considerar um pivô da LU nulo, relativa ao maior
+// elemento da matriz, ou absoluta se a matriz for pequena
+const TOL_PIVO: NumReal = 1e-12;
+
+// Matriz densa de numeros reais, armazenada por linhas.
+// O elemento (i, j) está em valores[i * n_colunas + j]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matriz {
+    pub n_linhas: usize,
+    pub n_colunas: usize,
+    valores: Vec<NumReal>,
+}
+
+impl Index<(usize, usize)> for Matriz {
+    type Output = NumReal;
+
+    fn index(&self, (i, j): (usize, usize)) -> &NumReal {
+        &self.valores[i * self.n_colunas + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matriz {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut NumReal {
+        &mut self.valores[i * self.n_colunas + j]
+    }
+}
+
+impl Matriz {
+    // Matriz nula com as dimensões dadas
+    pub fn nula(n_linhas: usize, n_colunas: usize) -> Self {
+        Self {
+            n_linhas,
+            n_colunas,
+            valores: vec![0.0; n_linhas * n_colunas],
+        }
+    }
+
+    // Matriz identidade n × n
+    pub fn identidade(n: usize) -> Self {
+        let mut matriz = Matriz::nula(n, n);
+        for i in 0..n {
+            matriz[(i, i)] = 1.0;
+        }
+        matriz
+    }
+
+    // Cria a partir de uma lista de linhas, todas do mesmo tamanho.
+    // O numero de colunas precisa ser dado para o caso sem linhas
+    pub fn de_linhas(linhas: &[Vec<NumReal>], n_colunas: usize) -> Self {
+        let mut matriz = Matriz::nula(linhas.len(), n_colunas);
+        for (i, linha) in linhas.iter().enumerate() {
+            assert_eq!(linha.len(), n_colunas);
+            for (j, &valor) in linha.iter().enumerate() {
+                matriz[(i, j)] = valor;
+            }
+        }
+        matriz
+    }
+
+    // Cria a partir de uma lista de colunas, todas do mesmo tamanho
+    pub fn de_colunas(colunas: &[Vec<NumReal>], n_linhas: usize) -> Self {
+        Matriz::de_linhas(colunas, n_linhas).transposta()
+    }
+
+    // Converte para a lista de linhas usada no resto do programa
+    pub fn para_linhas(&self) -> Vec<Vec<NumReal>> {
+        (0..self.n_linhas).map(|i| self.linha(i)).collect()
+    }
+
+    // Copia da linha i
+    pub fn linha(&self, i: usize) -> Vec<NumReal> {
+        self.valores[i * self.n_colunas..(i + 1) * self.n_colunas].to_vec()
+    }
+
+    // Copia da coluna j
+    pub fn coluna(&self, j: usize) -> Vec<NumReal> {
+        (0..self.n_linhas).map(|i| self[(i, j)]).collect()
+    }
+
+    // Troca duas linhas de lugar
+    fn trocar_linhas(&mut self, i: usize, k: usize) {
+        for j in 0..self.n_colunas {
+            self.valores
+                .swap(i * self.n_colunas + j, k * self.n_colunas + j);
+        }
+    }
+
+    // Troca duas colunas de lugar
+    fn trocar_colunas(&mut self, j: usize, k: usize) {
+        for i in 0..self.n_linhas {
+            self.valores
+                .swap(i * self.n_colunas + j, i * self.n_colunas + k);
+        }
+    }
+
+    pub fn transposta(&self) -> Matriz {
+        let mut transposta = Matriz::nula(self.n_colunas, self.n_linhas);
+        for i in 0..self.n_linhas {
+            for j in 0..self.n_colunas {
+                transposta[(j, i)] = self[(i, j)];
+            }
+        }
+        transposta
+    }
+
+    // Produto AB
+    pub fn produto(&self, outra: &Matriz) -> Matriz {
+        assert_eq!(self.n_colunas, outra.n_linhas);
+        let mut resultante = Matriz::nula(self.n_linhas, outra.n_colunas);
+        for i in 0..self.n_linhas {
+            for k in 0..self.n_colunas {
+                let aik = self[(i, k)];
+                if aik == 0.0 {
+                    continue;
+                }
+                for j in 0..outra.n_colunas {
+                    resultante[(i, j)] += aik * outra[(k, j)];
+                }
+            }
+        }
+        resultante
+    }
+
+    // Produto Ax
+    pub fn produto_vetor(&self, x: &[NumReal]) -> Vec<NumReal> {
+        assert_eq!(self.n_colunas, x.len());
+        (0..self.n_linhas)
+            .map(|i| (0..self.n_colunas).map(|j| self[(i, j)] * x[j]).sum())
+            .collect()
+    }
+
+    // Maior elemento em modulo, usado como escala das tolerancias
+    pub fn maior_elemento(&self) -> NumReal {
+        self.valores
+            .iter()
+            .fold(0.0, |acc: NumReal, v| acc.max(v.abs()))
+    }
+
+    // Decomposição PA = LU com pivoteamento parcial.
+    // Retorna None se a matriz é singular, isto é, se algum pivô é
+    // desprezivel perto do maior elemento da matriz
+    pub fn lu(&self) -> Option<DecomposicaoLu> {
+        assert_eq!(self.n_linhas, self.n_colunas);
+        let n = self.n_linhas;
+        let tol = TOL_PIVO * self.maior_elemento().max(1.0);
+
+        let mut lu = self.clone();
+        let mut permutacao: Vec<usize> = (0..n).collect();
+
+        for k in 0..n {
+            // Escolhe o maior elemento restante da coluna como pivô
+            let mut idx_pivo = k;
+            for i in (k + 1)..n {
+                if lu[(i, k)].abs() > lu[(idx_pivo, k)].abs() {
+                    idx_pivo = i;
+                }
+            }
+            if lu[(idx_pivo, k)].abs() <= tol {
+                return None;
+            }
+            lu.trocar_linhas(k, idx_pivo);
+            permutacao.swap(k, idx_pivo);
+
+            // Guarda os multiplicadores abaixo da diagonal, que formam L
+            for i in (k + 1)..n {
+                let fator = lu[(i, k)] / lu[(k, k)];
+                lu[(i, k)] = fator;
+                for j in (k + 1)..n {
+                    lu[(i, j)] -= fator * lu[(k, j)];
+                }
+            }
+        }
+
+        Some(DecomposicaoLu { lu, permutacao })
+    }
+
+    // Decomposição AP = QR de Householder com pivoteamento de colunas,
+    // que escolhe a cada passo a coluna restante de maior norma. Assim
+    // os elementos da diagonal de R decrescem em modulo, e o posto
+    // aparece como o numero deles que não é desprezivel
+    pub fn qr(&self) -> DecomposicaoQr {
+        let (m, n) = (self.n_linhas, self.n_colunas);
+        let mut r = self.clone();
+        let mut q = Matriz::identidade(m);
+        let mut permutacao: Vec<usize> = (0..n).collect();
+
+        for k in 0..m.min(n) {
+            // Coluna restante de maior norma
+            let norma_coluna = |r: &Matriz, j: usize| -> NumReal {
+                (k..m).map(|i| r[(i, j)] * r[(i, j)]).sum::<NumReal>()
+            };
+            let mut idx_pivo = k;
+            for j in (k + 1)..n {
+                if norma_coluna(&r, j) > norma_coluna(&r, idx_pivo) {
+                    idx_pivo = j;
+                }
+            }
+            r.trocar_colunas(k, idx_pivo);
+            permutacao.swap(k, idx_pivo);
+
+            // Refletor de Householder v que leva r[k.., k] a um multiplo de e₁
+            let norma = norma_coluna(&r, k).sqrt();
+            if norma == 0.0 {
+                continue;
+            }
+            let alfa = if r[(k, k)] > 0.0 { -norma } else { norma };
+            let mut v: Vec<NumReal> = (k..m).map(|i| r[(i, k)]).collect();
+            v[0] -= alfa;
+            let vtv: NumReal = v.iter().map(|vi| vi * vi).sum();
+            if vtv == 0.0 {
+                continue;
+            }
+
+            // R ← (I - 2vvᵀ/vᵀv)R
+            for j in k..n {
+                let vtr: NumReal = (k..m).map(|i| v[i - k] * r[(i, j)]).sum();
+                let fator = 2.0 * vtr / vtv;
+                for i in k..m {
+                    r[(i, j)] -= fator * v[i - k];
+                }
+            }
+
+            // Q ← Q(I - 2vvᵀ/vᵀv)
+            for i in 0..m {
+                let qv: NumReal = (k..m).map(|l| q[(i, l)] * v[l - k]).sum();
+                let fator = 2.0 * qv / vtv;
+                for l in k..m {
+                    q[(i, l)] -= fator * v[l - k];
+                }
+            }
+
+            // Abaixo da diagonal só sobra erro de arredondamento
+            for i in (k + 1)..m {
+                r[(i, k)] = 0.0;
+            }
+        }
+
+        DecomposicaoQr {
+            q,
+            r,
+            permutacao,
+            escala: self.maior_elemento(),
+        }
+    }
+
+    // Posto numerico, pela decomposição QR com pivoteamento
+    pub fn posto(&self) -> usize {
+        self.qr().posto()
+    }
+
+    // Base ortonormal do nucleo, os x com Ax = 0, um vetor por elemento.
+    // Como o nucleo de A é o complemento ortogonal da imagem de Aᵀ, são as
+    // ultimas colunas do Q da decomposição de Aᵀ
+    pub fn base_nucleo(&self) -> Vec<Vec<NumReal>> {
+        let qr = self.transposta().qr();
+        let posto = qr.posto();
+        (posto..self.n_colunas).map(|j| qr.q.coluna(j)).collect()
+    }
+
+    // Decomposição de Cholesky A = LLᵀ de uma matriz simetrica. Retorna
+    // None se A não é definida positiva, isto é, se algum pivô não é positivo
+    pub fn cholesky(&self) -> Option<Matriz> {
+        assert_eq!(self.n_linhas, self.n_colunas);
+        let n = self.n_linhas;
+        let mut l = Matriz::nula(n, n);
+
+        for j in 0..n {
+            let mut pivo = self[(j, j)];
+            for s in 0..j {
+                pivo -= l[(j, s)] * l[(j, s)];
+            }
+            if pivo <= 0.0 {
+                return None;
+            }
+            l[(j, j)] = pivo.sqrt();
+
+            for i in (j + 1)..n {
+                let mut soma = self[(i, j)];
+                for s in 0..j {
+                    soma -= l[(i, s)] * l[(j, s)];
+                }
+                l[(i, j)] = soma / l[(j, j)];
+            }
+        }
+
+        Some(l)
+    }
+
+    // Decomposição de Cholesky modificada de Gill e Murray para matrizes
+    // simetricas, A + E = LDLᵀ, com L triangular inferior de diagonal
+    // unitaria, D diagonal positiva e E diagonal não negativa. Quando A é
+    // suficientemente definida positiva, E = 0. Caso contrario, os
+    // elementos de D são aumentados o minimo para que os de L fiquem
+    // limitados por β, o que mantém a decomposição estavel
+    // (Nocedal e Wright, 2006, Numerical Optimization, pg 53)
+    pub fn cholesky_modificada(&self) -> CholeskyModificada {
+        assert_eq!(self.n_linhas, self.n_colunas);
+        let n = self.n_linhas;
+
+        // Maiores elementos da diagonal e de fora dela
+        let mut gama: NumReal = 0.0;
+        let mut xi: NumReal = 0.0;
+        for i in 0..n {
+            gama = gama.max(self[(i, i)].abs());
+            for j in 0..n {
+                if i != j {
+                    xi = xi.max(self[(i, j)].abs());
+                }
+            }
+        }
+
+        // Limite dos elementos de L e menor elemento aceito em D
+        let nu = if n > 1 {
+            ((n * n - 1) as NumReal).sqrt()
+        } else {
+            1.0
+        };
+        let beta2 = gama.max(xi / nu).max(NumReal::EPSILON);
+        let delta = NumReal::EPSILON.sqrt() * (gama + xi).max(1.0);
+
+        let mut l = Matriz::identidade(n);
+        let mut d = vec![0.0; n];
+        let mut e = vec![0.0; n];
+
+        // Coluna j de C = A - Σ dₛlₛlₛᵀ, sendo calculada
+        let mut c = self.clone();
+
+        for j in 0..n {
+            // Atualiza a coluna j de C com as colunas anteriores de L
+            for i in j..n {
+                let mut soma = 0.0;
+                for s in 0..j {
+                    soma += d[s] * l[(i, s)] * l[(j, s)];
+                }
+                c[(i, j)] = self[(i, j)] - soma;
+            }
+
+            let theta = ((j + 1)..n).fold(0.0, |acc: NumReal, i| acc.max(c[(i, j)].abs()));
+
+            d[j] = c[(j, j)].abs().max(theta * theta / beta2).max(delta);
+            e[j] = d[j] - c[(j, j)];
+
+            for i in (j + 1)..n {
+                l[(i, j)] = c[(i, j)] / d[j];
+            }
+        }
+
+        CholeskyModificada { l, d, e }
+    }
+
+    // Autovalores e autovetores de uma matriz simetrica pelo metodo
+    // ciclico de Jacobi, que percorre os elementos fora da diagonal e zera
+    // cada um com uma rotação, até que a matriz seja praticamente diagonal.
+    // Os autovalores saem em ordem crescente, com os autovetores nas
+    // colunas correspondentes
+    pub fn autodecomposicao_simetrica(&self) -> (Vec<NumReal>, Matriz) {
+        assert_eq!(self.n_linhas, self.n_colunas);
+        let n = self.n_linhas;
+        let mut a = self.clone();
+        let mut v = Matriz::identidade(n);
+
+        for _ in 0..100 {
+            // Soma dos quadrados fora da diagonal
+            let mut fora = 0.0;
+            let mut diagonal = 0.0;
+            for i in 0..n {
+                for j in 0..n {
+                    if i != j {
+                        fora += a[(i, j)] * a[(i, j)];
+                    } else {
+                        diagonal += a[(i, j)] * a[(i, j)];
+                    }
+                }
+            }
+            if fora <= NumReal::EPSILON * NumReal::EPSILON * diagonal || fora == 0.0 {
+                break;
+            }
+
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    if a[(p, q)] == 0.0 {
+                        continue;
+                    }
+
+                    // Rotação que zera a[p][q]
+                    let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+
+                    // A ← JᵀAJ e V ← VJ
+                    for k in 0..n {
+                        let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                        a[(k, p)] = c * akp - s * akq;
+                        a[(k, q)] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                        a[(p, k)] = c * apk - s * aqk;
+                        a[(q, k)] = s * apk + c * aqk;
+                    }
+                    for k in 0..n {
+                        let (vkp, vkq) = (v[(k, p)], v[(k, q)]);
+                        v[(k, p)] = c * vkp - s * vkq;
+                        v[(k, q)] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+
+        // Ordena os autovalores, levando junto os autovetores
+        let mut ordem: Vec<usize> = (0..n).collect();
+        ordem.sort_by(|&i, &j| a[(i, i)].partial_cmp(&a[(j, j)]).unwrap());
+
+        let autovalores = ordem.iter().map(|&i| a[(i, i)]).collect();
+        let mut autovetores = Matriz::nula(n, n);
+        for (nova, &antiga) in ordem.iter().enumerate() {
+            for k in 0..n {
+                autovetores[(k, nova)] = v[(k, antiga)];
+            }
+        }
+
+        (autovalores, autovetores)
+    }
+}
+
+// Resultado da decomposição PA = LU, com L e U guardadas juntas
+#[derive(Clone, Debug)]
+pub struct DecomposicaoLu {
+    lu: Matriz,
+    permutacao: Vec<usize>, // Linha de A que foi para cada linha de PA
+}
+
+impl DecomposicaoLu {
+    // Resolve Ax = b por substituição progressiva e regressiva
+    pub fn resolver(&self, b: &[NumReal]) -> Vec<NumReal> {
+        let n = self.lu.n_linhas;
+
+        // Ly = Pb
+        let mut x: Vec<NumReal> = self.permutacao.iter().map(|&i| b[i]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+        }
+
+        // Ux = y
+        for i in (0..n).rev() {
+            for j in (i + 1)..n {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+            x[i] /= self.lu[(i, i)];
+        }
+
+        x
+    }
+
+    // Resolve Aᵀy = b com a mesma decomposição. Como Aᵀ = UᵀLᵀP, resolve
+    // Uᵀz = b, depois Lᵀw = z, e y = Pᵀw
+    pub fn resolver_transposta(&self, b: &[NumReal]) -> Vec<NumReal> {
+        let n = self.lu.n_linhas;
+
+        // Uᵀz = b
+        let mut z = b.to_vec();
+        for i in 0..n {
+            for j in 0..i {
+                z[i] -= self.lu[(j, i)] * z[j];
+            }
+            z[i] /= self.lu[(i, i)];
+        }
+
+        // Lᵀw = z, com L de diagonal unitaria
+        for i in (0..n).rev() {
+            for j in (i + 1)..n {
+                z[i] -= self.lu[(j, i)] * z[j];
+            }
+        }
+
+        // y = Pᵀw
+        let mut y = vec![0.0; n];
+        for (i, &linha) in self.permutacao.iter().enumerate() {
+            y[linha] = z[i];
+        }
+        y
+    }
+}
+
+// Resultado da decomposição AP = QR, com Q ortogonal completa
+#[derive(Clone, Debug)]
+pub struct DecomposicaoQr {
+    pub q: Matriz,
+    pub r: Matriz,
+    pub permutacao: Vec<usize>, // Coluna de A que foi para cada coluna de AP
+    escala: NumReal,            // Maior elemento de A
+}
+
+impl DecomposicaoQr {
+    // Numero de elementos da diagonal de R que não são desprezíveis
+    pub fn posto(&self) -> usize {
+        let tol = TOL_POSTO * self.escala;
+        (0..self.r.n_linhas.min(self.r.n_colunas))
+            .take_while(|&k| self.r[(k, k)].abs() > tol)
+            .count()
+    }
+
+    // Solução de minimos quadrados de min ‖Ax - b‖, usando apenas as
+    // colunas independentes. As colunas descartadas por dependencia
+    // ficam com valor nulo na solução
+    pub fn minimos_quadrados(&self, b: &[NumReal]) -> Vec<NumReal> {
+        let posto = self.posto();
+
+        // Qᵀb
+        let qtb = self.q.transposta().produto_vetor(b);
+
+        // R₁₁z = (Qᵀb)₁, R₁₁ sendo o bloco posto × posto
+        let mut z = qtb[0..posto].to_vec();
+        for i in (0..posto).rev() {
+            for j in (i + 1)..posto {
+                z[i] -= self.r[(i, j)] * z[j];
+            }
+            z[i] /= self.r[(i, i)];
+        }
+
+        // Desfaz a permutação das colunas
+        let mut x = vec![0.0; self.r.n_colunas];
+        for k in 0..posto {
+            x[self.permutacao[k]] = z[k];
+        }
+        x
+    }
+}
+
+// Resultado da decomposição de Cholesky modificada, A + E = LDLᵀ
+#[derive(Clone, Debug)]
+pub struct CholeskyModificada {
+    pub l: Matriz,
+    pub d: Vec<NumReal>,
+    pub e: Vec<NumReal>, // Diagonal da modificação E
+}
+
+impl CholeskyModificada {
+    // Reconstroi LDLᵀ = A + E, que é definida positiva
+    pub fn matriz_modificada(&self) -> Matriz {
+        let n = self.d.len();
+        let mut ld = self.l.clone();
+        for i in 0..n {
+            for j in 0..n {
+                ld[(i, j)] *= self.d[j];
+            }
+        }
+        ld.produto(&self.l.transposta())
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    const TOL_TESTE: NumReal = 1e-10;
+
+    fn proximas(a: &Matriz, b: &Matriz) -> bool {
+        assert_eq!((a.n_linhas, a.n_colunas), (b.n_linhas, b.n_colunas));
+        let escala = a.maior_elemento().max(b.maior_elemento()).max(1.0);
+        a.valores
+            .iter()
+            .zip(&b.valores)
+            .all(|(x, y)| (x - y).abs() <= TOL_TESTE * escala)
+    }
+
+    // Simetrica, definida positiva
+    fn definida_positiva() -> Matriz {
+        Matriz::de_linhas(
+            &[
+                vec![4.0, 2.0, -2.0],
+                vec![2.0, 10.0, 2.0],
+                vec![-2.0, 2.0, 5.0],
+            ],
+            3,
+        )
+    }
+
+    // Simetrica, com autovalores -1, 1 e 3
+    fn indefinida() -> Matriz {
+        Matriz::de_linhas(
+            &[
+                vec![1.0, 2.0, 0.0],
+                vec![2.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0],
+            ],
+            3,
+        )
+    }
+
+    #[test]
+    fn lu_reconstroi_a_e_resolve_os_dois_sistemas() {
+        // O primeiro pivô é nulo, então a permutação é necessaria
+        let a = Matriz::de_linhas(
+            &[
+                vec![0.0, 2.0, 1.0],
+                vec![1.0, 1.0, 0.0],
+                vec![3.0, 0.0, 4.0],
+            ],
+            3,
+        );
+        let decomposicao = a.lu().unwrap();
+
+        let mut l = Matriz::identidade(3);
+        let mut u = Matriz::nula(3, 3);
+        for i in 0..3 {
+            for j in 0..3 {
+                if j < i {
+                    l[(i, j)] = decomposicao.lu[(i, j)];
+                } else {
+                    u[(i, j)] = decomposicao.lu[(i, j)];
+                }
+            }
+        }
+        let linhas_pa: Vec<Vec<NumReal>> = decomposicao
+            .permutacao
+            .iter()
+            .map(|&i| a.linha(i))
+            .collect();
+        assert!(proximas(&l.produto(&u), &Matriz::de_linhas(&linhas_pa, 3)));
+
+        let b = [1.0, -2.0, 3.0];
+        let x = decomposicao.resolver(&b);
+        let y = decomposicao.resolver_transposta(&b);
+        let ax = a.produto_vetor(&x);
+        let aty = a.transposta().produto_vetor(&y);
+        for i in 0..3 {
+            assert!((ax[i] - b[i]).abs() <= TOL_TESTE);
+            assert!((aty[i] - b[i]).abs() <= TOL_TESTE);
+        }
+
+        // A singular não tem decomposição
+        let singular = Matriz::de_linhas(&[vec![1.0, 2.0], vec![2.0, 4.0]], 2);
+        assert!(singular.lu().is_none());
+    }
+
+    #[test]
+    fn qr_reconstroi_a_e_da_o_posto() {
+        // A terceira coluna é a soma das duas primeiras, então o posto é 2
+        let a = Matriz::de_linhas(
+            &[
+                vec![1.0, 0.0, 1.0],
+                vec![2.0, 1.0, 3.0],
+                vec![0.0, 3.0, 3.0],
+                vec![1.0, 1.0, 2.0],
+            ],
+            3,
+        );
+        let qr = a.qr();
+
+        // AP = QR, com Q ortogonal e R triangular superior
+        let colunas_ap: Vec<Vec<NumReal>> = qr.permutacao.iter().map(|&j| a.coluna(j)).collect();
+        let ap = Matriz::de_colunas(&colunas_ap, 4);
+        assert!(proximas(&qr.q.produto(&qr.r), &ap));
+        assert!(proximas(
+            &qr.q.transposta().produto(&qr.q),
+            &Matriz::identidade(4)
+        ));
+        for i in 0..4 {
+            for j in 0..i.min(3) {
+                assert_eq!(qr.r[(i, j)], 0.0);
+            }
+        }
+        assert_eq!(qr.posto(), 2);
+        assert_eq!(a.posto(), 2);
+
+        // O nucleo é gerado por (1, 1, -1)
+        let nucleo = a.base_nucleo();
+        assert_eq!(nucleo.len(), 1);
+        let ax = a.produto_vetor(&nucleo[0]);
+        assert!(ax.iter().all(|v| v.abs() <= TOL_TESTE));
+
+        // A solução de minimos quadrados satisfaz AᵀAx = Aᵀb
+        let b = [1.0, 0.0, 2.0, -1.0];
+        let x = qr.minimos_quadrados(&b);
+        let residuo: Vec<NumReal> = a
+            .produto_vetor(&x)
+            .iter()
+            .zip(&b)
+            .map(|(ax, b)| ax - b)
+            .collect();
+        let gradiente = a.transposta().produto_vetor(&residuo);
+        assert!(gradiente.iter().all(|v| v.abs() <= TOL_TESTE));
+    }
+
+    #[test]
+    fn cholesky_reconstroi_a_definida_positiva() {
+        let a = definida_positiva();
+        let l = a.cholesky().unwrap();
+        assert!(proximas(&l.produto(&l.transposta()), &a));
+        assert!(indefinida().cholesky().is_none());
+    }
+
+    #[test]
+    fn cholesky_modificada_so_muda_a_indefinida() {
+        // Definida positiva o bastante, E = 0 e LDLᵀ = A
+        let a = definida_positiva();
+        let modificada = a.cholesky_modificada();
+        assert!(modificada.e.iter().all(|&e| e == 0.0));
+        assert!(proximas(&modificada.matriz_modificada(), &a));
+
+        // Indefinida, LDLᵀ = A + E, com D > 0 e E ≥ 0
+        let a = indefinida();
+        let modificada = a.cholesky_modificada();
+        assert!(modificada.d.iter().all(|&d| d > 0.0));
+        assert!(modificada.e.iter().all(|&e| e >= 0.0));
+        assert!(modificada.e.iter().any(|&e| e > 0.0));
+        let mut a_mais_e = a.clone();
+        for i in 0..3 {
+            a_mais_e[(i, i)] += modificada.e[i];
+        }
+        assert!(proximas(&modificada.matriz_modificada(), &a_mais_e));
+        assert!(modificada.matriz_modificada().cholesky().is_some());
+    }
+
+    #[test]
+    fn jacobi_da_autovalores_em_ordem_e_autovetores_ortonormais() {
+        let a = indefinida();
+        let (autovalores, v) = a.autodecomposicao_simetrica();
+
+        for (autovalor, esperado) in autovalores.iter().zip([-1.0, 1.0, 3.0]) {
+            assert!((autovalor - esperado).abs() <= TOL_TESTE);
+        }
+        assert!(proximas(
+            &v.transposta().produto(&v),
+            &Matriz::identidade(3)
+        ));
+
+        // AV = VΛ
+        let mut v_lambda = v.clone();
+        for i in 0..3 {
+            for j in 0..3 {
+                v_lambda[(i, j)] *= autovalores[j];
+            }
+        }
+        assert!(proximas(&a.produto(&v), &v_lambda));
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::aleatorio::GeradorAleatorio;
+use crate::defs::{NumReal, Ponto, DIM};
+
+// Forma de sortear os pontos iniciais na caixa das variaveis
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Amostragem {
+    Uniforme,        // Pontos independentes, uniformes na caixa
+    HipercuboLatino, // Uma só amostra em cada uma das n faixas de cada variavel
+    Sobol,           // Sequencia de baixa discrepancia, com deslocamento digital aleatorio
+}
+
+impl Amostragem {
+    // Nome usado para escolher a amostragem
+    pub fn nome(&self) -> &'static str {
+        match self {
+            Amostragem::Uniforme => "uniforme",
+            Amostragem::HipercuboLatino => "hipercubo-latino",
+            Amostragem::Sobol => "sobol",
+        }
+    }
+
+    // Encontra a amostragem pelo nome
+    pub fn por_nome(nome: &str) -> Option<Amostragem> {
+        [
+            Amostragem::Uniforme,
+            Amostragem::HipercuboLatino,
+            Amostragem::Sobol,
+        ]
+        .iter()
+        .find(|amostragem| amostragem.nome() == nome)
+        .copied()
+    }
+}
+
+// Parametros (s, a, m) das direções de Sobol de cada dimensão, do grau s e
+// dos coeficientes a do polinomio primitivo, e dos m iniciais. A primeira
+// dimensão usa todos os m iguais a 1
+// (Joe e Kuo, 2008, Constructing Sobol sequences with better two-dimensional projections)
+const PARAMETROS_SOBOL: [(u32, u32, &[u32]); 6] = [
+    (0, 0, &[]),
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+];
+
+const _: () = assert!(DIM <= PARAMETROS_SOBOL.len());
+
+// Bits de cada coordenada da sequencia de Sobol
+const BITS_SOBOL: usize = 32;
+
+// Numeros de direção v_1..v_32 de uma dimensão, já deslocados para 32 bits
+fn direcoes_sobol(dimensao: usize) -> [u32; BITS_SOBOL] {
+    let mut v = [0u32; BITS_SOBOL];
+    if dimensao == 0 {
+        for (k, vk) in v.iter_mut().enumerate() {
+            *vk = 1 << (BITS_SOBOL - 1 - k);
+        }
+        return v;
+    }
+
+    let (s, a, m) = PARAMETROS_SOBOL[dimensao];
+    let s = s as usize;
+    for k in 0..BITS_SOBOL {
+        if k < s {
+            v[k] = m[k] << (BITS_SOBOL - 1 - k);
+        } else {
+            v[k] = v[k - s] ^ (v[k - s] >> s);
+            for i in 1..s {
+                if (a >> (s - 1 - i)) & 1 == 1 {
+                    v[k] ^= v[k - i];
+                }
+            }
+        }
+    }
+    v
+}
+
+// n pontos na caixa [inferior, superior], sorteados pelo gerador
+pub fn pontos_na_caixa(
+    amostragem: Amostragem,
+    n: usize,
+    inferior: &Ponto,
+    superior: &Ponto,
+    gerador: &mut GeradorAleatorio,
+) -> Vec<Ponto> {
+    // Pontos no cubo unitario, levados depois à caixa
+    let unitarios: Vec<Ponto> = match amostragem {
+        Amostragem::Uniforme => {
+            return (0..n)
+                .map(|_| gerador.ponto_na_caixa(inferior, superior))
+                .collect();
+        }
+        Amostragem::HipercuboLatino => {
+            // Cada variavel tem a sua permutação das faixas, por Fisher-Yates
+            let mut pontos = vec![[0.0; DIM]; n];
+            for i in 0..DIM {
+                let mut faixas: Vec<usize> = (0..n).collect();
+                for j in (1..n).rev() {
+                    let troca = (gerador.proximo() % (j as u64 + 1)) as usize;
+                    faixas.swap(j, troca);
+                }
+                for (ponto, &faixa) in pontos.iter_mut().zip(&faixas) {
+                    ponto[i] = (faixa as NumReal + gerador.uniforme()) / n as NumReal;
+                }
+            }
+            pontos
+        }
+        Amostragem::Sobol => {
+            // Construção pelo codigo de Gray, x_j = x_{j-1} ^ v_c, com c o
+            // indice do bit zero mais baixo de j - 1
+            let direcoes: Vec<[u32; BITS_SOBOL]> = (0..DIM).map(direcoes_sobol).collect();
+            let deslocamento: Vec<u32> =
+                (0..DIM).map(|_| (gerador.proximo() >> 32) as u32).collect();
+            let mut inteiros = [0u32; DIM];
+            let mut pontos = Vec::with_capacity(n);
+            for j in 0..n {
+                if j > 0 {
+                    let c = (j - 1).trailing_ones() as usize;
+                    for i in 0..DIM {
+                        inteiros[i] ^= direcoes[i][c];
+                    }
+                }
+                let mut ponto = [0.0; DIM];
+                for i in 0..DIM {
+                    ponto[i] = (inteiros[i] ^ deslocamento[i]) as NumReal
+                        / (1u64 << BITS_SOBOL) as NumReal;
+                }
+                pontos.push(ponto);
+            }
+            pontos
+        }
+    };
+
+    unitarios
+        .into_iter()
+        .map(|unitario| {
+            let mut ponto = [0.0; DIM];
+            for i in 0..DIM {
+                ponto[i] = inferior[i] + unitario[i] * (superior[i] - inferior[i]);
+            }
+            ponto
+        })
+        .collect()
+}
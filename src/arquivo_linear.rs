@@ -0,0 +1,417 @@
+use crate::defs::NumReal;
+use crate::matriz_esparsa::MatrizEsparsa;
+
+use std::fs;
+
+// Nome da linha da função objetivo nos arquivos
+const NOME_OBJETIVO: &str = "obj";
+
+// Subproblema linear da forma
+// min cᵀx
+// s.a.: Ax ≥ b
+// com todas as variaveis livres, junto dos nomes das linhas (1a_j, 1b_r+,
+// 1c_k-, 1d_il, ...) e das colunas (d_i, tg_j, th+_r, th-_r), para ser
+// gravado em arquivo e inspecionado ou resolvido novamente depois
+#[derive(Debug, Clone)]
+pub struct ProblemaLinearArquivo {
+    pub nome: String,
+    pub a: MatrizEsparsa,
+    pub b: Vec<NumReal>,
+    pub c: Vec<NumReal>,
+    pub nomes_linhas: Vec<String>,
+    pub nomes_colunas: Vec<String>,
+}
+
+impl ProblemaLinearArquivo {
+    // Grava no formato MPS livre. Os nomes são mantidos como estão,
+    // então o arquivo pode ser lido de volta por ler_mps
+    pub fn escrever_mps(&self, caminho: &str) -> Result<(), String> {
+        let mut texto = String::new();
+
+        texto.push_str(&format!("NAME {}\n", self.nome));
+
+        // Linha da função objetivo e uma linha ≥ (G) por linha de A
+        texto.push_str("ROWS\n");
+        texto.push_str(&format!(" N {}\n", NOME_OBJETIVO));
+        for nome in &self.nomes_linhas {
+            texto.push_str(&format!(" G {}\n", nome));
+        }
+
+        // O MPS é escrito por colunas, então as entradas de A são
+        // agrupadas por coluna antes
+        let mut colunas: Vec<Vec<(usize, NumReal)>> = vec![Vec::new(); self.a.n_colunas];
+        for i in 0..self.a.n_linhas() {
+            for (j, valor) in self.a.linha(i) {
+                colunas[j].push((i, valor));
+            }
+        }
+
+        texto.push_str("COLUMNS\n");
+        for (j, entradas) in colunas.iter().enumerate() {
+            let nome_coluna = &self.nomes_colunas[j];
+            // O custo é sempre escrito, mesmo nulo, para que toda coluna
+            // apareça em COLUMNS na ordem original
+            texto.push_str(&format!(
+                "    {} {} {:e}\n",
+                nome_coluna, NOME_OBJETIVO, self.c[j]
+            ));
+            for &(i, valor) in entradas {
+                texto.push_str(&format!(
+                    "    {} {} {:e}\n",
+                    nome_coluna, self.nomes_linhas[i], valor
+                ));
+            }
+        }
+
+        texto.push_str("RHS\n");
+        for (i, &valor) in self.b.iter().enumerate() {
+            if valor != 0.0 {
+                texto.push_str(&format!("    RHS {} {:e}\n", self.nomes_linhas[i], valor));
+            }
+        }
+
+        // Todas as variaveis são livres
+        texto.push_str("BOUNDS\n");
+        for nome_coluna in &self.nomes_colunas {
+            texto.push_str(&format!(" FR BND {}\n", nome_coluna));
+        }
+
+        texto.push_str("ENDATA\n");
+
+        fs::write(caminho, texto).map_err(|erro| format!("{}: {}", caminho, erro))
+    }
+
+    // Grava no formato LP do CPLEX. Nesse formato os nomes não podem
+    // começar por digito nem conter + e -, então as linhas ganham o
+    // prefixo r e os sinais viram p e m, como em r1b_0p e thm_0
+    pub fn escrever_lp(&self, caminho: &str) -> Result<(), String> {
+        let mut texto = String::new();
+
+        texto.push_str(&format!("\\ {}\n", self.nome));
+
+        let objetivo: Vec<(usize, NumReal)> = self
+            .c
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|&(_, valor)| valor != 0.0)
+            .collect();
+        texto.push_str("Minimize\n");
+        texto.push_str(&format!(
+            " {}: {}\n",
+            NOME_OBJETIVO,
+            self.expressao_lp(objetivo.into_iter())
+        ));
+
+        texto.push_str("Subject To\n");
+        for i in 0..self.a.n_linhas() {
+            texto.push_str(&format!(
+                " {}: {} >= {:e}\n",
+                nome_lp(&self.nomes_linhas[i]),
+                self.expressao_lp(self.a.linha(i)),
+                self.b[i] + 0.0 // Evita escrever -0
+            ));
+        }
+
+        // Todas as variaveis são livres
+        texto.push_str("Bounds\n");
+        for nome_coluna in &self.nomes_colunas {
+            texto.push_str(&format!(" {} free\n", nome_lp(nome_coluna)));
+        }
+
+        texto.push_str("End\n");
+
+        fs::write(caminho, texto).map_err(|erro| format!("{}: {}", caminho, erro))
+    }
+
+    // Grava o problema em <diretorio>/<nome>.mps e <diretorio>/<nome>.lp,
+    // criando o diretorio se necessario
+    pub fn exportar(&self, diretorio: &str) -> Result<(), String> {
+        fs::create_dir_all(diretorio).map_err(|erro| format!("{}: {}", diretorio, erro))?;
+
+        // Espaços no nome atrapalhariam tanto o caminho quanto a linha NAME
+        let nome_arquivo = self.nome.replace(' ', "_");
+        self.escrever_mps(&format!("{}/{}.mps", diretorio, nome_arquivo))?;
+        self.escrever_lp(&format!("{}/{}.lp", diretorio, nome_arquivo))
+    }
+
+    // Expressão linear no formato LP, como 2e0 d_0 - 1e0 tg_1
+    fn expressao_lp(&self, entradas: impl Iterator<Item = (usize, NumReal)>) -> String {
+        let mut expressao = String::new();
+        for (j, valor) in entradas {
+            let sinal = if valor < 0.0 { "-" } else { "+" };
+            if expressao.is_empty() && valor >= 0.0 {
+                expressao.push_str(&format!("{:e} {}", valor, nome_lp(&self.nomes_colunas[j])));
+            } else {
+                expressao.push_str(&format!(
+                    " {} {:e} {}",
+                    sinal,
+                    valor.abs(),
+                    nome_lp(&self.nomes_colunas[j])
+                ));
+            }
+        }
+
+        // Uma linha sem coeficientes ainda precisa de um termo
+        if expressao.is_empty() {
+            expressao = format!("0 {}", nome_lp(&self.nomes_colunas[0]));
+        }
+
+        expressao.trim_start().to_string()
+    }
+}
+
+// Adapta um nome para o formato LP
+fn nome_lp(nome: &str) -> String {
+    let nome = nome.replace('+', "p").replace('-', "m");
+    if nome.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("r{}", nome)
+    } else {
+        nome
+    }
+}
+
+// Lê um problema gravado por escrever_mps. São aceitas apenas linhas
+// N, G e L (estas são convertidas para G trocando o sinal) e limites
+// FR, já que o problema linear do SCP só tem variaveis livres
+pub fn ler_mps(caminho: &str) -> Result<ProblemaLinearArquivo, String> {
+    let texto = fs::read_to_string(caminho).map_err(|erro| format!("{}: {}", caminho, erro))?;
+
+    let erro =
+        |n_linha: usize, mensagem: &str| format!("{}:{}: {}", caminho, n_linha + 1, mensagem);
+
+    let mut nome = String::new();
+    let mut secao = "";
+    let mut nome_objetivo = None;
+
+    // Linhas na ordem do arquivo, com o sinal usado para levar L para G
+    let mut nomes_linhas: Vec<String> = Vec::new();
+    let mut sinais_linhas: Vec<NumReal> = Vec::new();
+
+    // Colunas na ordem em que aparecem, com suas entradas (linha, valor)
+    let mut nomes_colunas: Vec<String> = Vec::new();
+    let mut entradas: Vec<(usize, usize, NumReal)> = Vec::new();
+    let mut c: Vec<NumReal> = Vec::new();
+    let mut b: Vec<NumReal> = Vec::new();
+
+    for (n_linha, linha) in texto.lines().enumerate() {
+        if linha.trim().is_empty() || linha.starts_with('*') {
+            continue;
+        }
+
+        let campos: Vec<&str> = linha.split_whitespace().collect();
+
+        // Cabeçalhos de seção começam na primeira coluna
+        if !linha.starts_with(' ') {
+            match campos[0] {
+                "NAME" => nome = campos[1..].join(" "),
+                "ROWS" | "COLUMNS" | "RHS" | "BOUNDS" => secao = campos[0],
+                "ENDATA" => break,
+                outra => return Err(erro(n_linha, &format!("seção não suportada {}", outra))),
+            }
+            continue;
+        }
+
+        let indice_linha = |nome_linha: &str| nomes_linhas.iter().position(|n| n == nome_linha);
+        let valor = |campo: &str| {
+            campo
+                .parse::<NumReal>()
+                .map_err(|_| erro(n_linha, &format!("valor invalido {}", campo)))
+        };
+
+        match secao {
+            "ROWS" => {
+                if campos.len() != 2 {
+                    return Err(erro(n_linha, "linha de ROWS invalida"));
+                }
+                match campos[0] {
+                    "N" => nome_objetivo = Some(campos[1].to_string()),
+                    "G" | "L" => {
+                        nomes_linhas.push(campos[1].to_string());
+                        sinais_linhas.push(if campos[0] == "G" { 1.0 } else { -1.0 });
+                        b.push(0.0);
+                    }
+                    tipo => {
+                        return Err(erro(
+                            n_linha,
+                            &format!("tipo de linha não suportado {}", tipo),
+                        ))
+                    }
+                }
+            }
+
+            "COLUMNS" => {
+                if campos.len() < 3 || campos.len() % 2 == 0 {
+                    return Err(erro(n_linha, "linha de COLUMNS invalida"));
+                }
+                let j = match nomes_colunas.iter().position(|n| n == campos[0]) {
+                    Some(j) => j,
+                    None => {
+                        nomes_colunas.push(campos[0].to_string());
+                        c.push(0.0);
+                        nomes_colunas.len() - 1
+                    }
+                };
+                for par in campos[1..].chunks(2) {
+                    let v = valor(par[1])?;
+                    if Some(par[0].to_string()) == nome_objetivo {
+                        c[j] = v;
+                    } else {
+                        match indice_linha(par[0]) {
+                            Some(i) => entradas.push((i, j, sinais_linhas[i] * v)),
+                            None => {
+                                return Err(erro(
+                                    n_linha,
+                                    &format!("linha desconhecida {}", par[0]),
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+
+            "RHS" => {
+                if campos.len() < 3 || campos.len() % 2 == 0 {
+                    return Err(erro(n_linha, "linha de RHS invalida"));
+                }
+                for par in campos[1..].chunks(2) {
+                    match indice_linha(par[0]) {
+                        Some(i) => b[i] = sinais_linhas[i] * valor(par[1])?,
+                        None => {
+                            return Err(erro(n_linha, &format!("linha desconhecida {}", par[0])))
+                        }
+                    }
+                }
+            }
+
+            "BOUNDS" => {
+                if campos[0] != "FR" {
+                    return Err(erro(
+                        n_linha,
+                        &format!("limite não suportado {}", campos[0]),
+                    ));
+                }
+            }
+
+            _ => return Err(erro(n_linha, "conteudo fora de uma seção")),
+        }
+    }
+
+    // Monta A por linhas a partir das entradas lidas por colunas
+    let mut linhas: Vec<Vec<(usize, NumReal)>> = vec![Vec::new(); nomes_linhas.len()];
+    for (i, j, v) in entradas {
+        linhas[i].push((j, v));
+    }
+    let mut a = MatrizEsparsa::nova(nomes_colunas.len());
+    for linha in linhas {
+        a.adicionar_linha(&linha);
+    }
+
+    Ok(ProblemaLinearArquivo {
+        nome,
+        a,
+        b,
+        c,
+        nomes_linhas,
+        nomes_colunas,
+    })
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::defs::Configuracao;
+    use crate::instancias::todas_as_instancias;
+    use crate::matricial::{matriz_e_vetores_problema_linear, nomes_colunas_problema_linear};
+    use crate::prob_linear::SessaoProblemaLinear;
+    use crate::quase_newton::AproximacaoHessiana;
+
+    // Diretorio temporario proprio de cada teste
+    fn diretorio_temporario(nome: &str) -> String {
+        let diretorio =
+            std::env::temp_dir().join(format!("tcc_prog_{}_{}", nome, std::process::id()));
+        diretorio.to_string_lossy().into_owned()
+    }
+
+    // Subproblemas lineares do SCP de cada instancia no ponto inicial
+    fn subproblemas() -> Vec<ProblemaLinearArquivo> {
+        let hessiana = AproximacaoHessiana::nova(&Configuracao::padrao());
+        todas_as_instancias()
+            .iter()
+            .filter_map(|problema| {
+                let (a, b, c, nomes_linhas) =
+                    matriz_e_vetores_problema_linear(problema, problema.x_inicial, &[], &hessiana);
+                let finito = a.valores.iter().chain(&b).chain(&c).all(|v| v.is_finite());
+                finito.then(|| ProblemaLinearArquivo {
+                    nome: format!("{}_k0_i1", problema.nome.replace(' ', "_")),
+                    a,
+                    b,
+                    c,
+                    nomes_linhas,
+                    nomes_colunas: nomes_colunas_problema_linear(problema),
+                })
+            })
+            .collect()
+    }
+
+    fn resolver(problema_linear: &ProblemaLinearArquivo) -> (Vec<NumReal>, Vec<NumReal>) {
+        SessaoProblemaLinear::nova(&Configuracao::padrao())
+            .resolver_matriz(
+                &problema_linear.a,
+                &problema_linear.b,
+                &problema_linear.c,
+                &problema_linear.nomes_linhas,
+            )
+            .unwrap()
+    }
+
+    // O subproblema gravado e lido de volta é o mesmo, com os valores
+    // exatos, e tem a mesma solução primal e dual
+    #[test]
+    fn subproblema_gravado_e_lido_tem_a_mesma_solucao() {
+        let diretorio = diretorio_temporario("exportar");
+        for original in subproblemas() {
+            original.exportar(&diretorio).unwrap();
+
+            let lido = ler_mps(&format!("{}/{}.mps", diretorio, original.nome)).unwrap();
+            assert_eq!(lido.nome, original.nome);
+            assert_eq!(lido.nomes_linhas, original.nomes_linhas);
+            assert_eq!(lido.nomes_colunas, original.nomes_colunas);
+            assert_eq!(lido.b, original.b);
+            assert_eq!(lido.c, original.c);
+            assert_eq!(lido.a.inicio_linhas, original.a.inicio_linhas);
+            assert_eq!(lido.a.indices_colunas, original.a.indices_colunas);
+            assert_eq!(lido.a.valores, original.a.valores);
+            assert_eq!(resolver(&lido), resolver(&original), "{}", original.nome);
+
+            // O LP tem uma restrição por linha e todas as colunas livres
+            let lp = fs::read_to_string(format!("{}/{}.lp", diretorio, original.nome)).unwrap();
+            for nome_linha in &original.nomes_linhas {
+                assert!(lp.contains(&format!(" {}: ", nome_lp(nome_linha))));
+            }
+            for nome_coluna in &original.nomes_colunas {
+                assert!(lp.contains(&format!(" {} free\n", nome_lp(nome_coluna))));
+            }
+        }
+        fs::remove_dir_all(&diretorio).unwrap();
+    }
+
+    // Linhas L são levadas para G trocando o sinal
+    #[test]
+    fn linhas_menor_ou_igual_viram_maior_ou_igual() {
+        let diretorio = diretorio_temporario("ler_mps");
+        fs::create_dir_all(&diretorio).unwrap();
+        let caminho = format!("{}/l.mps", diretorio);
+        fs::write(
+            &caminho,
+            "NAME l\nROWS\n N obj\n L r0\nCOLUMNS\n    x obj 1 r0 2\nRHS\n    rhs r0 4\nBOUNDS\n FR bnd x\nENDATA\n",
+        )
+        .unwrap();
+
+        let lido = ler_mps(&caminho).unwrap();
+        assert_eq!(lido.a.linha_densa(0), vec![-2.0]);
+        assert_eq!(lido.b, vec![-4.0]);
+        assert_eq!(lido.c, vec![1.0]);
+        fs::remove_dir_all(&diretorio).unwrap();
+    }
+}
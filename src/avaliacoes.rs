@@ -0,0 +1,146 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::defs::{NumReal, Ponto, DIM};
+
+// Numero de pontos guardados no cache. Só os pontos das iterações passam
+// por ele, então poucos bastam
+const TAMANHO_CACHE: usize = 64;
+
+// Contadores das avaliações de um problema
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContadoresAvaliacao {
+    pub funcao: usize,     // Chamadas de f(x), inclusive dentro das diferenças finitas
+    pub restricoes: usize, // Chamadas de cada gⱼ(x) e hᵣ(x), ou de c(x)
+    pub gradientes: usize, // ∇f(x) calculados
+    pub jacobianas: usize, // ∇g(x) e ∇h(x) calculados juntos
+    pub hessianas: usize,  // ∇²L(x) calculadas
+    pub acertos_cache: usize, // Valores e derivadas reaproveitados do cache
+}
+
+impl ContadoresAvaliacao {
+    // Chamadas das funções do usuario, que é o que o orçamento limita
+    pub fn chamadas(&self) -> usize {
+        self.funcao + self.restricoes
+    }
+
+    pub fn mostrar(&self) {
+        println!(
+            "Avaliações: f = {}, restrições = {}, ∇f = {}, jacobianas = {}, hessianas = {}, acertos do cache = {}",
+            self.funcao,
+            self.restricoes,
+            self.gradientes,
+            self.jacobianas,
+            self.hessianas,
+            self.acertos_cache
+        );
+    }
+}
+
+// Valores e derivadas já calculados em um ponto
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntradaCache {
+    #[serde(with = "crate::reais_json")]
+    pub funcao: Option<NumReal>,
+    #[serde(with = "crate::reais_json")]
+    pub restricoes: Option<(Vec<NumReal>, Vec<NumReal>)>,
+    #[serde(with = "crate::reais_json")]
+    pub gradiente: Option<Ponto>,
+    #[serde(with = "crate::reais_json")]
+    pub jacobianas: Option<(Vec<Ponto>, Vec<Ponto>)>,
+}
+
+// Contadores e cache de um registro, com o cache na ordem de descarte,
+// para que uma resolução retomada avalie e conte como a original
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EstadoAvaliacoes {
+    pub contadores: ContadoresAvaliacao,
+    pub cache: Vec<([u64; DIM], EntradaCache)>,
+}
+
+// Cache por ponto, contadores e orçamento das avaliações de um problema,
+// compartilhado entre as copias do problema
+#[derive(Debug, Clone, Default)]
+pub struct RegistroAvaliacoes {
+    pub contadores: ContadoresAvaliacao,
+
+    // Maximo de chamadas das funções do usuario. É verificado entre as
+    // iterações, então pode ser ultrapassado pelas avaliações de uma iteração
+    pub orcamento: Option<usize>,
+
+    cache: HashMap<[u64; DIM], EntradaCache>,
+    ordem: VecDeque<[u64; DIM]>,
+}
+
+impl RegistroAvaliacoes {
+    pub fn novo() -> Self {
+        Self::default()
+    }
+
+    pub fn orcamento_esgotado(&self) -> bool {
+        match self.orcamento {
+            Some(orcamento) => self.contadores.chamadas() >= orcamento,
+            None => false,
+        }
+    }
+
+    // Procura um valor no cache, contando o acerto
+    pub fn buscar<T>(
+        &mut self,
+        x: &Ponto,
+        campo: impl FnOnce(&EntradaCache) -> Option<T>,
+    ) -> Option<T> {
+        let valor = self.cache.get(&chave(x)).and_then(campo);
+        if valor.is_some() {
+            self.contadores.acertos_cache += 1;
+        }
+        valor
+    }
+
+    // Guarda um valor no cache, descartando o ponto mais antigo se cheio
+    pub fn guardar(&mut self, x: &Ponto, preencher: impl FnOnce(&mut EntradaCache)) {
+        let chave = chave(x);
+        if !self.cache.contains_key(&chave) {
+            if self.ordem.len() == TAMANHO_CACHE {
+                if let Some(antiga) = self.ordem.pop_front() {
+                    self.cache.remove(&antiga);
+                }
+            }
+            self.ordem.push_back(chave);
+        }
+        preencher(self.cache.entry(chave).or_default());
+    }
+
+    pub fn estado(&self) -> EstadoAvaliacoes {
+        EstadoAvaliacoes {
+            contadores: self.contadores.clone(),
+            cache: self
+                .ordem
+                .iter()
+                .map(|chave| (*chave, self.cache[chave].clone()))
+                .collect(),
+        }
+    }
+
+    // Troca os contadores e o cache pelos do estado, mantendo o orçamento
+    pub fn restaurar(&mut self, estado: EstadoAvaliacoes) {
+        self.contadores = estado.contadores;
+        self.cache.clear();
+        self.ordem.clear();
+        for (chave, entrada) in estado.cache {
+            self.ordem.push_back(chave);
+            self.cache.insert(chave, entrada);
+        }
+    }
+}
+
+// Os bits do ponto, já que f64 não é Hash. Pontos iguais em ponto
+// flutuante têm os mesmos bits, exceto 0.0 e -0.0, que são separados
+fn chave(x: &Ponto) -> [u64; DIM] {
+    let mut chave = [0; DIM];
+    for i in 0..DIM {
+        chave[i] = x[i].to_bits();
+    }
+    chave
+}
@@ -0,0 +1,655 @@
+use serde::{Deserialize, Serialize};
+
+use crate::defs::NumReal;
+use crate::matriz_esparsa::MatrizEsparsa;
+
+// Backends que resolvem o problema linear da forma
+// min cᵀx
+// s.a.: Ax ≥ b
+// com todas as variaveis livres. Cada backend monta o modelo na sua
+// biblioteca, resolve, e devolve a solução primal, os valores duais
+// quando a biblioteca os fornece, e o estado da resolução.
+
+// Estado final da resolução de um problema linear
+#[derive(Debug, Clone, PartialEq)]
+pub enum EstadoProblemaLinear {
+    Otimo,
+    Inviavel,
+    Ilimitado,
+    Erro(String),
+}
+
+impl std::fmt::Display for EstadoProblemaLinear {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstadoProblemaLinear::Otimo => write!(f, "otimo"),
+            EstadoProblemaLinear::Inviavel => write!(f, "inviavel"),
+            EstadoProblemaLinear::Ilimitado => write!(f, "ilimitado"),
+            EstadoProblemaLinear::Erro(erro) => write!(f, "erro: {}", erro),
+        }
+    }
+}
+
+// Resultado da resolução por um backend
+#[derive(Debug, Clone)]
+pub struct ResultadoBackend {
+    pub estado: EstadoProblemaLinear,
+
+    // Valores das variaveis, na ordem das colunas de A. Vazio se não for otimo
+    pub x: Vec<NumReal>,
+
+    // Valores duais de cada linha de Ax ≥ b, se o backend os fornece.
+    // Sem eles, a sessão os reconstroi da solução primal por minimos
+    // quadrados não negativos nas linhas ativas, que em vertices
+    // degenerados podem ser outros multiplicadores que os do simplex
+    pub duais: Option<Vec<NumReal>>,
+}
+
+impl ResultadoBackend {
+    // Resultado sem solução, com o estado dado
+    fn sem_solucao(estado: EstadoProblemaLinear) -> Self {
+        Self {
+            estado,
+            x: Vec::new(),
+            duais: None,
+        }
+    }
+}
+
+// Interface comum dos backends de programação linear
+pub trait BackendProblemaLinear {
+    // Nome do backend, para mensagens
+    fn nome(&self) -> &'static str;
+
+    // Monta e resolve o problema min cᵀx, s.a.: Ax ≥ b
+    fn resolver(&self, a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal]) -> ResultadoBackend;
+}
+
+// Backends disponiveis, escolhidos em tempo de execução pelo nome
+// Serializados pelos mesmos nomes da linha de comando
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TipoBackendLinear {
+    Minilp,  // minilp usado diretamente
+    Microlp, // microlp (sucessor do minilp) pelo good_lp
+    #[cfg(feature = "clarabel")]
+    Clarabel, // clarabel (pontos interiores) pelo good_lp
+    #[cfg(feature = "highs")]
+    Highs, // HiGHS, compilado junto, pelo good_lp
+}
+
+impl TipoBackendLinear {
+    // Backend usado quando nenhum é escolhido: o HiGHS, quando compilado,
+    // que devolve os duais do proprio simplex, e senão o minilp. O
+    // clarabel, mesmo compilado por padrão, não é o padrão, já que por
+    // pontos interiores a solução e os duais só são exatos até a
+    // tolerancia dele, e o SCP converge pior com eles
+    pub fn padrao() -> TipoBackendLinear {
+        #[cfg(feature = "highs")]
+        return TipoBackendLinear::Highs;
+        #[cfg(not(feature = "highs"))]
+        TipoBackendLinear::Minilp
+    }
+
+    // Todos os backends compilados
+    pub fn disponiveis() -> Vec<TipoBackendLinear> {
+        vec![
+            TipoBackendLinear::Minilp,
+            TipoBackendLinear::Microlp,
+            #[cfg(feature = "clarabel")]
+            TipoBackendLinear::Clarabel,
+            #[cfg(feature = "highs")]
+            TipoBackendLinear::Highs,
+        ]
+    }
+
+    // Nome usado para escolher o backend
+    pub fn nome(&self) -> &'static str {
+        match self {
+            TipoBackendLinear::Minilp => "minilp",
+            TipoBackendLinear::Microlp => "microlp",
+            #[cfg(feature = "clarabel")]
+            TipoBackendLinear::Clarabel => "clarabel",
+            #[cfg(feature = "highs")]
+            TipoBackendLinear::Highs => "highs",
+        }
+    }
+
+    // Encontra o backend pelo nome, entre os compilados
+    pub fn por_nome(nome: &str) -> Option<TipoBackendLinear> {
+        TipoBackendLinear::disponiveis()
+            .into_iter()
+            .find(|tipo| tipo.nome() == nome)
+    }
+
+    // Cria o backend
+    pub fn criar(&self) -> Box<dyn BackendProblemaLinear> {
+        match self {
+            TipoBackendLinear::Minilp => Box::new(BackendMinilp),
+            TipoBackendLinear::Microlp => Box::new(BackendMicrolp),
+            #[cfg(feature = "clarabel")]
+            TipoBackendLinear::Clarabel => Box::new(BackendClarabel),
+            #[cfg(feature = "highs")]
+            TipoBackendLinear::Highs => Box::new(BackendHighs),
+        }
+    }
+}
+
+// minilp usado diretamente, sem valores duais
+pub struct BackendMinilp;
+
+impl BackendProblemaLinear for BackendMinilp {
+    fn nome(&self) -> &'static str {
+        "minilp"
+    }
+
+    fn resolver(&self, a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal]) -> ResultadoBackend {
+        // Biblioteca usada
+        use minilp::*;
+
+        // Gera um problema de minimização
+        let mut problema_minilp = Problem::new(OptimizationDirection::Minimize);
+
+        // TODAS as variaveis são livres, as restrições já ditam
+        // os dominios das variaveis, então não é necessário
+        // definir limites para cada uma
+        // Livre no que é possivel para o computador representar
+        let dom = (f64::NEG_INFINITY, f64::INFINITY);
+
+        // Adiciona os coeficientes de d, tᵍ, tʰ⁺ e tʰ⁻ armazenados em c,
+        // EM ORDEM, na mesma ordem das colunas de A
+        let vars: Vec<Variable> = c
+            .iter()
+            .map(|&cof| problema_minilp.add_var(cof, dom))
+            .collect();
+
+        // Para cada linha em A e em b
+        for (idx_linha, &bi) in b.iter().enumerate() {
+            // Adiciona a expressão linear como restrição relacionada ao valore de b
+            // Então
+            // A_idx_1*x_1 + A_idx_2*x_2 + A_idx_3*x_3 + ... ≥ b_idx
+            let mut expressao_linear = LinearExpr::empty();
+            for (idx_var, cof) in a.linha(idx_linha) {
+                expressao_linear.add(vars[idx_var], cof);
+            }
+            problema_minilp.add_constraint(expressao_linear, ComparisonOp::Ge, bi);
+        }
+
+        match problema_minilp.solve() {
+            Ok(s) => ResultadoBackend {
+                estado: EstadoProblemaLinear::Otimo,
+                x: vars.iter().map(|&v| s[v]).collect(),
+                duais: None,
+            },
+            Err(Error::Infeasible) => ResultadoBackend::sem_solucao(EstadoProblemaLinear::Inviavel),
+            Err(Error::Unbounded) => ResultadoBackend::sem_solucao(EstadoProblemaLinear::Ilimitado),
+        }
+    }
+}
+
+// Monta o problema no good_lp para um solver qualquer.
+// Retorna o modelo, as variaveis na ordem das colunas de A e
+// as referencias das restrições na ordem das linhas
+fn montar_good_lp<S: good_lp::Solver>(
+    solver: S,
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+) -> (
+    S::Model,
+    Vec<good_lp::Variable>,
+    Vec<good_lp::constraint::ConstraintReference>,
+) {
+    use good_lp::*;
+
+    // Variaveis livres, uma por coluna de A
+    let mut variaveis = ProblemVariables::new();
+    let vars: Vec<Variable> = c.iter().map(|_| variaveis.add(variable())).collect();
+
+    let objetivo: Expression = vars.iter().zip(c.iter()).map(|(&v, &cof)| cof * v).sum();
+    let mut modelo = variaveis.minimise(objetivo).using(solver);
+
+    // Uma restrição por linha de A, aᵢᵀx ≥ bᵢ
+    let mut restricoes = Vec::with_capacity(b.len());
+    for (idx_linha, &bi) in b.iter().enumerate() {
+        let expressao: Expression = a.linha(idx_linha).map(|(j, cof)| cof * vars[j]).sum();
+        restricoes.push(modelo.add_constraint(expressao.geq(bi)));
+    }
+
+    (modelo, vars, restricoes)
+}
+
+// Converte o erro do good_lp para o estado da resolução
+fn estado_do_erro(erro: good_lp::ResolutionError) -> EstadoProblemaLinear {
+    match erro {
+        good_lp::ResolutionError::Infeasible => EstadoProblemaLinear::Inviavel,
+        good_lp::ResolutionError::Unbounded => EstadoProblemaLinear::Ilimitado,
+        outro => EstadoProblemaLinear::Erro(outro.to_string()),
+    }
+}
+
+// Resolve pelo good_lp com um solver que não fornece valores duais
+fn resolver_good_lp_sem_duais<S>(
+    solver: S,
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+) -> ResultadoBackend
+where
+    S: good_lp::Solver,
+    S::Model: good_lp::SolverModel<Error = good_lp::ResolutionError>,
+{
+    use good_lp::{Solution, SolverModel};
+
+    let (modelo, vars, _) = montar_good_lp(solver, a, b, c);
+    match modelo.solve() {
+        Ok(s) => ResultadoBackend {
+            estado: EstadoProblemaLinear::Otimo,
+            x: vars.iter().map(|&v| s.value(v)).collect(),
+            duais: None,
+        },
+        Err(erro) => ResultadoBackend::sem_solucao(estado_do_erro(erro)),
+    }
+}
+
+// Resolve pelo good_lp com um solver que fornece valores duais.
+// Os duais do good_lp seguem a convenção de cada biblioteca, então são
+// normalizados para y ≥ 0 nas linhas de Ax ≥ b, com Aᵀy = c
+#[cfg(any(feature = "clarabel", feature = "highs"))]
+fn resolver_good_lp_com_duais<S>(
+    solver: S,
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+) -> ResultadoBackend
+where
+    S: good_lp::Solver,
+    S::Model: good_lp::SolverModel<Error = good_lp::ResolutionError>,
+    for<'a> <S::Model as good_lp::SolverModel>::Solution: good_lp::solvers::SolutionWithDual<'a>,
+{
+    use good_lp::solvers::{DualValues, SolutionWithDual};
+    use good_lp::{Solution, SolverModel};
+
+    let (modelo, vars, restricoes) = montar_good_lp(solver, a, b, c);
+    let mut s = match modelo.solve() {
+        Ok(s) => s,
+        Err(erro) => return ResultadoBackend::sem_solucao(estado_do_erro(erro)),
+    };
+
+    let x: Vec<NumReal> = vars.iter().map(|&v| s.value(v)).collect();
+    let valores_duais = s.compute_dual();
+    let mut duais: Vec<NumReal> = restricoes
+        .iter()
+        .map(|r| valores_duais.dual(r.clone()))
+        .collect();
+
+    // Escolhe o sinal que satisfaz Aᵀy = c
+    let erro_sinal = |sinal: NumReal| -> NumReal {
+        let y: Vec<NumReal> = duais.iter().map(|yi| sinal * yi).collect();
+        a.produto_transposta_vetor(&y)
+            .iter()
+            .zip(c.iter())
+            .map(|(ai, ci)| (ai - ci).abs())
+            .sum()
+    };
+    if erro_sinal(-1.0) < erro_sinal(1.0) {
+        for yi in duais.iter_mut() {
+            *yi = -*yi;
+        }
+    }
+
+    // Pequenos valores negativos são ruido numerico
+    for yi in duais.iter_mut() {
+        *yi = yi.max(0.0);
+    }
+
+    ResultadoBackend {
+        estado: EstadoProblemaLinear::Otimo,
+        x,
+        duais: Some(duais),
+    }
+}
+
+// microlp pelo good_lp, sem valores duais
+pub struct BackendMicrolp;
+
+impl BackendProblemaLinear for BackendMicrolp {
+    fn nome(&self) -> &'static str {
+        "microlp"
+    }
+
+    fn resolver(&self, a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal]) -> ResultadoBackend {
+        resolver_good_lp_sem_duais(good_lp::solvers::microlp::microlp, a, b, c)
+    }
+}
+
+// clarabel pelo good_lp, com valores duais
+#[cfg(feature = "clarabel")]
+pub struct BackendClarabel;
+
+#[cfg(feature = "clarabel")]
+impl BackendProblemaLinear for BackendClarabel {
+    fn nome(&self) -> &'static str {
+        "clarabel"
+    }
+
+    fn resolver(&self, a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal]) -> ResultadoBackend {
+        resolver_good_lp_com_duais(good_lp::solvers::clarabel::clarabel, a, b, c)
+    }
+}
+
+// HiGHS pelo good_lp, com valores duais
+#[cfg(feature = "highs")]
+pub struct BackendHighs;
+
+#[cfg(feature = "highs")]
+impl BackendProblemaLinear for BackendHighs {
+    fn nome(&self) -> &'static str {
+        "highs"
+    }
+
+    fn resolver(&self, a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal]) -> ResultadoBackend {
+        resolver_good_lp_com_duais(good_lp::solvers::highs::highs, a, b, c)
+    }
+}
+
+// Tolerancia relativa na comparação entre backends
+const TOL_CONFERENCIA: NumReal = 1e-6;
+
+// Resolve o mesmo problema com todos os backends disponiveis e compara
+// o estado e o valor objetivo com o resultado de referencia.
+// Os pontos podem diferir quando o problema tem varias soluções, então
+// apenas o valor otimo é comparado, junto da viabilidade de cada ponto.
+// Retorna as discrepancias encontradas, uma mensagem por backend
+pub fn conferir_backends(
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+    referencia: &ResultadoBackend,
+    nome_referencia: &str,
+) -> Vec<String> {
+    let valor =
+        |x: &Vec<NumReal>| -> NumReal { c.iter().zip(x.iter()).map(|(ci, xi)| ci * xi).sum() };
+
+    // Maior violação de Ax ≥ b em x, relativa a |bᵢ|
+    let violacao = |x: &Vec<NumReal>| -> NumReal {
+        (0..a.n_linhas())
+            .map(|i| {
+                let ax: NumReal = a.linha(i).map(|(j, cof)| cof * x[j]).sum();
+                ((b[i] - ax) / (1.0 + b[i].abs())).max(0.0)
+            })
+            .fold(0.0, NumReal::max)
+    };
+
+    let mut discrepancias = Vec::new();
+
+    // Os duais da referencia, quando existem, também devem dar o valor otimo
+    if referencia.estado == EstadoProblemaLinear::Otimo {
+        if let Some(duais) = &referencia.duais {
+            let by: NumReal = b.iter().zip(duais.iter()).map(|(bi, yi)| bi * yi).sum();
+            let v_ref = valor(&referencia.x);
+            if (by - v_ref).abs() > TOL_CONFERENCIA * (1.0 + v_ref.abs()) {
+                discrepancias.push(format!(
+                    "{}: valor dual {} contra valor otimo {}",
+                    nome_referencia, by, v_ref
+                ));
+            }
+        }
+    }
+
+    for tipo in TipoBackendLinear::disponiveis() {
+        if tipo.nome() == nome_referencia {
+            continue;
+        }
+
+        let resultado = tipo.criar().resolver(a, b, c);
+        if resultado.estado != referencia.estado {
+            discrepancias.push(format!(
+                "{}: estado {} contra {} de {}",
+                tipo.nome(),
+                resultado.estado,
+                referencia.estado,
+                nome_referencia
+            ));
+            continue;
+        }
+
+        if resultado.estado != EstadoProblemaLinear::Otimo {
+            continue;
+        }
+
+        let (v, v_ref) = (valor(&resultado.x), valor(&referencia.x));
+        if (v - v_ref).abs() > TOL_CONFERENCIA * (1.0 + v_ref.abs()) {
+            discrepancias.push(format!(
+                "{}: valor otimo {} contra {} de {}",
+                tipo.nome(),
+                v,
+                v_ref,
+                nome_referencia
+            ));
+        }
+
+        let inviabilidade = violacao(&resultado.x);
+        if inviabilidade > TOL_CONFERENCIA {
+            discrepancias.push(format!(
+                "{}: ponto viola Ax ≥ b em {}",
+                tipo.nome(),
+                inviabilidade
+            ));
+        }
+
+        // Pela dualidade forte, bᵀy deve ser igual ao valor otimo
+        if let Some(duais) = &resultado.duais {
+            let by: NumReal = b.iter().zip(duais.iter()).map(|(bi, yi)| bi * yi).sum();
+            if (by - v_ref).abs() > TOL_CONFERENCIA * (1.0 + v_ref.abs()) {
+                discrepancias.push(format!(
+                    "{}: valor dual {} contra valor otimo {}",
+                    tipo.nome(),
+                    by,
+                    v_ref
+                ));
+            }
+        }
+    }
+
+    discrepancias
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::defs::Configuracao;
+    use crate::instancias::todas_as_instancias;
+    use crate::matricial::matriz_e_vetores_problema_linear;
+    use crate::prob_linear::SessaoProblemaLinear;
+    use crate::quase_newton::AproximacaoHessiana;
+
+    // Tolerancia da comparação entre os backends nos testes, mais folgada
+    // que a da conferencia pelo clarabel, que só é exato até a sua tolerancia
+    const TOL_TESTE: NumReal = 1e-5;
+
+    fn proximos(a: NumReal, b: NumReal) -> bool {
+        (a - b).abs() <= TOL_TESTE * (1.0 + a.abs().max(b.abs()))
+    }
+
+    // Subproblemas lineares do SCP de cada instancia no ponto inicial: o
+    // da primeira subiteração, sem direções, e o da segunda, com as linhas
+    // da restrição 1c da direção encontrada na primeira. Ficam de fora os
+    // pontos iniciais onde alguma derivada não está definida
+    fn subproblemas() -> Vec<(String, MatrizEsparsa, Vec<NumReal>, Vec<NumReal>)> {
+        let configuracao = Configuracao::padrao();
+        let mut subproblemas = Vec::new();
+        for problema in todas_as_instancias() {
+            let hessiana = AproximacaoHessiana::nova(&configuracao);
+            let x = problema.x_inicial;
+
+            let (a, b, c, _) = matriz_e_vetores_problema_linear(&problema, x, &[], &hessiana);
+            if !a.valores.iter().chain(&b).chain(&c).all(|v| v.is_finite()) {
+                continue;
+            }
+            let resultado = TipoBackendLinear::Minilp.criar().resolver(&a, &b, &c);
+            subproblemas.push((format!("{} sem direções", problema.nome), a, b, c));
+
+            if resultado.estado == EstadoProblemaLinear::Otimo {
+                let d = [resultado.x[0], resultado.x[1]];
+                let (a, b, c, _) = matriz_e_vetores_problema_linear(&problema, x, &[d], &hessiana);
+                subproblemas.push((format!("{} com uma direção", problema.nome), a, b, c));
+            }
+        }
+        subproblemas
+    }
+
+    #[test]
+    fn backends_concordam_no_estado_e_no_valor_otimo() {
+        for (nome, a, b, c) in subproblemas() {
+            let referencia = TipoBackendLinear::Minilp.criar().resolver(&a, &b, &c);
+            for tipo in TipoBackendLinear::disponiveis() {
+                let resultado = tipo.criar().resolver(&a, &b, &c);
+                assert_eq!(
+                    resultado.estado,
+                    referencia.estado,
+                    "{}: estado de {}",
+                    nome,
+                    tipo.nome()
+                );
+                if resultado.estado != EstadoProblemaLinear::Otimo {
+                    continue;
+                }
+
+                let valor =
+                    |x: &[NumReal]| -> NumReal { c.iter().zip(x).map(|(ci, xi)| ci * xi).sum() };
+                assert!(
+                    proximos(valor(&resultado.x), valor(&referencia.x)),
+                    "{}: valor otimo {} de {} contra {} do minilp",
+                    nome,
+                    valor(&resultado.x),
+                    tipo.nome(),
+                    valor(&referencia.x)
+                );
+                assert!(
+                    conferir_backends(&a, &b, &c, &resultado, tipo.nome()).is_empty(),
+                    "{}: conferencia a partir de {}",
+                    nome,
+                    tipo.nome()
+                );
+            }
+        }
+    }
+
+    // Resolve pela sessão, que reconstroi os duais dos backends que não
+    // os fornecem
+    fn resolver_pela_sessao(
+        tipo: TipoBackendLinear,
+        a: &MatrizEsparsa,
+        b: &[NumReal],
+        c: &[NumReal],
+    ) -> (Vec<NumReal>, Vec<NumReal>) {
+        let configuracao = Configuracao {
+            backend_linear: tipo,
+            ..Configuracao::padrao()
+        };
+        let nomes_linhas: Vec<String> = (0..a.n_linhas()).map(|i| i.to_string()).collect();
+        SessaoProblemaLinear::nova(&configuracao)
+            .resolver_matriz(a, b, c, &nomes_linhas)
+            .unwrap()
+    }
+
+    // Um problema inviavel volta como erro da sessão, sem encerrar o
+    // processo, com o mesmo estado em todos os backends
+    #[test]
+    fn sessao_devolve_o_estado_do_problema_inviavel() {
+        // x₀ ≥ 1 e -x₀ ≥ 0
+        let mut a = MatrizEsparsa::nova(2);
+        a.adicionar_linha(&[(0, 1.0)]);
+        a.adicionar_linha(&[(0, -1.0)]);
+        let b = [1.0, 0.0];
+        let c = [1.0, 1.0];
+        let nomes_linhas = vec!["x ≥ 1".to_string(), "x ≤ 0".to_string()];
+        for tipo in TipoBackendLinear::disponiveis() {
+            let configuracao = Configuracao {
+                backend_linear: tipo,
+                ..Configuracao::padrao()
+            };
+            let resultado = SessaoProblemaLinear::nova(&configuracao).resolver_matriz(
+                &a,
+                &b,
+                &c,
+                &nomes_linhas,
+            );
+            assert_eq!(
+                resultado,
+                Err(EstadoProblemaLinear::Inviavel),
+                "estado de {}",
+                tipo.nome()
+            );
+        }
+    }
+
+    // Os subproblemas do SCP são degenerados, com mais linhas ativas que
+    // variaveis, então os duais otimos não são unicos e cada backend pode
+    // dar outros. Todos devem ser otimos no dual: y ≥ 0, Aᵀy = c e bᵀy
+    // igual ao valor otimo
+    #[test]
+    fn backends_dao_duais_otimos() {
+        for (nome, a, b, c) in subproblemas() {
+            let (x_ref, _) = resolver_pela_sessao(TipoBackendLinear::Minilp, &a, &b, &c);
+            let valor_otimo: NumReal = c.iter().zip(&x_ref).map(|(ci, xi)| ci * xi).sum();
+
+            for tipo in TipoBackendLinear::disponiveis() {
+                let (_, y) = resolver_pela_sessao(tipo, &a, &b, &c);
+                let escala = y.iter().fold(1.0, |acc: NumReal, yi| acc.max(yi.abs()));
+                assert!(
+                    y.iter().all(|&yi| yi >= -TOL_TESTE * escala),
+                    "{}: dual negativo de {}",
+                    nome,
+                    tipo.nome()
+                );
+                for (j, (aty, cj)) in a.produto_transposta_vetor(&y).iter().zip(&c).enumerate() {
+                    assert!(
+                        (aty - cj).abs() <= TOL_TESTE * escala,
+                        "{}: (Aᵀy)_{} de {} é {} contra c = {}",
+                        nome,
+                        j,
+                        tipo.nome(),
+                        aty,
+                        cj
+                    );
+                }
+                let by: NumReal = b.iter().zip(&y).map(|(bi, yi)| bi * yi).sum();
+                assert!(
+                    proximos(by, valor_otimo),
+                    "{}: bᵀy de {} é {} contra o valor otimo {}",
+                    nome,
+                    tipo.nome(),
+                    by,
+                    valor_otimo
+                );
+            }
+        }
+    }
+
+    // Com o vertice otimo não degenerado, o dual tem solução unica, e os
+    // duais devem ser os mesmos em todos os backends:
+    // min x₁ + x₂, s.a.: x₁ + 2x₂ ≥ 2, 2x₁ + x₂ ≥ 2, x₁ ≥ -10, x₂ ≥ -10
+    // tem otimo em (2/3, 2/3), com duais (1/3, 1/3, 0, 0)
+    #[test]
+    fn backends_concordam_nos_duais_unicos() {
+        let mut a = MatrizEsparsa::nova(2);
+        a.adicionar_linha(&[(0, 1.0), (1, 2.0)]);
+        a.adicionar_linha(&[(0, 2.0), (1, 1.0)]);
+        a.adicionar_linha(&[(0, 1.0)]);
+        a.adicionar_linha(&[(1, 1.0)]);
+        let b = [2.0, 2.0, -10.0, -10.0];
+        let c = [1.0, 1.0];
+
+        for tipo in TipoBackendLinear::disponiveis() {
+            let (x, y) = resolver_pela_sessao(tipo, &a, &b, &c);
+            for (xi, esperado) in x.iter().zip([2.0 / 3.0, 2.0 / 3.0]) {
+                assert!(proximos(*xi, esperado), "{}: x = {:?}", tipo.nome(), x);
+            }
+            for (yi, esperado) in y.iter().zip([1.0 / 3.0, 1.0 / 3.0, 0.0, 0.0]) {
+                assert!(proximos(*yi, esperado), "{}: y = {:?}", tipo.nome(), y);
+            }
+        }
+    }
+}
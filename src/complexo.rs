@@ -0,0 +1,109 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::defs::NumReal;
+
+// Numero complexo re + i·im, só com as operações necessarias para
+// escrever funções que possam ser derivadas pelo passo complexo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complexo {
+    pub re: NumReal,
+    pub im: NumReal,
+}
+
+// Nem toda instancia usa todas as funções elementares
+#[allow(dead_code)]
+impl Complexo {
+    pub fn novo(re: NumReal, im: NumReal) -> Self {
+        Self { re, im }
+    }
+
+    // Numero real visto como complexo
+    pub fn real(re: NumReal) -> Self {
+        Self { re, im: 0.0 }
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        let mut resultado = Complexo::real(1.0);
+        let base = if n < 0 {
+            Complexo::real(1.0) / self
+        } else {
+            self
+        };
+        for _ in 0..n.abs() {
+            resultado = resultado * base;
+        }
+        resultado
+    }
+
+    // e^(a + ib) = e^a (cos b + i sen b)
+    pub fn exp(self) -> Self {
+        let modulo = self.re.exp();
+        Complexo::novo(modulo * self.im.cos(), modulo * self.im.sin())
+    }
+
+    // sen(a + ib) = sen a cosh b + i cos a senh b
+    pub fn sin(self) -> Self {
+        Complexo::novo(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    // cos(a + ib) = cos a cosh b - i sen a senh b
+    pub fn cos(self) -> Self {
+        Complexo::novo(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    // Raiz principal
+    pub fn sqrt(self) -> Self {
+        let modulo = (self.re * self.re + self.im * self.im).sqrt();
+        let re = ((modulo + self.re) / 2.0).sqrt();
+        let im = ((modulo - self.re) / 2.0).sqrt();
+        Complexo::novo(re, if self.im < 0.0 { -im } else { im })
+    }
+}
+
+impl Add for Complexo {
+    type Output = Complexo;
+    fn add(self, outro: Complexo) -> Complexo {
+        Complexo::novo(self.re + outro.re, self.im + outro.im)
+    }
+}
+
+impl Sub for Complexo {
+    type Output = Complexo;
+    fn sub(self, outro: Complexo) -> Complexo {
+        Complexo::novo(self.re - outro.re, self.im - outro.im)
+    }
+}
+
+impl Mul for Complexo {
+    type Output = Complexo;
+    fn mul(self, outro: Complexo) -> Complexo {
+        Complexo::novo(
+            self.re * outro.re - self.im * outro.im,
+            self.re * outro.im + self.im * outro.re,
+        )
+    }
+}
+
+impl Div for Complexo {
+    type Output = Complexo;
+    fn div(self, outro: Complexo) -> Complexo {
+        let denominador = outro.re * outro.re + outro.im * outro.im;
+        Complexo::novo(
+            (self.re * outro.re + self.im * outro.im) / denominador,
+            (self.im * outro.re - self.re * outro.im) / denominador,
+        )
+    }
+}
+
+impl Neg for Complexo {
+    type Output = Complexo;
+    fn neg(self) -> Complexo {
+        Complexo::novo(-self.re, -self.im)
+    }
+}
@@ -0,0 +1,547 @@
+use std::sync::Arc;
+
+use crate::defs::{
+    Funcao, FuncaoComplexa, FuncaoIntervalar, FuncaoIntervalarVetorial, Gradiente, Jacobiana,
+    NumReal, Ponto, Problema, DIM,
+};
+
+// Variavel nomeada, com os limites, o chute inicial e se só pode ter
+// valores inteiros. Os limites podem ser infinitos, nas variaveis livres
+#[derive(Debug, Clone)]
+struct Variavel {
+    nome: String,
+    inferior: NumReal,
+    superior: NumReal,
+    inicial: NumReal,
+    inteira: bool,
+}
+
+// Função objetivo ou restrição nomeada, com o gradiente analitico e as
+// formas intervalar e complexa opcionais
+#[derive(Clone)]
+struct FuncaoNomeada {
+    nome: String,
+    funcao: Funcao,
+    gradiente: Option<Gradiente>,
+    intervalar: Option<FuncaoIntervalar>,
+    complexa: Option<FuncaoComplexa>,
+}
+
+// Monta um Problema por partes nomeadas, em vez dos argumentos posicionais
+// de Problema::novo. Os nomes aparecem na solução, nos multiplicadores e
+// nas qualificações. Os limites finitos das variaveis viram desigualdades,
+// e a região de confiança inicial d_l..d_u é a dada, ou, sem ela, a largura
+// da caixa das variaveis
+//
+// let p = ConstrutorProblema::novo("217")
+//     .variavel_livre("x", 10.0)
+//     .variavel_livre("y", 10.0)
+//     .regiao_de_confianca([-100.0, -100.0], [100.0, 100.0])
+//     .objetivo("-y", Arc::new(|x: Ponto| -x[1]))
+//     .igualdade("circulo", Arc::new(|x: Ponto| x[0] * x[0] + x[1] * x[1] - 1.0))
+//     .solucao([0.6, 0.8])
+//     .construir()?;
+#[derive(Clone)]
+pub struct ConstrutorProblema {
+    nome: String,
+    variaveis: Vec<Variavel>,
+    objetivo: Option<FuncaoNomeada>,
+    desigualdades: Vec<FuncaoNomeada>,
+    igualdades: Vec<FuncaoNomeada>,
+    solucao: Option<Ponto>,
+    regiao_de_confianca: Option<(Ponto, Ponto)>,
+    intervalares: Vec<(String, FuncaoIntervalar)>,
+    complexas: Vec<(String, FuncaoComplexa)>,
+}
+
+impl ConstrutorProblema {
+    pub fn novo(nome: &str) -> Self {
+        Self {
+            nome: nome.to_string(),
+            variaveis: Vec::new(),
+            objetivo: None,
+            desigualdades: Vec::new(),
+            igualdades: Vec::new(),
+            solucao: None,
+            regiao_de_confianca: None,
+            intervalares: Vec::new(),
+            complexas: Vec::new(),
+        }
+    }
+
+    // As variaveis são adicionadas na ordem das componentes do Ponto
+    pub fn variavel(
+        mut self,
+        nome: &str,
+        inferior: NumReal,
+        superior: NumReal,
+        inicial: NumReal,
+    ) -> Self {
+        self.variaveis.push(Variavel {
+            nome: nome.to_string(),
+            inferior,
+            superior,
+            inicial,
+            inteira: false,
+        });
+        self
+    }
+
+    // Variavel sem limites, como nos problemas montados por Problema::novo,
+    // que precisa da região de confiança dada
+    pub fn variavel_livre(self, nome: &str, inicial: NumReal) -> Self {
+        self.variavel(nome, NumReal::NEG_INFINITY, NumReal::INFINITY, inicial)
+    }
+
+    // Variavel inteira, como uma contagem ou um tamanho de uma lista
+    // discreta, tratada pela ramificação inteira
+    #[allow(dead_code)]
+    pub fn variavel_inteira(
+        mut self,
+        nome: &str,
+        inferior: NumReal,
+        superior: NumReal,
+        inicial: NumReal,
+    ) -> Self {
+        self.variaveis.push(Variavel {
+            nome: nome.to_string(),
+            inferior,
+            superior,
+            inicial,
+            inteira: true,
+        });
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn objetivo(mut self, nome: &str, funcao: Funcao) -> Self {
+        self.objetivo = Some(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: None,
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    pub fn objetivo_com_gradiente(
+        mut self,
+        nome: &str,
+        funcao: Funcao,
+        gradiente: Gradiente,
+    ) -> Self {
+        self.objetivo = Some(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: Some(gradiente),
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    // Restrição g(x) ≤ 0
+    #[allow(dead_code)]
+    pub fn desigualdade(mut self, nome: &str, funcao: Funcao) -> Self {
+        self.desigualdades.push(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: None,
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    pub fn desigualdade_com_gradiente(
+        mut self,
+        nome: &str,
+        funcao: Funcao,
+        gradiente: Gradiente,
+    ) -> Self {
+        self.desigualdades.push(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: Some(gradiente),
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    // Restrição h(x) = 0
+    #[allow(dead_code)]
+    pub fn igualdade(mut self, nome: &str, funcao: Funcao) -> Self {
+        self.igualdades.push(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: None,
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    pub fn igualdade_com_gradiente(
+        mut self,
+        nome: &str,
+        funcao: Funcao,
+        gradiente: Gradiente,
+    ) -> Self {
+        self.igualdades.push(FuncaoNomeada {
+            nome: nome.to_string(),
+            funcao,
+            gradiente: Some(gradiente),
+            intervalar: None,
+            complexa: None,
+        });
+        self
+    }
+
+    // Forma intervalar da função de nome dado, objetivo ou restrição, para
+    // a busca global
+    pub fn intervalar(mut self, nome: &str, forma: FuncaoIntervalar) -> Self {
+        self.intervalares.push((nome.to_string(), forma));
+        self
+    }
+
+    // Forma complexa da função de nome dado, objetivo ou restrição, para
+    // as derivadas pelo passo complexo
+    pub fn complexa(mut self, nome: &str, forma: FuncaoComplexa) -> Self {
+        self.complexas.push((nome.to_string(), forma));
+        self
+    }
+
+    // Solução conhecida, para comparar com a encontrada
+    pub fn solucao(mut self, solucao: Ponto) -> Self {
+        self.solucao = Some(solucao);
+        self
+    }
+
+    // Região de confiança inicial d_l ≤ d ≤ d_u das direções, no lugar da
+    // largura da caixa das variaveis
+    pub fn regiao_de_confianca(mut self, d_l: Ponto, d_u: Ponto) -> Self {
+        self.regiao_de_confianca = Some((d_l, d_u));
+        self
+    }
+
+    // Verifica as partes e monta o problema
+    pub fn construir(mut self) -> Result<Problema, String> {
+        if self.variaveis.len() != DIM {
+            return Err(format!(
+                "O problema {} tem {} variaveis, mas a dimensão é {}",
+                self.nome,
+                self.variaveis.len(),
+                DIM
+            ));
+        }
+
+        let mut objetivo = match self.objetivo {
+            Some(objetivo) => objetivo,
+            None => return Err(format!("O problema {} não tem função objetivo", self.nome)),
+        };
+
+        // Os limites finitos das variaveis viram desigualdades sobre x, e a
+        // região de confiança das direções é a dada ou, sem ela, começa do
+        // tamanho da caixa, o maior passo que não sai dela
+        let mut inferior = [0.0; DIM];
+        let mut superior = [0.0; DIM];
+        let mut d_l = [0.0; DIM];
+        let mut d_u = [0.0; DIM];
+        let mut x_inicial = [0.0; DIM];
+        let mut variaveis_inteiras = [false; DIM];
+        for (i, variavel) in self.variaveis.iter().enumerate() {
+            if variavel.inferior.is_nan()
+                || variavel.superior.is_nan()
+                || variavel.inferior >= variavel.superior
+                || variavel.inferior == NumReal::INFINITY
+                || variavel.superior == NumReal::NEG_INFINITY
+            {
+                return Err(format!(
+                    "A variavel {} tem limites invalidos {} e {}",
+                    variavel.nome, variavel.inferior, variavel.superior
+                ));
+            }
+            if !variavel.inicial.is_finite()
+                || !(variavel.inferior..=variavel.superior).contains(&variavel.inicial)
+            {
+                return Err(format!(
+                    "O chute inicial {} da variavel {} está fora dos limites {} e {}",
+                    variavel.inicial, variavel.nome, variavel.inferior, variavel.superior
+                ));
+            }
+            inferior[i] = variavel.inferior;
+            superior[i] = variavel.superior;
+            match self.regiao_de_confianca {
+                Some((regiao_l, regiao_u)) => {
+                    d_l[i] = regiao_l[i];
+                    d_u[i] = regiao_u[i];
+                }
+                None if (variavel.superior - variavel.inferior).is_finite() => {
+                    d_u[i] = variavel.superior - variavel.inferior;
+                    d_l[i] = -d_u[i];
+                }
+                None => {
+                    return Err(format!(
+                        "A variavel {} não tem limites finitos, e o problema {} não tem região de confiança",
+                        variavel.nome, self.nome
+                    ))
+                }
+            }
+            x_inicial[i] = variavel.inicial;
+            variaveis_inteiras[i] = variavel.inteira;
+        }
+
+        let nomes_variaveis: Vec<String> = self.variaveis.iter().map(|v| v.nome.clone()).collect();
+        repetido(&nomes_variaveis, "variavel")?;
+
+        let mut nomes_restricoes: Vec<String> =
+            self.desigualdades.iter().map(|r| r.nome.clone()).collect();
+        nomes_restricoes.extend(self.igualdades.iter().map(|r| r.nome.clone()));
+        repetido(&nomes_restricoes, "restrição")?;
+
+        // Cada forma intervalar ou complexa vai para a função do mesmo nome
+        for (nome, forma) in std::mem::take(&mut self.intervalares) {
+            let funcao = funcao_de_nome(
+                &mut objetivo,
+                &mut self.desigualdades,
+                &mut self.igualdades,
+                &nome,
+            )
+            .ok_or(format!(
+                "Forma intervalar de uma função que não existe: {}",
+                nome
+            ))?;
+            funcao.intervalar = Some(forma);
+        }
+        for (nome, forma) in std::mem::take(&mut self.complexas) {
+            let funcao = funcao_de_nome(
+                &mut objetivo,
+                &mut self.desigualdades,
+                &mut self.igualdades,
+                &nome,
+            )
+            .ok_or(format!(
+                "Forma complexa de uma função que não existe: {}",
+                nome
+            ))?;
+            funcao.complexa = Some(forma);
+        }
+
+        let jacobiana_desigualdades = jacobiana_das_restricoes(&self.desigualdades)?;
+        let jacobiana_igualdades = jacobiana_das_restricoes(&self.igualdades)?;
+
+        let mut problema = Problema::novo(
+            objetivo.funcao,
+            self.desigualdades
+                .iter()
+                .map(|r| r.funcao.clone())
+                .collect(),
+            self.igualdades.iter().map(|r| r.funcao.clone()).collect(),
+            d_l,
+            d_u,
+            x_inicial,
+            self.solucao,
+            self.nome,
+        );
+
+        problema.gradiente_objetivo = objetivo.gradiente;
+        problema.jacobiana_desigualdades = jacobiana_desigualdades;
+        problema.jacobiana_igualdades = jacobiana_igualdades;
+
+        problema.variaveis_inteiras = variaveis_inteiras;
+
+        problema.objetivo_intervalar = objetivo.intervalar;
+        problema.desigualdades_intervalares = formas_das_restricoes(&self.desigualdades)?;
+        problema.igualdades_intervalares = formas_das_restricoes(&self.igualdades)?;
+
+        problema.objetivo_complexo = objetivo.complexa;
+        problema.desigualdades_complexas = self
+            .desigualdades
+            .iter()
+            .map(|r| r.complexa.clone())
+            .collect();
+        problema.igualdades_complexas =
+            self.igualdades.iter().map(|r| r.complexa.clone()).collect();
+
+        problema.nome_objetivo = objetivo.nome;
+        problema.nomes_variaveis = nomes_variaveis;
+        problema.nomes_desigualdades = self.desigualdades.into_iter().map(|r| r.nome).collect();
+        problema.nomes_igualdades = self.igualdades.into_iter().map(|r| r.nome).collect();
+
+        Ok(problema.com_limites_variaveis(&inferior, &superior))
+    }
+}
+
+// Objetivo ou restrição com o nome dado
+fn funcao_de_nome<'a>(
+    objetivo: &'a mut FuncaoNomeada,
+    desigualdades: &'a mut [FuncaoNomeada],
+    igualdades: &'a mut [FuncaoNomeada],
+    nome: &str,
+) -> Option<&'a mut FuncaoNomeada> {
+    if objetivo.nome == nome {
+        return Some(objetivo);
+    }
+    desigualdades
+        .iter_mut()
+        .chain(igualdades.iter_mut())
+        .find(|r| r.nome == nome)
+}
+
+fn repetido(nomes: &[String], tipo: &str) -> Result<(), String> {
+    for (k, nome) in nomes.iter().enumerate() {
+        if nomes[..k].contains(nome) {
+            return Err(format!("Nome de {} repetido: {}", tipo, nome));
+        }
+    }
+    Ok(())
+}
+
+// Junta os gradientes das restrições em uma jacobiana. O problema só tem
+// uma jacobiana por familia, então os gradientes são de todas ou de nenhuma
+fn jacobiana_das_restricoes(restricoes: &[FuncaoNomeada]) -> Result<Option<Jacobiana>, String> {
+    let gradientes: Vec<Gradiente> = restricoes
+        .iter()
+        .filter_map(|r| r.gradiente.clone())
+        .collect();
+
+    if gradientes.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(sem_gradiente) = restricoes.iter().find(|r| r.gradiente.is_none()) {
+        return Err(format!(
+            "A restrição {} não tem gradiente, mas outras da mesma familia têm",
+            sem_gradiente.nome
+        ));
+    }
+
+    Ok(Some(Arc::new(move |x: Ponto| {
+        gradientes.iter().map(|gradiente| gradiente(x)).collect()
+    })))
+}
+
+// Junta as formas intervalares das restrições, também de todas ou de nenhuma
+fn formas_das_restricoes(
+    restricoes: &[FuncaoNomeada],
+) -> Result<Option<FuncaoIntervalarVetorial>, String> {
+    let formas: Vec<FuncaoIntervalar> = restricoes
+        .iter()
+        .filter_map(|r| r.intervalar.clone())
+        .collect();
+
+    if formas.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(sem_forma) = restricoes.iter().find(|r| r.intervalar.is_none()) {
+        return Err(format!(
+            "A restrição {} não tem forma intervalar, mas outras da mesma familia têm",
+            sem_forma.nome
+        ));
+    }
+
+    Ok(Some(Arc::new(move |caixa| {
+        formas.iter().map(|forma| forma(caixa)).collect()
+    })))
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::defs::Configuracao;
+    use crate::lote::instancias_com_partidas;
+
+    fn construtor(inicial: NumReal) -> ConstrutorProblema {
+        ConstrutorProblema::novo("caixa")
+            .variavel("x", -10.0, 10.0, inicial)
+            .variavel("y", 0.0, 1.0, 0.5)
+            .objetivo("soma", Arc::new(|x: Ponto| x[0] + x[1]))
+    }
+
+    #[test]
+    fn limites_viram_desigualdades_e_nao_regiao_de_confianca() {
+        let problema = construtor(5.0).construir().unwrap();
+
+        assert_eq!(problema.limites_inferiores, [-10.0, 0.0]);
+        assert_eq!(problema.limites_superiores, [10.0, 1.0]);
+        assert_eq!(problema.d_l, [-20.0, -1.0]);
+        assert_eq!(problema.d_u, [20.0, 1.0]);
+
+        // x ≥ -10, x ≤ 10, y ≥ 0 e y ≤ 1, todas satisfeitas no chute inicial
+        let (g, _) = problema.avaliar_restricoes([5.0, 0.5]);
+        assert_eq!(g, vec![-15.0, -5.0, -0.5, -0.5]);
+    }
+
+    // Variavel livre não ganha desigualdade, e a região de confiança é a
+    // dada, como nos problemas de Problema::novo
+    #[test]
+    fn variavel_livre_usa_a_regiao_de_confianca_dada() {
+        let problema = ConstrutorProblema::novo("livre")
+            .variavel_livre("x", 15.0)
+            .variavel("y", 0.0, NumReal::INFINITY, 7.5)
+            .regiao_de_confianca([-4.0, -4.0], [4.0, 4.0])
+            .objetivo("soma", Arc::new(|x: Ponto| x[0] + x[1]))
+            .construir()
+            .unwrap();
+
+        assert_eq!(problema.x_inicial, [15.0, 7.5]);
+        assert_eq!(problema.d_l, [-4.0, -4.0]);
+        assert_eq!(problema.d_u, [4.0, 4.0]);
+
+        // Só y ≥ 0
+        let (g, _) = problema.avaliar_restricoes([15.0, 7.5]);
+        assert_eq!(g, vec![-7.5]);
+    }
+
+    #[test]
+    fn variavel_livre_sem_regiao_de_confianca_e_recusada() {
+        let construtor = ConstrutorProblema::novo("livre")
+            .variavel_livre("x", 0.0)
+            .variavel("y", 0.0, 1.0, 0.5)
+            .objetivo("soma", Arc::new(|x: Ponto| x[0] + x[1]));
+        assert!(construtor.construir().is_err());
+    }
+
+    #[test]
+    fn chute_inicial_fora_dos_limites_e_recusado() {
+        assert!(construtor(15.0).construir().is_err());
+    }
+
+    // O clone divide o registro de avaliações com o original, e a copia
+    // independente começa com um registro novo
+    #[test]
+    fn clone_divide_o_registro_e_copia_independente_nao() {
+        let problema = construtor(5.0).construir().unwrap();
+        let clone = problema.clone();
+        let independente = problema.copia_independente();
+
+        clone.avaliar_objetivo([1.0, 0.5]);
+        assert_eq!(problema.contadores_avaliacao().funcao, 1);
+
+        independente.avaliar_objetivo([2.0, 0.5]);
+        assert_eq!(problema.contadores_avaliacao().funcao, 1);
+        assert_eq!(independente.contadores_avaliacao().funcao, 1);
+    }
+
+    // As partidas sorteadas ficam dentro dos limites das variaveis, e não
+    // na caixa d_l..d_u dos passos, que é mais larga
+    #[test]
+    fn partidas_sorteadas_respeitam_os_limites() {
+        let configuracao = Configuracao {
+            partidas: 50,
+            ..Configuracao::padrao()
+        };
+        let problema = construtor(5.0).construir().unwrap();
+        let instancias = instancias_com_partidas(vec![problema], &configuracao);
+
+        assert_eq!(instancias.len(), 50);
+        for instancia in &instancias {
+            let x = instancia.x_inicial;
+            assert!((-10.0..=10.0).contains(&x[0]), "{:?}", x);
+            assert!((0.0..=1.0).contains(&x[1]), "{:?}", x);
+        }
+    }
+}
@@ -1,7 +1,22 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::amostragem::Amostragem;
+use crate::avaliacoes::{ContadoresAvaliacao, EntradaCache, EstadoAvaliacoes, RegistroAvaliacoes};
+use crate::backend_linear::TipoBackendLinear;
+use crate::complexo::Complexo;
+use crate::diferencas_finitas::{ModoDiferenca, OpcoesDiferencas};
+use crate::estimativa_mul_lagrange::EstimadorMultiplicadores;
+use crate::intervalo::Intervalo;
+use crate::quase_newton::TipoHessiana;
+use crate::ramificacao_inteira::SelecaoNos;
+use crate::regularizacao_hessiana::RegularizacaoHessiana;
+use crate::resultado::FormatoResultados;
+
 // Considerando isso como infinitesimal
 pub const DBL_EPS: f64 = 1e-1;
-pub const DDBL_EPS: f64 = 2e-1;
-pub const DBL_EPS2: f64 = 1e-2;
 
 // Dimensão do self, R^DIM
 pub const DIM: usize = 2;
@@ -9,20 +24,6 @@ pub const DIM: usize = 2;
 // Constante de inviabilidade das iterações lineares
 pub const C: NumReal = 10.0;
 
-// Constante de definição das ellipses das restrições de desigualdades
-pub const A: NumReal = 1.0;
-pub const B: NumReal = 1.0;
-pub const CC: NumReal = 1.0;
-pub const D: NumReal = -0.4;
-pub const E: NumReal = 2.7182818;
-pub const F: NumReal = -9.0;
-
-pub const O1: NumReal = 2.0;
-pub const O2: NumReal = 2.0;
-
-pub const L1: NumReal = 4.0;
-pub const L2: NumReal = 4.0;
-
 // Constante ρ
 pub const RHO: NumReal = 0.7055;
 
@@ -40,15 +41,63 @@ pub const LINE_SEARCH_INC: NumReal = 0.001;
 pub const SIGMA_MERITO: NumReal = 0.5;
 pub const ETA_MERITO: NumReal = 0.75;
 
+// Numero de pares (s, y) guardados pelo L-BFGS
+pub const MEMORIA_LBFGS: usize = 5;
+
+// Tolerancias padrão dos residuos KKT
+pub const TOL_KKT_ABSOLUTA: NumReal = 1e-6;
+pub const TOL_KKT_RELATIVA: NumReal = 1e-6;
+
+// Semente padrão do sorteio dos pontos iniciais, para que as partidas
+// sejam as mesmas em toda execução
+pub const SEMENTE_PARTIDAS: u64 = 47;
+
+// Partidas da busca multipartida quando o --partidas não é dado
+pub const PARTIDAS_MULTIPARTIDA: usize = 10 * DIM;
+
+// Lacuna aceita entre as cotas da busca global, relativa a max(1, |f*|),
+// e maximo de caixas examinadas
+pub const TOL_GLOBAL: NumReal = 1e-4;
+pub const MAXIMO_CAIXAS: usize = 100_000;
+
+// Maximo de nós da ramificação inteira, cada um uma resolução pelo SCP
+pub const MAXIMO_NOS: usize = 10_000;
+
 // Aliases de tipo, pra facilitar o entendimento
 pub type NumReal = f64;
-pub type Funcao = fn(Ponto) -> NumReal; // o tipo Funcao é um ponteiro de uma função de recebe um Ponto e retorna um NumReal
+// As funções são closures compartilhadas, que podem capturar dados do
+// problema (parametros, conjuntos de dados, restrições geradas em laços),
+// e podem ser copiadas entre problemas e threads
+pub type Funcao = Arc<dyn Fn(Ponto) -> NumReal + Send + Sync>; // Função que recebe um Ponto e retorna um NumReal
+pub type Gradiente = Arc<dyn Fn(Ponto) -> Ponto + Send + Sync>; // Gradiente analitico de uma Funcao
+pub type Jacobiana = Arc<dyn Fn(Ponto) -> Vec<Ponto> + Send + Sync>; // Gradientes analiticos de uma lista de restrições, um por linha
+pub type FuncaoVetorial = Arc<dyn Fn(Ponto) -> Vec<NumReal> + Send + Sync>; // Função que recebe um Ponto e retorna varios NumReal
 
 // Definição de um ponto
 pub type Ponto = [NumReal; DIM];
 
-// Estrutura do self
-#[derive(Clone, Debug)]
+// Caixa de variaveis, um intervalo por componente, e formas intervalares
+// das funções, que contêm os valores da função em todos os pontos da caixa
+pub type Caixa = [Intervalo; DIM];
+pub type FuncaoIntervalar = Arc<dyn Fn(Caixa) -> Intervalo + Send + Sync>;
+pub type FuncaoIntervalarVetorial = Arc<dyn Fn(Caixa) -> Vec<Intervalo> + Send + Sync>;
+
+// Mesma função escrita sobre numeros complexos, para o passo complexo
+pub type FuncaoComplexa = Arc<dyn Fn([Complexo; DIM]) -> Complexo + Send + Sync>;
+
+// Restrições calculadas todas de uma vez, c(x) = (g(x), h(x)), para
+// modelos em que uma só avaliação cara dá todas as restrições
+#[derive(Clone)]
+pub struct RestricoesVetoriais {
+    pub funcao: FuncaoVetorial, // c(x), com as m_i desigualdades seguidas das m_e igualdades
+    pub jacobiana: Option<Jacobiana>, // ∇c(x) opcional, uma linha por restrição na mesma ordem
+    pub mi: usize,
+    pub me: usize,
+}
+
+// Estrutura do self. O clone divide o registro de avaliações com o
+// original, como copia_compartilhada; copia_independente dá um registro
+// novo
 pub struct Problema {
     pub funcao_objetivo: Funcao, // A função objetivo que quer ser minimizada
     pub restricoes_igualdades: Vec<Funcao>, // A lista das m_e funções restrições de igualdades, onde h_r(x) == 0, para r = 1, ..., m_e
@@ -61,16 +110,95 @@ pub struct Problema {
     pub d_l: Ponto,
     pub d_u: Ponto,
 
+    // Limites das variaveis lᵢ ≤ xᵢ ≤ uᵢ, que já estão entre as
+    // desigualdades, impostos por com_limites_variaveis. Infinitos nas
+    // variaveis livres
+    pub limites_inferiores: Ponto,
+    pub limites_superiores: Ponto,
+
     // Chute inicial
     pub x_inicial: Ponto,
 
     // Solução e nome
     pub solucao: Option<Ponto>,
     pub nome: String,
+
+    // Opções das derivadas por diferenças finitas
+    pub diferencas: OpcoesDiferencas,
+
+    // Derivadas analiticas opcionais. Quando ausentes, são calculadas
+    // por diferenças finitas. A linha j da jacobiana é o gradiente da
+    // restrição j, na mesma ordem das listas de restrições
+    pub gradiente_objetivo: Option<Gradiente>,
+    pub jacobiana_desigualdades: Option<Jacobiana>,
+    pub jacobiana_igualdades: Option<Jacobiana>,
+
+    // Alternativa às listas de restrições, que então ficam vazias
+    pub restricoes_vetoriais: Option<RestricoesVetoriais>,
+
+    // Formas intervalares opcionais, usadas só pela busca global. As das
+    // restrições dão uma caixa por restrição, na mesma ordem das listas
+    pub objetivo_intervalar: Option<FuncaoIntervalar>,
+    pub desigualdades_intervalares: Option<FuncaoIntervalarVetorial>,
+    pub igualdades_intervalares: Option<FuncaoIntervalarVetorial>,
+
+    // Formas complexas opcionais, usadas pelas diferenças no modo de passo
+    // complexo. As das restrições seguem a ordem das listas, e as
+    // restrições sem forma, ou além do fim da lista, usam diferenças centrais
+    pub objetivo_complexo: Option<FuncaoComplexa>,
+    pub desigualdades_complexas: Vec<Option<FuncaoComplexa>>,
+    pub igualdades_complexas: Vec<Option<FuncaoComplexa>>,
+
+    // Variaveis que só podem ter valores inteiros, respeitadas só pela
+    // ramificação inteira. O SCP resolve sempre a relaxação continua
+    pub variaveis_inteiras: [bool; DIM],
+
+    // Nomes para os relatorios. Listas vazias, ou mais curtas, usam os
+    // nomes padrão x_i, g_j e h_r
+    pub nome_objetivo: String,
+    pub nomes_variaveis: Vec<String>,
+    pub nomes_desigualdades: Vec<String>,
+    pub nomes_igualdades: Vec<String>,
+
+    // Cache, contadores e orçamento das avaliações, divididos só com as
+    // copias compartilhadas
+    avaliacoes: Arc<Mutex<RegistroAvaliacoes>>,
+}
+
+// O clone divide as funções, que são Arc, e o registro de avaliações,
+// então as avaliações da copia contam no cache, nos contadores e no
+// orçamento do original
+impl Clone for Problema {
+    fn clone(&self) -> Self {
+        self.copia_compartilhada()
+    }
+}
+
+// As closures não têm Debug, então são mostradas só as quantidades
+impl fmt::Debug for Problema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Problema")
+            .field("nome", &self.nome)
+            .field("mi", &self.mi())
+            .field("me", &self.me())
+            .field("d_l", &self.d_l)
+            .field("d_u", &self.d_u)
+            .field("limites_inferiores", &self.limites_inferiores)
+            .field("limites_superiores", &self.limites_superiores)
+            .field("x_inicial", &self.x_inicial)
+            .field("solucao", &self.solucao)
+            .field("diferencas", &self.diferencas)
+            .field("derivadas_analiticas", &self.tem_derivadas_analiticas())
+            .field("formas_intervalares", &self.tem_formas_intervalares())
+            .field("variaveis_inteiras", &self.variaveis_inteiras)
+            .field("avaliacoes", &self.contadores_avaliacao())
+            .finish()
+    }
 }
 
 impl Problema {
     // Retorna um novo problema preenchido a partir do mínimo
+    #[allow(clippy::too_many_arguments)]
     pub fn novo(
         funcao_objetivo: Funcao,
         restricoes_desigualdades: Vec<Funcao>,
@@ -87,9 +215,474 @@ impl Problema {
             restricoes_desigualdades,
             d_l,
             d_u,
+            limites_inferiores: [NumReal::NEG_INFINITY; DIM],
+            limites_superiores: [NumReal::INFINITY; DIM],
+            x_inicial,
+            solucao,
+            nome,
+            diferencas: OpcoesDiferencas::padrao(),
+            gradiente_objetivo: None,
+            jacobiana_desigualdades: None,
+            jacobiana_igualdades: None,
+            restricoes_vetoriais: None,
+            objetivo_intervalar: None,
+            desigualdades_intervalares: None,
+            igualdades_intervalares: None,
+            objetivo_complexo: None,
+            desigualdades_complexas: Vec::new(),
+            igualdades_complexas: Vec::new(),
+            variaveis_inteiras: [false; DIM],
+            nome_objetivo: "f".into(),
+            nomes_variaveis: Vec::new(),
+            nomes_desigualdades: Vec::new(),
+            nomes_igualdades: Vec::new(),
+            avaliacoes: Arc::new(Mutex::new(RegistroAvaliacoes::novo())),
+        }
+    }
+
+    // Retorna um novo problema com as restrições dadas por uma só função vetorial
+    #[allow(dead_code)]
+    pub fn novo_vetorial(
+        funcao_objetivo: Funcao,
+        restricoes: RestricoesVetoriais,
+        d_l: Ponto,
+        d_u: Ponto,
+        x_inicial: Ponto,
+        solucao: Option<Ponto>,
+        nome: String,
+    ) -> Self {
+        let mut problema = Problema::novo(
+            funcao_objetivo,
+            Vec::new(),
+            Vec::new(),
+            d_l,
+            d_u,
             x_inicial,
             solucao,
             nome,
+        );
+        problema.restricoes_vetoriais = Some(restricoes);
+        problema
+    }
+
+    // Copia com um registro de avaliações proprio, vazio, para ser resolvida
+    // sem dividir cache, contadores e orçamento com o original
+    pub fn copia_independente(&self) -> Self {
+        self.copia_com_registro(Arc::new(Mutex::new(RegistroAvaliacoes::novo())))
+    }
+
+    // Copia que divide o registro de avaliações com o original, para as
+    // funções auxiliares, como as de merito e as lagrangianas, cujas
+    // avaliações contam na resolução do original
+    pub fn copia_compartilhada(&self) -> Self {
+        self.copia_com_registro(self.avaliacoes.clone())
+    }
+
+    fn copia_com_registro(&self, avaliacoes: Arc<Mutex<RegistroAvaliacoes>>) -> Self {
+        Self {
+            funcao_objetivo: self.funcao_objetivo.clone(),
+            restricoes_igualdades: self.restricoes_igualdades.clone(),
+            restricoes_desigualdades: self.restricoes_desigualdades.clone(),
+            d_l: self.d_l,
+            d_u: self.d_u,
+            limites_inferiores: self.limites_inferiores,
+            limites_superiores: self.limites_superiores,
+            x_inicial: self.x_inicial,
+            solucao: self.solucao,
+            nome: self.nome.clone(),
+            diferencas: self.diferencas,
+            gradiente_objetivo: self.gradiente_objetivo.clone(),
+            jacobiana_desigualdades: self.jacobiana_desigualdades.clone(),
+            jacobiana_igualdades: self.jacobiana_igualdades.clone(),
+            restricoes_vetoriais: self.restricoes_vetoriais.clone(),
+            objetivo_intervalar: self.objetivo_intervalar.clone(),
+            desigualdades_intervalares: self.desigualdades_intervalares.clone(),
+            igualdades_intervalares: self.igualdades_intervalares.clone(),
+            objetivo_complexo: self.objetivo_complexo.clone(),
+            desigualdades_complexas: self.desigualdades_complexas.clone(),
+            igualdades_complexas: self.igualdades_complexas.clone(),
+            variaveis_inteiras: self.variaveis_inteiras,
+            nome_objetivo: self.nome_objetivo.clone(),
+            nomes_variaveis: self.nomes_variaveis.clone(),
+            nomes_desigualdades: self.nomes_desigualdades.clone(),
+            nomes_igualdades: self.nomes_igualdades.clone(),
+            avaliacoes,
+        }
+    }
+
+    // Copia independente com os limites finitos lᵢ ≤ xᵢ ≤ uᵢ acrescentados
+    // ao fim das desigualdades, como xᵢ - uᵢ ≤ 0 e lᵢ - xᵢ ≤ 0, junto com
+    // os gradientes, as formas intervalares e os nomes. Os limites infinitos,
+    // ou que não apertam os que o problema já tem, não viram restrições
+    pub fn com_limites_variaveis(&self, inferior: &Ponto, superior: &Ponto) -> Self {
+        // (i, sinal, limite) de cada restrição sinal·(xᵢ - limite) ≤ 0
+        let mut limites: Vec<(usize, NumReal, NumReal)> = Vec::new();
+        for i in 0..DIM {
+            if inferior[i].is_finite() && inferior[i] > self.limites_inferiores[i] {
+                limites.push((i, -1.0, inferior[i]));
+            }
+            if superior[i].is_finite() && superior[i] < self.limites_superiores[i] {
+                limites.push((i, 1.0, superior[i]));
+            }
+        }
+
+        let mut copia = self.copia_independente();
+        for &(i, sinal, limite) in &limites {
+            if sinal > 0.0 {
+                copia.limites_superiores[i] = limite;
+            } else {
+                copia.limites_inferiores[i] = limite;
+            }
+        }
+        if limites.is_empty() {
+            return copia;
+        }
+
+        let mi = self.mi();
+        let mut nomes: Vec<String> = (0..mi).map(|j| self.nome_desigualdade(j)).collect();
+        for &(i, sinal, limite) in &limites {
+            nomes.push(format!(
+                "{} {} {}",
+                self.nome_variavel(i),
+                if sinal > 0.0 { "≤" } else { "≥" },
+                limite
+            ));
+        }
+        copia.nomes_desigualdades = nomes;
+
+        let limites = Arc::new(limites);
+        let valores = {
+            let limites = limites.clone();
+            move |x: Ponto| -> Vec<NumReal> {
+                limites
+                    .iter()
+                    .map(|&(i, sinal, limite)| sinal * (x[i] - limite))
+                    .collect()
+            }
+        };
+        let gradientes = {
+            let limites = limites.clone();
+            move || -> Vec<Ponto> {
+                limites
+                    .iter()
+                    .map(|&(i, sinal, _)| {
+                        let mut gradiente = [0.0; DIM];
+                        gradiente[i] = sinal;
+                        gradiente
+                    })
+                    .collect()
+            }
+        };
+
+        match &self.restricoes_vetoriais {
+            // Os limites entram entre as desigualdades e as igualdades de c(x)
+            Some(restricoes) => {
+                let funcao = restricoes.funcao.clone();
+                let valores = valores.clone();
+                let mut vetoriais = RestricoesVetoriais {
+                    funcao: Arc::new(move |x: Ponto| {
+                        let mut c = funcao(x);
+                        let igualdades = c.split_off(mi);
+                        c.extend(valores(x));
+                        c.extend(igualdades);
+                        c
+                    }),
+                    jacobiana: None,
+                    mi: mi + limites.len(),
+                    me: restricoes.me,
+                };
+                if let Some(jacobiana) = restricoes.jacobiana.clone() {
+                    let gradientes = gradientes.clone();
+                    vetoriais.jacobiana = Some(Arc::new(move |x: Ponto| {
+                        let mut linhas = jacobiana(x);
+                        let igualdades = linhas.split_off(mi);
+                        linhas.extend(gradientes());
+                        linhas.extend(igualdades);
+                        linhas
+                    }));
+                }
+                copia.restricoes_vetoriais = Some(vetoriais);
+            }
+            None => {
+                for &(i, sinal, limite) in limites.iter() {
+                    copia
+                        .restricoes_desigualdades
+                        .push(Arc::new(move |x: Ponto| sinal * (x[i] - limite)));
+                }
+                if let Some(jacobiana) = self.jacobiana_desigualdades.clone() {
+                    let gradientes = gradientes.clone();
+                    copia.jacobiana_desigualdades = Some(Arc::new(move |x: Ponto| {
+                        let mut linhas = jacobiana(x);
+                        linhas.extend(gradientes());
+                        linhas
+                    }));
+                }
+            }
+        }
+
+        // Sem desigualdades, a falta da forma intervalar não é falta
+        if self.desigualdades_intervalares.is_some() || mi == 0 {
+            let formas = self.desigualdades_intervalares.clone();
+            copia.desigualdades_intervalares = Some(Arc::new(move |caixa: Caixa| {
+                let mut intervalos = match &formas {
+                    Some(formas) => formas(caixa),
+                    None => Vec::new(),
+                };
+                intervalos.extend(
+                    limites
+                        .iter()
+                        .map(|&(i, sinal, limite)| sinal * (caixa[i] - limite)),
+                );
+                intervalos
+            }));
+        }
+
+        copia
+    }
+
+    // Caixa das variaveis, para amostrar pontos e para a busca global: os
+    // limites das variaveis onde são finitos, e a caixa d_l..d_u nas
+    // variaveis livres, como nos problemas montados sem limites
+    pub fn caixa_variaveis(&self) -> (Ponto, Ponto) {
+        let mut inferior = self.d_l;
+        let mut superior = self.d_u;
+        for i in 0..DIM {
+            if self.limites_inferiores[i].is_finite() {
+                inferior[i] = self.limites_inferiores[i];
+            }
+            if self.limites_superiores[i].is_finite() {
+                superior[i] = self.limites_superiores[i];
+            }
+        }
+        (inferior, superior)
+    }
+
+    // Verifica se alguma variavel é inteira
+    pub fn tem_variaveis_inteiras(&self) -> bool {
+        self.variaveis_inteiras.iter().any(|&inteira| inteira)
+    }
+
+    // Verifica se alguma derivada analitica foi dada
+    pub fn tem_derivadas_analiticas(&self) -> bool {
+        self.gradiente_objetivo.is_some()
+            || self.jacobiana_desigualdades.is_some()
+            || self.jacobiana_igualdades.is_some()
+            || matches!(&self.restricoes_vetoriais, Some(restricoes) if restricoes.jacobiana.is_some())
+    }
+
+    // Verifica se o objetivo e todas as restrições têm forma intervalar
+    pub fn tem_formas_intervalares(&self) -> bool {
+        self.objetivo_intervalar.is_some()
+            && (self.mi() == 0 || self.desigualdades_intervalares.is_some())
+            && (self.me() == 0 || self.igualdades_intervalares.is_some())
+    }
+
+    // Acesso exclusivo ao registro das avaliações. As funções do usuario
+    // nunca são chamadas com ele travado
+    fn registrar<T>(&self, acao: impl FnOnce(&mut RegistroAvaliacoes) -> T) -> T {
+        acao(&mut self.avaliacoes.lock().unwrap())
+    }
+
+    pub fn contadores_avaliacao(&self) -> ContadoresAvaliacao {
+        self.registrar(|registro| registro.contadores.clone())
+    }
+
+    pub fn definir_orcamento_avaliacoes(&self, orcamento: Option<usize>) {
+        self.registrar(|registro| registro.orcamento = orcamento);
+    }
+
+    pub fn estado_avaliacoes(&self) -> EstadoAvaliacoes {
+        self.registrar(|registro| registro.estado())
+    }
+
+    pub fn restaurar_avaliacoes(&self, estado: EstadoAvaliacoes) {
+        self.registrar(|registro| registro.restaurar(estado));
+    }
+
+    pub fn orcamento_esgotado(&self) -> bool {
+        self.registrar(|registro| registro.orcamento_esgotado())
+    }
+
+    pub fn contar_hessiana(&self) {
+        self.registrar(|registro| registro.contadores.hessianas += 1);
+    }
+
+    // Reaproveita o valor guardado no cache para x, ou o calcula e guarda
+    fn em_cache<T: Clone>(
+        &self,
+        x: Ponto,
+        campo: impl FnOnce(&EntradaCache) -> Option<T>,
+        guardar: impl FnOnce(&mut EntradaCache, T),
+        calcular: impl FnOnce() -> T,
+    ) -> T {
+        if let Some(valor) = self.registrar(|registro| registro.buscar(&x, campo)) {
+            return valor;
+        }
+
+        let valor = calcular();
+        let copia = valor.clone();
+        self.registrar(|registro| registro.guardar(&x, |entrada| guardar(entrada, copia)));
+        valor
+    }
+
+    // f(x) sem passar pelo cache, para pontos que não se repetem, como os
+    // das diferenças finitas e das buscas em linha
+    pub fn avaliar_objetivo(&self, x: Ponto) -> NumReal {
+        self.registrar(|registro| registro.contadores.funcao += 1);
+        (self.funcao_objetivo)(x)
+    }
+
+    // gⱼ(x) e hᵣ(x) sem passar pelo cache. Com restrições vetoriais, c(x)
+    // é avaliada uma só vez
+    pub fn avaliar_restricoes(&self, x: Ponto) -> (Vec<NumReal>, Vec<NumReal>) {
+        match &self.restricoes_vetoriais {
+            Some(restricoes) => {
+                self.registrar(|registro| registro.contadores.restricoes += 1);
+                let mut desigualdades = (restricoes.funcao)(x);
+                let igualdades = desigualdades.split_off(restricoes.mi);
+                (desigualdades, igualdades)
+            }
+            None => {
+                let chamadas = self.mi() + self.me();
+                self.registrar(|registro| registro.contadores.restricoes += chamadas);
+                (
+                    self.restricoes_desigualdades.iter().map(|f| f(x)).collect(),
+                    self.restricoes_igualdades.iter().map(|f| f(x)).collect(),
+                )
+            }
+        }
+    }
+
+    // f(x), pelo cache
+    pub fn objetivo_em(&self, x: Ponto) -> NumReal {
+        self.em_cache(
+            x,
+            |entrada| entrada.funcao,
+            |entrada, valor| entrada.funcao = Some(valor),
+            || self.avaliar_objetivo(x),
+        )
+    }
+
+    // gⱼ(x) e hᵣ(x) de todas as restrições, pelo cache
+    pub fn restricoes_em(&self, x: Ponto) -> (Vec<NumReal>, Vec<NumReal>) {
+        self.em_cache(
+            x,
+            |entrada| entrada.restricoes.clone(),
+            |entrada, valor| entrada.restricoes = Some(valor),
+            || self.avaliar_restricoes(x),
+        )
+    }
+
+    // ∇f(x), analitico se disponivel, pelo cache
+    pub fn gradiente_objetivo_em(&self, x: Ponto) -> Ponto {
+        use crate::diferencas_finitas::gradiente_passo_complexo;
+        use crate::utils::auto_grad;
+
+        self.em_cache(
+            x,
+            |entrada| entrada.gradiente,
+            |entrada, valor| entrada.gradiente = Some(valor),
+            || {
+                self.registrar(|registro| registro.contadores.gradientes += 1);
+                match (&self.gradiente_objetivo, &self.objetivo_complexo) {
+                    (Some(gradiente), _) => gradiente(x),
+                    (None, Some(forma)) if self.diferencas.modo == ModoDiferenca::PassoComplexo => {
+                        gradiente_passo_complexo(x, |y| {
+                            self.registrar(|registro| registro.contadores.funcao += 1);
+                            forma(y)
+                        })
+                    }
+                    (None, _) => {
+                        auto_grad(x, |y: Ponto| self.avaliar_objetivo(y), &self.diferencas)
+                    }
+                }
+            },
+        )
+    }
+
+    // ∇gⱼ(x) e ∇hᵣ(x) de todas as restrições, analiticos se disponiveis,
+    // pelo cache. Com restrições vetoriais sem jacobiana, as diferenças
+    // finitas avaliam c(x) uma vez por deslocamento, para todas as
+    // restrições juntas
+    pub fn jacobianas_em(&self, x: Ponto) -> (Vec<Ponto>, Vec<Ponto>) {
+        self.em_cache(
+            x,
+            |entrada| entrada.jacobianas.clone(),
+            |entrada, valor| entrada.jacobianas = Some(valor),
+            || {
+                self.registrar(|registro| registro.contadores.jacobianas += 1);
+                self.calcular_jacobianas(x)
+            },
+        )
+    }
+
+    fn calcular_jacobianas(&self, x: Ponto) -> (Vec<Ponto>, Vec<Ponto>) {
+        use crate::diferencas_finitas::jacobiana;
+
+        match &self.restricoes_vetoriais {
+            Some(restricoes) => {
+                let mut desigualdades = match &restricoes.jacobiana {
+                    Some(jacobiana) => jacobiana(x),
+                    None => jacobiana(
+                        x,
+                        |y: Ponto| {
+                            self.registrar(|registro| registro.contadores.restricoes += 1);
+                            (restricoes.funcao)(y)
+                        },
+                        &self.diferencas,
+                    ),
+                };
+                let igualdades = desigualdades.split_off(restricoes.mi);
+                (desigualdades, igualdades)
+            }
+            None => (
+                self.gradientes_restricoes(
+                    &self.restricoes_desigualdades,
+                    &self.jacobiana_desigualdades,
+                    &self.desigualdades_complexas,
+                    x,
+                ),
+                self.gradientes_restricoes(
+                    &self.restricoes_igualdades,
+                    &self.jacobiana_igualdades,
+                    &self.igualdades_complexas,
+                    x,
+                ),
+            ),
+        }
+    }
+
+    fn gradientes_restricoes(
+        &self,
+        restricoes: &[Funcao],
+        jacobiana: &Option<Jacobiana>,
+        complexas: &[Option<FuncaoComplexa>],
+        x: Ponto,
+    ) -> Vec<Ponto> {
+        use crate::diferencas_finitas::gradiente_passo_complexo;
+        use crate::utils::auto_grad;
+
+        let passo_complexo = self.diferencas.modo == ModoDiferenca::PassoComplexo;
+
+        match jacobiana {
+            Some(jacobiana) => jacobiana(x),
+            None => restricoes
+                .iter()
+                .enumerate()
+                .map(|(j, f)| match complexas.get(j) {
+                    Some(Some(forma)) if passo_complexo => gradiente_passo_complexo(x, |y| {
+                        self.registrar(|registro| registro.contadores.restricoes += 1);
+                        forma(y)
+                    }),
+                    _ => {
+                        let contada = |y: Ponto| {
+                            self.registrar(|registro| registro.contadores.restricoes += 1);
+                            f(y)
+                        };
+                        auto_grad(x, contada, &self.diferencas)
+                    }
+                })
+                .collect(),
         }
     }
 
@@ -104,48 +697,47 @@ impl Problema {
         Vec<Ponto>,   // Gradientes das funções de desigualdades avaliadas em x
         Vec<Ponto>,   // Gradientes das funções de igualdades avaliadas em x
     ) {
-        use crate::utils::auto_grad;
-
-        let val_funcao_objetivo = (self.funcao_objetivo)(x);
-        let grad_funcao_objetivo = auto_grad(x, self.funcao_objetivo);
-
-        let grads_funcao_igualdades: Vec<Ponto> = self
-            .restricoes_igualdades
-            .iter()
-            .map(|&f| auto_grad(x, f))
-            .collect();
+        let val_funcao_objetivo = self.objetivo_em(x);
+        let grad_funcao_objetivo = self.gradiente_objetivo_em(x);
 
-        let grads_funcao_desigualdades: Vec<Ponto> = self
-            .restricoes_desigualdades
-            .iter()
-            .map(|&f| auto_grad(x, f))
-            .collect();
+        let (grads_funcao_desigualdades, grads_funcao_igualdades) = self.jacobianas_em(x);
 
-        let funcao_desigualdades_avaliadas: Vec<NumReal> = self
-            .restricoes_desigualdades
-            .iter()
-            .map(|&f| f(x))
-            .collect();
+        let (funcao_desigualdades_avaliadas, funcao_igualdades_avaliadas) = self.restricoes_em(x);
 
-        let funcao_igualdades_avaliadas: Vec<NumReal> =
-            self.restricoes_igualdades.iter().map(|&f| f(x)).collect();
-
-        return (
+        (
             val_funcao_objetivo,
             grad_funcao_objetivo,
             funcao_desigualdades_avaliadas,
             funcao_igualdades_avaliadas,
             grads_funcao_desigualdades,
             grads_funcao_igualdades,
-        );
+        )
     }
 
     pub fn mi(&self) -> usize {
-        return self.restricoes_desigualdades.len();
+        match &self.restricoes_vetoriais {
+            Some(restricoes) => restricoes.mi,
+            None => self.restricoes_desigualdades.len(),
+        }
     }
 
     pub fn me(&self) -> usize {
-        return self.restricoes_igualdades.len();
+        match &self.restricoes_vetoriais {
+            Some(restricoes) => restricoes.me,
+            None => self.restricoes_igualdades.len(),
+        }
+    }
+
+    pub fn nome_variavel(&self, i: usize) -> String {
+        nome_ou_padrao(&self.nomes_variaveis, "x", i)
+    }
+
+    pub fn nome_desigualdade(&self, j: usize) -> String {
+        nome_ou_padrao(&self.nomes_desigualdades, "g", j)
+    }
+
+    pub fn nome_igualdade(&self, r: usize) -> String {
+        nome_ou_padrao(&self.nomes_igualdades, "h", r)
     }
 
     pub fn atualizar_regiao_de_confianca(&mut self, d_l: Ponto, d_u: Ponto) {
@@ -154,16 +746,415 @@ impl Problema {
     }
 }
 
+fn nome_ou_padrao(nomes: &[String], prefixo: &str, indice: usize) -> String {
+    match nomes.get(indice) {
+        Some(nome) => nome.clone(),
+        None => format!("{}_{}", prefixo, indice),
+    }
+}
+
 // Estrutura de armazenamento para multiplicadores de lagrange do problema
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiplicadoresDeLagrange {
+    #[serde(with = "crate::reais_json")]
     pub lambdas: Vec<NumReal>,
+    #[serde(with = "crate::reais_json")]
     pub mus: Vec<NumReal>,
 }
 
 // Util para o uso nas matrizes
 #[allow(dead_code)]
 pub enum OP {
-    ADD,
-    SUB,
+    Add,
+    Sub,
+}
+
+// Tolerancias dos residuos KKT. Um residuo r com referencia de escala s é
+// aceito quando r ≤ max(absoluta, relativa * s)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToleranciasKkt {
+    #[serde(with = "crate::reais_json")]
+    pub absoluta: NumReal,
+    #[serde(with = "crate::reais_json")]
+    pub relativa: NumReal,
+}
+
+// Escolhas do solver feitas em tempo de execução
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Configuracao {
+    // Backend usado para resolver do zero os subproblemas lineares. Os
+    // que não fornecem valores duais (minilp e microlp) têm os duais
+    // reconstruidos da solução primal, veja ResultadoBackend::duais
+    pub backend_linear: TipoBackendLinear,
+
+    // Resolve cada subproblema também com os outros backends disponiveis,
+    // conferindo se todos concordam
+    pub conferir_backends: bool,
+
+    // Diretorio onde cada subproblema linear é gravado, em MPS e LP, antes de ser resolvido
+    pub diretorio_exportacao_linear: Option<String>,
+
+    // Arquivo MPS de um subproblema gravado, que é resolvido no lugar das instancias
+    pub arquivo_reproducao_linear: Option<String>,
+
+    // Tolerancias da parada por ponto KKT
+    pub tolerancias_kkt: ToleranciasKkt,
+
+    // Forma de estimar os multiplicadores usados na função de mérito e na parada
+    pub estimador_multiplicadores: EstimadorMultiplicadores,
+
+    // Forma de obter a hessiana da lagrangiana da restrição 1c, e a memoria do L-BFGS
+    pub tipo_hessiana: TipoHessiana,
+    pub memoria_lbfgs: usize,
+
+    // Forma de tornar a hessiana definida positiva antes da restrição 1c
+    pub regularizacao_hessiana: RegularizacaoHessiana,
+
+    // Mostra o historico das iterações não lineares ao fim de cada problema
+    pub mostrar_historico: bool,
+
+    // Opções das derivadas por diferenças finitas
+    pub diferencas: OpcoesDiferencas,
+
+    // Mostra a verificação das derivadas analiticas mesmo quando conferem
+    pub verificar_derivadas: bool,
+
+    // Maximo de chamadas das funções do problema, por instancia
+    pub orcamento_avaliacoes: Option<usize>,
+
+    // Diretorio onde o resultado de cada problema é gravado, e o formato
+    pub diretorio_resultados: Option<String>,
+    pub formato_resultados: FormatoResultados,
+
+    // Diretorio onde o estado das iterações não lineares é gravado a cada
+    // intervalo de iterações, e diretorio de onde é lido para retomar
+    pub diretorio_ponto_de_controle: Option<String>,
+    pub intervalo_ponto_de_controle: usize,
+    pub diretorio_retomada: Option<String>,
+
+    // Tempo limite de cada instancia, em segundos. É brando: só é conferido
+    // entre as iterações externas, então a iteração em andamento, com a
+    // busca linear e os subproblemas lineares dela, termina antes da parada
+    #[serde(with = "crate::reais_json")]
+    pub tempo_limite: Option<NumReal>,
+
+    // Resolve as instancias em lote, em paralelo, mostrando só um relatorio
+    // agregado, com o numero de threads e de pontos iniciais por instancia
+    pub lote: bool,
+    pub threads: usize,
+    pub partidas: usize,
+
+    // Busca global de cada instancia por multiplas partidas, com a
+    // amostragem e a semente dos pontos iniciais extras
+    pub multipartida: bool,
+    pub amostragem: Amostragem,
+    pub semente: u64,
+
+    // Busca global por ramificação espacial, com a lacuna aceita entre as
+    // cotas e o maximo de caixas examinadas
+    pub global: bool,
+    #[serde(with = "crate::reais_json")]
+    pub tolerancia_global: NumReal,
+    pub maximo_caixas: usize,
+
+    // Ramificação inteira sobre as variaveis inteiras, com a seleção dos
+    // nós e o maximo de nós. A lacuna aceita é a mesma da busca global
+    pub inteiro: bool,
+    pub selecao_nos: SelecaoNos,
+    pub maximo_nos: usize,
+}
+
+impl Configuracao {
+    // Configuração padrão, com o backend linear padrão: o HiGHS quando
+    // compilado, e senão o minilp
+    pub fn padrao() -> Self {
+        Self {
+            backend_linear: TipoBackendLinear::padrao(),
+            conferir_backends: false,
+            diretorio_exportacao_linear: None,
+            arquivo_reproducao_linear: None,
+            tolerancias_kkt: ToleranciasKkt {
+                absoluta: TOL_KKT_ABSOLUTA,
+                relativa: TOL_KKT_RELATIVA,
+            },
+            estimador_multiplicadores: EstimadorMultiplicadores::DuaisLineares,
+            tipo_hessiana: TipoHessiana::Exata,
+            memoria_lbfgs: MEMORIA_LBFGS,
+            regularizacao_hessiana: RegularizacaoHessiana::Nenhuma,
+            mostrar_historico: false,
+            diferencas: OpcoesDiferencas::padrao(),
+            verificar_derivadas: false,
+            orcamento_avaliacoes: None,
+            diretorio_resultados: None,
+            formato_resultados: FormatoResultados::Json,
+            diretorio_ponto_de_controle: None,
+            intervalo_ponto_de_controle: 1,
+            diretorio_retomada: None,
+            tempo_limite: None,
+            lote: false,
+            threads: std::thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+            partidas: 1,
+            multipartida: false,
+            amostragem: Amostragem::Uniforme,
+            semente: SEMENTE_PARTIDAS,
+            global: false,
+            tolerancia_global: TOL_GLOBAL,
+            maximo_caixas: MAXIMO_CAIXAS,
+            inteiro: false,
+            selecao_nos: SelecaoNos::MelhorCota,
+            maximo_nos: MAXIMO_NOS,
+        }
+    }
+
+    // Diferenças nas escolhas que mudam as iterações, e portanto impedem
+    // que uma resolução seja retomada com outra configuração
+    pub fn diferencas_de_solver(&self, outra: &Configuracao) -> Vec<&'static str> {
+        let mut diferencas = Vec::new();
+        if self.backend_linear != outra.backend_linear {
+            diferencas.push("backend linear");
+        }
+        if self.tolerancias_kkt != outra.tolerancias_kkt {
+            diferencas.push("tolerancias KKT");
+        }
+        if self.estimador_multiplicadores != outra.estimador_multiplicadores {
+            diferencas.push("estimador de multiplicadores");
+        }
+        if self.tipo_hessiana != outra.tipo_hessiana || self.memoria_lbfgs != outra.memoria_lbfgs {
+            diferencas.push("hessiana");
+        }
+        if self.regularizacao_hessiana != outra.regularizacao_hessiana {
+            diferencas.push("regularização da hessiana");
+        }
+        if self.diferencas != outra.diferencas {
+            diferencas.push("diferenças finitas");
+        }
+        if self.orcamento_avaliacoes != outra.orcamento_avaliacoes {
+            diferencas.push("orçamento de avaliações");
+        }
+        diferencas
+    }
+
+    // Lê a configuração dos argumentos da linha de comando, na forma
+    // --backend-lp=<nome>, --conferir-backends, --exportar-lp=<diretorio>,
+    // --reproduzir-lp=<arquivo.mps>, --tol-kkt-abs=<valor>, --tol-kkt-rel=<valor>
+    // --multiplicadores=<duais|minimos-quadrados>, --hessiana=<exata|bfgs|sr1|lbfgs>,
+    // --memoria-lbfgs=<pares>, --regularizar-hessiana=<nenhuma|cholesky|inverter|cortar|identidade>
+    // --historico, --diferencas=<progressiva|central|passo-complexo>, --richardson,
+    // --verificar-derivadas, --orcamento-avaliacoes=<chamadas>, --exportar-resultados=<diretorio>,
+    // --formato-resultados=<json|toml>, --ponto-de-controle=<diretorio>,
+    // --intervalo-ponto-de-controle=<iterações>, --retomar=<diretorio>,
+    // --tempo-limite=<segundos>, --lote, --threads=<numero>, --partidas=<numero>,
+    // --multipartida, --amostragem=<uniforme|hipercubo-latino|sobol>, --semente=<numero>,
+    // --global, --tol-global=<valor>, --maximo-caixas=<numero>, --inteiro,
+    // --selecao-nos=<melhor-cota|profundidade> e --maximo-nos=<numero>.
+    // O --tempo-limite é brando, conferido só entre as iterações externas, e
+    // pode passar do limite pelo tempo de uma iteração. O --multipartida sem
+    // --partidas usa PARTIDAS_MULTIPARTIDA, e com uma só partida é recusado
+    pub fn dos_argumentos(argumentos: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut configuracao = Configuracao::padrao();
+        let mut partidas_dadas = false;
+
+        for argumento in argumentos {
+            if let Some(nome) = argumento.strip_prefix("--backend-lp=") {
+                configuracao.backend_linear = match TipoBackendLinear::por_nome(nome) {
+                    Some(tipo) => tipo,
+                    None => {
+                        let nomes: Vec<&str> = TipoBackendLinear::disponiveis()
+                            .iter()
+                            .map(|tipo| tipo.nome())
+                            .collect();
+                        return Err(format!(
+                            "backend linear desconhecido: {} (disponiveis: {})",
+                            nome,
+                            nomes.join(", ")
+                        ));
+                    }
+                };
+            } else if argumento == "--conferir-backends" {
+                configuracao.conferir_backends = true;
+            } else if let Some(diretorio) = argumento.strip_prefix("--exportar-lp=") {
+                configuracao.diretorio_exportacao_linear = Some(diretorio.to_string());
+            } else if let Some(arquivo) = argumento.strip_prefix("--reproduzir-lp=") {
+                configuracao.arquivo_reproducao_linear = Some(arquivo.to_string());
+            } else if let Some(valor) = argumento.strip_prefix("--tol-kkt-abs=") {
+                configuracao.tolerancias_kkt.absoluta = ler_numero(valor)?;
+            } else if let Some(valor) = argumento.strip_prefix("--tol-kkt-rel=") {
+                configuracao.tolerancias_kkt.relativa = ler_numero(valor)?;
+            } else if let Some(nome) = argumento.strip_prefix("--multiplicadores=") {
+                configuracao.estimador_multiplicadores =
+                    EstimadorMultiplicadores::por_nome(nome).ok_or(format!(
+                        "estimador de multiplicadores desconhecido: {} (disponiveis: duais, minimos-quadrados)",
+                        nome
+                    ))?;
+            } else if let Some(nome) = argumento.strip_prefix("--hessiana=") {
+                configuracao.tipo_hessiana = TipoHessiana::por_nome(nome).ok_or(format!(
+                    "hessiana desconhecida: {} (disponiveis: exata, bfgs, sr1, lbfgs)",
+                    nome
+                ))?;
+            } else if let Some(valor) = argumento.strip_prefix("--memoria-lbfgs=") {
+                configuracao.memoria_lbfgs = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&memoria| memoria > 0)
+                    .ok_or(format!("memoria do L-BFGS invalida: {}", valor))?;
+            } else if let Some(nome) = argumento.strip_prefix("--regularizar-hessiana=") {
+                configuracao.regularizacao_hessiana = RegularizacaoHessiana::por_nome(nome)
+                    .ok_or(format!(
+                        "regularização desconhecida: {} (disponiveis: nenhuma, cholesky, inverter, cortar, identidade)",
+                        nome
+                    ))?;
+            } else if argumento == "--historico" {
+                configuracao.mostrar_historico = true;
+            } else if let Some(nome) = argumento.strip_prefix("--diferencas=") {
+                configuracao.diferencas.modo = ModoDiferenca::por_nome(nome).ok_or(format!(
+                    "modo de diferenças desconhecido: {} (disponiveis: progressiva, central, passo-complexo)",
+                    nome
+                ))?;
+            } else if argumento == "--richardson" {
+                configuracao.diferencas.richardson = true;
+            } else if argumento == "--verificar-derivadas" {
+                configuracao.verificar_derivadas = true;
+            } else if let Some(valor) = argumento.strip_prefix("--orcamento-avaliacoes=") {
+                configuracao.orcamento_avaliacoes = Some(
+                    valor
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&orcamento| orcamento > 0)
+                        .ok_or(format!("orçamento de avaliações invalido: {}", valor))?,
+                );
+            } else if let Some(diretorio) = argumento.strip_prefix("--exportar-resultados=") {
+                configuracao.diretorio_resultados = Some(diretorio.to_string());
+            } else if let Some(nome) = argumento.strip_prefix("--formato-resultados=") {
+                configuracao.formato_resultados = match FormatoResultados::por_nome(nome) {
+                    Some(formato) => formato,
+                    None => {
+                        let nomes: Vec<&str> = FormatoResultados::disponiveis()
+                            .iter()
+                            .map(|formato| formato.nome())
+                            .collect();
+                        return Err(format!(
+                            "formato de resultados desconhecido: {} (disponiveis: {})",
+                            nome,
+                            nomes.join(", ")
+                        ));
+                    }
+                };
+            } else if let Some(diretorio) = argumento.strip_prefix("--ponto-de-controle=") {
+                configuracao.diretorio_ponto_de_controle = Some(diretorio.to_string());
+            } else if let Some(valor) = argumento.strip_prefix("--intervalo-ponto-de-controle=") {
+                configuracao.intervalo_ponto_de_controle = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&intervalo| intervalo > 0)
+                    .ok_or(format!(
+                        "intervalo do ponto de controle invalido: {}",
+                        valor
+                    ))?;
+            } else if let Some(diretorio) = argumento.strip_prefix("--retomar=") {
+                configuracao.diretorio_retomada = Some(diretorio.to_string());
+            } else if let Some(valor) = argumento.strip_prefix("--tempo-limite=") {
+                configuracao.tempo_limite = Some(
+                    ler_numero(valor)
+                        .ok()
+                        .filter(|&tempo| tempo > 0.0)
+                        .ok_or(format!("tempo limite invalido: {}", valor))?,
+                );
+            } else if argumento == "--lote" {
+                configuracao.lote = true;
+            } else if let Some(valor) = argumento.strip_prefix("--threads=") {
+                configuracao.threads = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&threads| threads > 0)
+                    .ok_or(format!("numero de threads invalido: {}", valor))?;
+            } else if let Some(valor) = argumento.strip_prefix("--partidas=") {
+                configuracao.partidas = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&partidas| partidas > 0)
+                    .ok_or(format!("numero de partidas invalido: {}", valor))?;
+                partidas_dadas = true;
+            } else if argumento == "--multipartida" {
+                configuracao.multipartida = true;
+            } else if let Some(nome) = argumento.strip_prefix("--amostragem=") {
+                configuracao.amostragem = Amostragem::por_nome(nome).ok_or(format!(
+                    "amostragem desconhecida: {} (disponiveis: uniforme, hipercubo-latino, sobol)",
+                    nome
+                ))?;
+            } else if let Some(valor) = argumento.strip_prefix("--semente=") {
+                configuracao.semente = valor
+                    .parse::<u64>()
+                    .map_err(|_| format!("semente invalida: {}", valor))?;
+            } else if argumento == "--global" {
+                configuracao.global = true;
+            } else if let Some(valor) = argumento.strip_prefix("--tol-global=") {
+                configuracao.tolerancia_global = ler_numero(valor)
+                    .ok()
+                    .filter(|&tolerancia| tolerancia > 0.0)
+                    .ok_or(format!("tolerancia global invalida: {}", valor))?;
+            } else if let Some(valor) = argumento.strip_prefix("--maximo-caixas=") {
+                configuracao.maximo_caixas = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&caixas| caixas > 0)
+                    .ok_or(format!("maximo de caixas invalido: {}", valor))?;
+            } else if argumento == "--inteiro" {
+                configuracao.inteiro = true;
+            } else if let Some(nome) = argumento.strip_prefix("--selecao-nos=") {
+                configuracao.selecao_nos = SelecaoNos::por_nome(nome).ok_or(format!(
+                    "seleção de nós desconhecida: {} (disponiveis: melhor-cota, profundidade)",
+                    nome
+                ))?;
+            } else if let Some(valor) = argumento.strip_prefix("--maximo-nos=") {
+                configuracao.maximo_nos = valor
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&nos| nos > 0)
+                    .ok_or(format!("maximo de nós invalido: {}", valor))?;
+            } else {
+                return Err(format!("argumento desconhecido: {}", argumento));
+            }
+        }
+
+        // A multipartida com uma partida só seria uma resolução simples
+        if configuracao.multipartida {
+            if !partidas_dadas {
+                configuracao.partidas = PARTIDAS_MULTIPARTIDA;
+            } else if configuracao.partidas <= 1 {
+                return Err("a multipartida precisa de --partidas maior que 1".into());
+            }
+        }
+
+        Ok(configuracao)
+    }
+}
+
+// Lê um numero real de um argumento
+fn ler_numero(valor: &str) -> Result<NumReal, String> {
+    valor
+        .parse::<NumReal>()
+        .map_err(|_| format!("numero invalido: {}", valor))
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn argumentos(lista: &[&str]) -> Result<Configuracao, String> {
+        Configuracao::dos_argumentos(lista.iter().map(|argumento| argumento.to_string()))
+    }
+
+    #[test]
+    fn multipartida_sem_partidas_usa_o_padrao() {
+        let configuracao = argumentos(&["--multipartida"]).unwrap();
+        assert_eq!(configuracao.partidas, PARTIDAS_MULTIPARTIDA);
+
+        let configuracao = argumentos(&["--partidas=7", "--multipartida"]).unwrap();
+        assert_eq!(configuracao.partidas, 7);
+
+        assert!(argumentos(&["--multipartida", "--partidas=1"]).is_err());
+        assert_eq!(argumentos(&["--partidas=1"]).unwrap().partidas, 1);
+    }
 }
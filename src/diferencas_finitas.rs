@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+
+use crate::complexo::Complexo;
+use crate::defs::*;
+
+// Passo do metodo do passo complexo. Como não há subtração entre valores
+// proximos, o passo pode ser tão pequeno quanto se queira
+const PASSO_COMPLEXO: NumReal = 1e-20;
+
+// Formula de diferenças usada nas derivadas
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModoDiferenca {
+    Progressiva, // (f(x + h) - f(x)) / h, erro O(h)
+    Central,     // (f(x + h) - f(x - h)) / 2h, erro O(h²)
+
+    // Im f(x + ih) / h nas funções com forma complexa, exato até o
+    // arredondamento, e diferenças centrais nas outras
+    #[serde(rename = "passo-complexo")]
+    PassoComplexo,
+}
+
+impl ModoDiferenca {
+    // Nome usado para escolher o modo
+    pub fn nome(&self) -> &'static str {
+        match self {
+            ModoDiferenca::Progressiva => "progressiva",
+            ModoDiferenca::Central => "central",
+            ModoDiferenca::PassoComplexo => "passo-complexo",
+        }
+    }
+
+    // Encontra o modo pelo nome
+    pub fn por_nome(nome: &str) -> Option<ModoDiferenca> {
+        [
+            ModoDiferenca::Progressiva,
+            ModoDiferenca::Central,
+            ModoDiferenca::PassoComplexo,
+        ]
+        .iter()
+        .find(|modo| modo.nome() == nome)
+        .copied()
+    }
+
+    // Formula de diferenças usada nas funções reais, sem forma complexa
+    fn formula(&self) -> ModoDiferenca {
+        match self {
+            ModoDiferenca::PassoComplexo => ModoDiferenca::Central,
+            modo => *modo,
+        }
+    }
+
+    // Ordem do erro de truncamento da formula
+    fn ordem(&self) -> i32 {
+        match self.formula() {
+            ModoDiferenca::Progressiva => 1,
+            _ => 2,
+        }
+    }
+}
+
+// Opções das derivadas por diferenças finitas
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OpcoesDiferencas {
+    pub modo: ModoDiferenca,
+
+    // Combina as diferenças com os passos h e h/2 pela extrapolação de
+    // Richardson, que cancela o primeiro termo do erro de truncamento
+    pub richardson: bool,
+}
+
+impl OpcoesDiferencas {
+    pub fn padrao() -> Self {
+        Self {
+            modo: ModoDiferenca::Central,
+            richardson: false,
+        }
+    }
+
+    // Ordem do erro de truncamento da estimativa final. A extrapolação
+    // ganha uma ordem nas progressivas e duas nas centrais, cujo erro só
+    // tem potencias pares de h
+    fn ordem_final(&self) -> i32 {
+        match (self.modo.formula(), self.richardson) {
+            (modo, false) => modo.ordem(),
+            (ModoDiferenca::Progressiva, true) => 2,
+            (_, true) => 4,
+        }
+    }
+
+    // Passo da componente i para uma derivada de grau dado. O erro total
+    // é o de truncamento, O(hᵖ), mais o de arredondamento, O(ε/hᵍ), que
+    // são equilibrados com h = ε^(1/(p+g)), escalado por |xᵢ| para que
+    // o passo seja relativo quando xᵢ é grande
+    fn passo(&self, xi: NumReal, grau: i32) -> NumReal {
+        let expoente = 1.0 / (self.ordem_final() + grau) as NumReal;
+        let h = NumReal::EPSILON.powf(expoente) * xi.abs().max(1.0);
+
+        // Usa o passo que de fato separa os pontos em ponto flutuante
+        (xi + h) - xi
+    }
+
+    // Aplica a formula de diferenças em função do fator do passo, com ou
+    // sem extrapolação, e estima o erro de truncamento pela diferença
+    // entre as estimativas com dois passos:
+    // - sem extrapolação, D(h) e D(2h), com erro ≈ |D(h) - D(2h)|/(2ᵖ - 1)
+    // - com extrapolação, D(h) e D(h/2), combinadas em
+    //   (2ᵖD(h/2) - D(h))/(2ᵖ - 1), com erro ≈ a distancia para D(h/2)
+    fn estimar(&self, diferenca: impl Fn(NumReal) -> NumReal) -> (NumReal, NumReal) {
+        let (valores, erros) = self.estimar_vetor(|fator| vec![diferenca(fator)]);
+        (valores[0], erros[0])
+    }
+
+    // Só a estimativa, sem o erro. Sem extrapolação basta D(h), o que
+    // poupa metade das avaliações
+    fn estimar_valor(&self, diferenca: impl Fn(NumReal) -> NumReal) -> NumReal {
+        self.estimar_valor_vetor(|fator| vec![diferenca(fator)])[0]
+    }
+
+    fn estimar_valor_vetor(&self, diferenca: impl Fn(NumReal) -> Vec<NumReal>) -> Vec<NumReal> {
+        if self.richardson {
+            self.estimar_vetor(diferenca).0
+        } else {
+            diferenca(1.0)
+        }
+    }
+
+    // O mesmo que estimar, para as diferenças de todas as componentes de
+    // uma função vetorial com o mesmo passo
+    fn estimar_vetor(
+        &self,
+        diferenca: impl Fn(NumReal) -> Vec<NumReal>,
+    ) -> (Vec<NumReal>, Vec<NumReal>) {
+        let fator = (2.0 as NumReal).powi(self.modo.ordem());
+
+        if self.richardson {
+            let d_h = diferenca(1.0);
+            let d_meio = diferenca(0.5);
+            d_h.iter()
+                .zip(d_meio.iter())
+                .map(|(&d_h, &d_meio)| {
+                    let extrapolada = (fator * d_meio - d_h) / (fator - 1.0);
+                    (extrapolada, (extrapolada - d_meio).abs())
+                })
+                .unzip()
+        } else {
+            let d_h = diferenca(1.0);
+            let d_dobro = diferenca(2.0);
+            d_h.iter()
+                .zip(d_dobro.iter())
+                .map(|(&d_h, &d_dobro)| (d_h, (d_h - d_dobro).abs() / (fator - 1.0)))
+                .unzip()
+        }
+    }
+}
+
+// Gradiente estimado junto da estimativa do erro de truncamento de cada componente
+#[derive(Debug, Clone)]
+pub struct GradienteEstimado {
+    pub gradiente: Ponto,
+    pub erro: Ponto,
+}
+
+// Diferença da componente i de f em x com o passo hi, pela formula do modo
+fn diferenca_componente(
+    x: Ponto,
+    i: usize,
+    hi: NumReal,
+    f: &impl Fn(Ponto) -> NumReal,
+    f_x: NumReal,
+    modo: ModoDiferenca,
+) -> NumReal {
+    let deslocado = |passo: NumReal| {
+        let mut ponto = x;
+        ponto[i] += passo;
+        f(ponto)
+    };
+
+    match modo {
+        ModoDiferenca::Progressiva => (deslocado(hi) - f_x) / hi,
+        _ => (deslocado(hi) - deslocado(-hi)) / (2.0 * hi),
+    }
+}
+
+// f(x), se a formula do modo usa
+fn centro(x: Ponto, f: &impl Fn(Ponto) -> NumReal, modo: ModoDiferenca) -> NumReal {
+    match modo {
+        ModoDiferenca::Progressiva => f(x),
+        _ => 0.0,
+    }
+}
+
+// Gradiente da função f no ponto x por diferenças finitas, com um passo
+// por componente
+pub fn gradiente(
+    x: Ponto,
+    f: impl Fn(Ponto) -> NumReal,
+    opcoes: &OpcoesDiferencas,
+) -> GradienteEstimado {
+    let modo = opcoes.modo.formula();
+    let f_x = centro(x, &f, modo);
+
+    let mut gradiente = [0.0; DIM];
+    let mut erro = [0.0; DIM];
+    for i in 0..DIM {
+        let h = opcoes.passo(x[i], 1);
+        let (valor, erro_i) =
+            opcoes.estimar(|fator| diferenca_componente(x, i, fator * h, &f, f_x, modo));
+
+        gradiente[i] = valor;
+        erro[i] = erro_i;
+    }
+
+    GradienteEstimado { gradiente, erro }
+}
+
+// O mesmo gradiente, sem a estimativa do erro, que custa outra diferença
+// por componente quando não há extrapolação
+pub fn gradiente_sem_erro(
+    x: Ponto,
+    f: impl Fn(Ponto) -> NumReal,
+    opcoes: &OpcoesDiferencas,
+) -> Ponto {
+    let modo = opcoes.modo.formula();
+    let f_x = centro(x, &f, modo);
+
+    let mut gradiente = [0.0; DIM];
+    for (i, derivada) in gradiente.iter_mut().enumerate() {
+        let h = opcoes.passo(x[i], 1);
+        *derivada =
+            opcoes.estimar_valor(|fator| diferenca_componente(x, i, fator * h, &f, f_x, modo));
+    }
+
+    gradiente
+}
+
+// Jacobiana da função vetorial f no ponto x por diferenças finitas, uma
+// linha por componente de f. Cada deslocamento avalia f uma só vez para
+// todas as componentes, então o custo não depende do numero delas
+pub fn jacobiana(
+    x: Ponto,
+    f: impl Fn(Ponto) -> Vec<NumReal>,
+    opcoes: &OpcoesDiferencas,
+) -> Vec<Ponto> {
+    let modo = opcoes.modo.formula();
+
+    // Só a diferença progressiva usa f(x)
+    let f_x = match modo {
+        ModoDiferenca::Progressiva => f(x),
+        _ => Vec::new(),
+    };
+
+    let mut colunas: Vec<Vec<NumReal>> = Vec::new();
+    for i in 0..DIM {
+        let h = opcoes.passo(x[i], 1);
+        let deslocado = |passo: NumReal| {
+            let mut ponto = x;
+            ponto[i] += passo;
+            f(ponto)
+        };
+
+        let coluna = opcoes.estimar_valor_vetor(|fator| {
+            let hi = fator * h;
+            match modo {
+                ModoDiferenca::Progressiva => deslocado(hi)
+                    .iter()
+                    .zip(f_x.iter())
+                    .map(|(mais, centro)| (mais - centro) / hi)
+                    .collect(),
+                _ => deslocado(hi)
+                    .iter()
+                    .zip(deslocado(-hi).iter())
+                    .map(|(mais, menos)| (mais - menos) / (2.0 * hi))
+                    .collect(),
+            }
+        });
+        colunas.push(coluna);
+    }
+
+    // Transpõe as colunas ∂f/∂xᵢ para as linhas ∇fⱼ
+    (0..colunas[0].len())
+        .map(|j| {
+            let mut linha = [0.0; DIM];
+            for i in 0..DIM {
+                linha[i] = colunas[i][j];
+            }
+            linha
+        })
+        .collect()
+}
+
+// Hessiana da função f no ponto x por diferenças finitas de segunda ordem
+// - progressivas: (f(x + hᵢeᵢ + hⱼeⱼ) - f(x + hᵢeᵢ) - f(x + hⱼeⱼ) + f(x)) / hᵢhⱼ
+// - centrais: (f(x + hᵢeᵢ + hⱼeⱼ) - f(x + hᵢeᵢ - hⱼeⱼ) - f(x - hᵢeᵢ + hⱼeⱼ)
+//   + f(x - hᵢeᵢ - hⱼeⱼ)) / 4hᵢhⱼ, que na diagonal é equivalente a
+//   (f(x + 2hᵢeᵢ) - 2f(x) + f(x - 2hᵢeᵢ)) / 4hᵢ²
+// O passo complexo só dá derivadas primeiras, então usa as centrais
+pub fn hessiana(
+    x: Ponto,
+    f: impl Fn(Ponto) -> NumReal,
+    opcoes: &OpcoesDiferencas,
+) -> Vec<Vec<NumReal>> {
+    let modo = opcoes.modo.formula();
+    let mut hessiana = vec![vec![0.0; DIM]; DIM];
+    let passos: Vec<NumReal> = (0..DIM).map(|i| opcoes.passo(x[i], 2)).collect();
+
+    let deslocado = |i: usize, hi: NumReal, j: usize, hj: NumReal| {
+        let mut ponto = x;
+        ponto[i] += hi;
+        ponto[j] += hj;
+        f(ponto)
+    };
+
+    for i in 0..DIM {
+        for j in i..DIM {
+            let valor = opcoes.estimar_valor(|fator| {
+                let (hi, hj) = (fator * passos[i], fator * passos[j]);
+                match modo {
+                    ModoDiferenca::Progressiva => {
+                        (deslocado(i, hi, j, hj)
+                            - deslocado(i, hi, j, 0.0)
+                            - deslocado(i, 0.0, j, hj)
+                            + f(x))
+                            / (hi * hj)
+                    }
+                    _ => {
+                        (deslocado(i, hi, j, hj)
+                            - deslocado(i, hi, j, -hj)
+                            - deslocado(i, -hi, j, hj)
+                            + deslocado(i, -hi, j, -hj))
+                            / (4.0 * hi * hj)
+                    }
+                }
+            });
+
+            hessiana[i][j] = valor;
+            hessiana[j][i] = valor;
+        }
+    }
+
+    hessiana
+}
+
+// Gradiente pelo passo complexo, ∂f/∂xᵢ = Im f(x + ihεᵢ) / h, para a forma
+// complexa de uma função, dada por Problema::objetivo_complexo ou pelas
+// formas das restrições. Não há cancelamento, então o resultado é exato
+// até o arredondamento
+pub fn gradiente_passo_complexo(x: Ponto, f: impl Fn([Complexo; DIM]) -> Complexo) -> Ponto {
+    let mut gradiente = [0.0; DIM];
+
+    for (i, derivada) in gradiente.iter_mut().enumerate() {
+        let mut ponto = x.map(Complexo::real);
+        ponto[i].im = PASSO_COMPLEXO;
+
+        *derivada = f(ponto).im / PASSO_COMPLEXO;
+    }
+
+    gradiente
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::instancias::todas_as_instancias;
+
+    // O passo complexo nas formas complexas das instancias tem que dar os
+    // mesmos gradientes que as diferenças centrais extrapoladas nas funções
+    // reais, que têm erro perto de 1e-10
+    #[test]
+    fn passo_complexo_confere_com_diferencas_centrais() {
+        let opcoes = OpcoesDiferencas {
+            modo: ModoDiferenca::Central,
+            richardson: true,
+        };
+        let confere = |complexo: Ponto, central: Ponto| {
+            complexo
+                .iter()
+                .zip(central.iter())
+                .all(|(a, b)| (a - b).abs() <= 1e-6 * b.abs().max(1.0))
+        };
+
+        let mut formas = 0;
+        for problema in todas_as_instancias() {
+            let x = problema.x_inicial;
+            if let Some(forma) = &problema.objetivo_complexo {
+                let complexo = gradiente_passo_complexo(x, &**forma);
+                let central = gradiente_sem_erro(x, &*problema.funcao_objetivo, &opcoes);
+                assert!(confere(complexo, central), "{}", problema.nome);
+                formas += 1;
+            }
+
+            let familias = [
+                (
+                    &problema.restricoes_desigualdades,
+                    &problema.desigualdades_complexas,
+                ),
+                (
+                    &problema.restricoes_igualdades,
+                    &problema.igualdades_complexas,
+                ),
+            ];
+            for (restricoes, complexas) in familias {
+                for (f, forma) in restricoes.iter().zip(complexas) {
+                    if let Some(forma) = forma {
+                        let complexo = gradiente_passo_complexo(x, &**forma);
+                        let central = gradiente_sem_erro(x, &**f, &opcoes);
+                        assert!(confere(complexo, central), "{}", problema.nome);
+                        formas += 1;
+                    }
+                }
+            }
+        }
+        assert!(formas > 0);
+    }
+}
@@ -1,4 +1,57 @@
+use serde::{Deserialize, Serialize};
+
 use crate::defs::*;
+use crate::prob_linear::SolucaoProblemaLinear;
+use crate::qualificacoes::desigualdades_ativas;
+use crate::utils::minimos_quadrados_nao_negativos;
+
+// Forma de estimar os multiplicadores de lagrange a cada subiteração linear
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EstimadorMultiplicadores {
+    #[serde(rename = "duais")]
+    DuaisLineares, // Valores duais do subproblema linear
+    #[serde(rename = "minimos-quadrados")]
+    MinimosQuadrados, // Minimos quadrados não negativos no conjunto ativo
+}
+
+impl EstimadorMultiplicadores {
+    // Nome usado para escolher o estimador
+    pub fn nome(&self) -> &'static str {
+        match self {
+            EstimadorMultiplicadores::DuaisLineares => "duais",
+            EstimadorMultiplicadores::MinimosQuadrados => "minimos-quadrados",
+        }
+    }
+
+    // Encontra o estimador pelo nome
+    pub fn por_nome(nome: &str) -> Option<EstimadorMultiplicadores> {
+        [
+            EstimadorMultiplicadores::DuaisLineares,
+            EstimadorMultiplicadores::MinimosQuadrados,
+        ]
+        .iter()
+        .find(|estimador| estimador.nome() == nome)
+        .copied()
+    }
+}
+
+// Estima os multiplicadores no ponto x, depois de resolvido o subproblema
+// linear, da forma escolhida na configuração
+pub fn estimar_multiplicadores(
+    problema: &Problema,
+    x: Ponto,
+    solucao: &SolucaoProblemaLinear,
+    configuracao: &Configuracao,
+) -> MultiplicadoresDeLagrange {
+    match configuracao.estimador_multiplicadores {
+        EstimadorMultiplicadores::DuaisLineares => {
+            extrair_multiplicadores_de_lagrange(problema, solucao)
+        }
+        EstimadorMultiplicadores::MinimosQuadrados => {
+            estimar_multiplicadores_minimos_quadrados(problema, x)
+        }
+    }
+}
 
 // Extrai os multiplicadores de lagrange a partir da
 // solução dual do problema. Como dito em 2.1.1 no
@@ -16,9 +69,11 @@ use crate::defs::*;
 // a segunda restrição de 1b é invertida, temos que o valor
 // para 1b é o valor para a primeira restrição derivida menos
 // o segundo.
+// Os valores duais vêm junto da solução do problema primal, um
+// por linha de A, na ordem em que as restrições foram geradas.
 pub fn extrair_multiplicadores_de_lagrange(
     problema: &Problema,
-    solucao_dual: (NumReal, Vec<NumReal>),
+    solucao: &SolucaoProblemaLinear,
 ) -> MultiplicadoresDeLagrange {
     // Cria listas para armazenar os valores
     let mut lambdas = Vec::new(); // λ
@@ -27,22 +82,22 @@ pub fn extrair_multiplicadores_de_lagrange(
     let mut mus = Vec::new(); // μ = μ⁺ - μ⁻
 
     // Dimensões do problema
-    let mi = problema.restricoes_desigualdades.len();
-    let me = problema.restricoes_igualdades.len();
+    let mi = problema.mi();
+    let me = problema.me();
 
     for j in 0..mi {
         // Extrai λ das primeiras mi soluções do problema dual
-        lambdas.push(solucao_dual.1[j]);
+        lambdas.push(solucao.duais[j]);
     }
 
     for r in 0..me {
         // Extrai μ⁺ das me soluções do problema dual, depois das primeias mi
-        mups.push(solucao_dual.1[mi + r]);
+        mups.push(solucao.duais[mi + r]);
     }
 
     for r in 0..me {
         // Extrai μ⁻ das me soluções do problema dual, depois das primeias mi+me
-        muns.push(solucao_dual.1[mi + me + r]);
+        muns.push(solucao.duais[mi + me + r]);
     }
 
     for (mup, mun) in mups.iter().zip(muns.iter()) {
@@ -51,5 +106,47 @@ pub fn extrair_multiplicadores_de_lagrange(
     }
 
     // Retorna
-    return MultiplicadoresDeLagrange { lambdas, mus };
+    MultiplicadoresDeLagrange { lambdas, mus }
+}
+
+// Estima os multiplicadores de lagrange no ponto x sem o subproblema
+// linear, como os que melhor satisfazem a estacionariedade:
+// min ‖∇f(x) + Σ λⱼ∇gⱼ(x) + Σ μᵣ∇hᵣ(x)‖, λ ≥ 0
+// com λⱼ = 0 nas desigualdades inativas. Os valores duais do subproblema
+// ficam degenerados, ou nulos, quando a região de confiança está ativa, e
+// essa estimativa não depende dela.
+// Como μ é livre, ele é separado em μ = μ⁺ - μ⁻, com colunas ∇hᵣ e -∇hᵣ,
+// e o problema é resolvido por minimos quadrados não negativos
+pub fn estimar_multiplicadores_minimos_quadrados(
+    problema: &Problema,
+    x: Ponto,
+) -> MultiplicadoresDeLagrange {
+    let (_, grad_f, _, _, grads_g, grads_h) = problema.avaliar_em(x);
+    let ativas = desigualdades_ativas(problema, x);
+    let me = problema.me();
+
+    // Colunas na ordem μ⁺, μ⁻, λ das ativas
+    let mut colunas: Vec<Vec<NumReal>> = Vec::new();
+    for grad_h in &grads_h {
+        colunas.push(grad_h.to_vec());
+    }
+    for grad_h in &grads_h {
+        colunas.push(grad_h.iter().map(|el| -el).collect());
+    }
+    for &j in &ativas {
+        colunas.push(grads_g[j].to_vec());
+    }
+
+    // Alvo -∇f(x)
+    let alvo: Vec<NumReal> = grad_f.iter().map(|el| -el).collect();
+
+    let y = minimos_quadrados_nao_negativos(&colunas, &alvo);
+
+    let mus = (0..me).map(|r| y[r] - y[me + r]).collect();
+    let mut lambdas = vec![0.0; problema.mi()];
+    for (k, &j) in ativas.iter().enumerate() {
+        lambdas[j] = y[2 * me + k];
+    }
+
+    MultiplicadoresDeLagrange { lambdas, mus }
 }
@@ -1,9 +1,8 @@
 use crate::{
     defs::{
-        Funcao, MultiplicadoresDeLagrange, NumReal, Ponto, Problema, ETA_MERITO, LAG_INC,
-        SIGMA_MERITO,
+        MultiplicadoresDeLagrange, NumReal, Ponto, Problema, ETA_MERITO, LAG_INC, SIGMA_MERITO,
     },
-    utils::{auto_grad, line_search, max, produto_escalar, produto_interno, soma_pontos},
+    utils::{line_search, max, produto_escalar, produto_interno, soma_pontos},
 };
 
 // Retorna a função de merito para os multiplicadores dados
@@ -11,10 +10,8 @@ fn gerar_funcao_merito(
     problema: &Problema,
     multiplicadores: &MultiplicadoresDeLagrange,
 ) -> impl Fn(Ponto) -> NumReal {
-    // Copias das funções
-    let funcao_obj = problema.funcao_objetivo.clone();
-    let g = problema.restricoes_desigualdades.clone();
-    let h = problema.restricoes_igualdades.clone();
+    // Copia local do problema, que dá as funções
+    let problema = problema.copia_compartilhada();
 
     // Gera uma lista de λ̅ⱼ > λⱼ
     let lbds_maiores: Vec<NumReal> = multiplicadores
@@ -32,39 +29,39 @@ fn gerar_funcao_merito(
 
     // Retorna a função que toma um ponto e retorna um número real
     // movendo todas as copias locais para o escopo da função retornada
-    return move |x: Ponto| -> NumReal {
+    move |x: Ponto| -> NumReal {
         // f(x)
-        let val_obj = funcao_obj(x);
+        let val_obj = problema.avaliar_objetivo(x);
+
+        // g(x) e h(x), avaliadas juntas
+        let (g, h) = problema.avaliar_restricoes(x);
 
         // λ̅ⱼg(x)⁺
         let mut soma_gj = 0.0;
         for j in 0..lbds_maiores.len() {
-            soma_gj += lbds_maiores[j] * max(g[j](x), 0.0);
+            soma_gj += lbds_maiores[j] * max(g[j], 0.0);
         }
 
         // μ̅ᵣ|hᵣ(x)|
         let mut soma_hr = 0.0;
         for r in 0..mus_maiores.len() {
-            soma_hr += mus_maiores[r] * h[r](x).abs();
+            soma_hr += mus_maiores[r] * h[r].abs();
         }
 
         // Retorna a soma das três parcelas
         val_obj + soma_gj + soma_hr
-    };
+    }
 }
 
 // Calcula o valor do componente positivo da derivada direcional de uma função g,
-// restrição de desigualdade, em uma direção d
-fn derivada_direcional_g(direcao: Ponto, g: Funcao, x: Ponto) -> NumReal {
+// restrição de desigualdade, em uma direção d, dados g(x) e ∇g(x)
+fn derivada_direcional_g(direcao: Ponto, val: NumReal, grad_g: Ponto) -> NumReal {
     // Se g(x) > 0, retorna ∇g(x)ᵀd
     // Se g(x) = 0, retorna max(∇g(x)ᵀd, 0)
     // Se g(x) < 0, retorna 0
 
-    let val: NumReal = g(x);
-
     if val >= 0.0 {
-        let grad_g = move |x: Ponto| auto_grad(x, g); // Cria a função gradiente de g
-        let prod: NumReal = produto_interno(&grad_g(x), &direcao);
+        let prod: NumReal = produto_interno(&grad_g, &direcao);
 
         if val > 0.0 {
             return prod;
@@ -77,15 +74,13 @@ fn derivada_direcional_g(direcao: Ponto, g: Funcao, x: Ponto) -> NumReal {
 }
 
 // Calcula o valor da derivada direcional de uma função h,
-// restrição de igualdade, em uma direção d
-fn derivada_direcional_h(direcao: Ponto, h: Funcao, x: Ponto) -> NumReal {
+// restrição de igualdade, em uma direção d, dados h(x) e ∇h(x)
+fn derivada_direcional_h(direcao: Ponto, val: NumReal, grad_h: Ponto) -> NumReal {
     // Se h(x) > 0, retorna ∇h(x)ᵀd
     // Se h(x) = 0, retorna |∇h(x)ᵀd|
     // Se h(x) < 0, retorna -(∇h(x)ᵀd)
 
-    let val: NumReal = h(x);
-    let grad_h = move |x: Ponto| auto_grad(x, h);
-    let prod: NumReal = produto_interno(&grad_h(x), &direcao);
+    let prod: NumReal = produto_interno(&grad_h, &direcao);
 
     if val > 0.0 {
         return prod;
@@ -95,7 +90,7 @@ fn derivada_direcional_h(direcao: Ponto, h: Funcao, x: Ponto) -> NumReal {
         return prod.abs();
     }
 
-    -1.0 * prod
+    -prod
 }
 
 // Gera a função a derivada direcional
@@ -117,36 +112,34 @@ fn gerar_derivada_direcional_funcao_merito(
         .map(|el| el.abs() + LAG_INC)
         .collect();
 
-    // Copias locais das funções
-    let funcao_obj = problema.funcao_objetivo.clone();
-    let g = problema.restricoes_desigualdades.clone();
-    let h = problema.restricoes_igualdades.clone();
-
-    // Função gradiente da função objetivo
-    let grad_funcao_obj = move |x: Ponto| auto_grad(x, funcao_obj);
+    // Copia local do problema, que dá as funções e seus gradientes
+    let problema = problema.copia_compartilhada();
 
     // Retorna a função DdM(x)
-    return move |x: Ponto| -> NumReal {
+    move |x: Ponto| -> NumReal {
         // ∇f(x)ᵀd
         // Valor do grandiente da função objetivo avaliada no ponto ()
-        let val_grad_funcao_obj = produto_interno(&grad_funcao_obj(x), &d);
+        let val_grad_funcao_obj = produto_interno(&problema.gradiente_objetivo_em(x), &d);
 
         //  λ̅ⱼDdgⱼ(x)⁺
         // Valores das derivadas direcionais de Ddgⱼ(x)⁺
+        let (val_g, val_h) = problema.restricoes_em(x);
+        let (grads_g, grads_h) = problema.jacobianas_em(x);
         let mut val_grad_g_acumulado = 0.0;
-        for j in 0..g.len() {
-            val_grad_g_acumulado += lbds_maiores[j] * derivada_direcional_g(d, g[j], x);
+        for j in 0..problema.mi() {
+            val_grad_g_acumulado +=
+                lbds_maiores[j] * derivada_direcional_g(d, val_g[j], grads_g[j]);
         }
 
         // μ̅ᵣDd|hᵣ(x)|
         // Valores das derivadas direcionais de Dd|hᵣ(x)|
         let mut val_grad_h_acumulado = 0.0;
-        for r in 0..h.len() {
-            val_grad_h_acumulado += mus_maiores[r] * derivada_direcional_h(d, h[r], x);
+        for r in 0..problema.me() {
+            val_grad_h_acumulado += mus_maiores[r] * derivada_direcional_h(d, val_h[r], grads_h[r]);
         }
 
         val_grad_funcao_obj + val_grad_g_acumulado + val_grad_h_acumulado
-    };
+    }
 }
 
 // Verifica se o novo ponto reduz suficientemente a função de mérito,
@@ -210,5 +203,5 @@ pub fn verificacao_funcao_merito(
     // println!("ponto novo = {:?}", x_novo);
 
     // Retorna o novo ponto
-    return x_novo_novo;
+    x_novo_novo
 }
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::defs::{NumReal, Ponto};
+
+// Registro de uma iteração não linear
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistroIteracao {
+    pub k: usize, // Numero da iteração
+    #[serde(with = "crate::reais_json")]
+    pub x: Ponto, // Ponto encontrado nas subiterações lineares
+    #[serde(with = "crate::reais_json")]
+    pub valor_objetivo: NumReal, // f(x)
+    #[serde(with = "crate::reais_json")]
+    pub alpha: NumReal, // Tamanho do ultimo passo na direção d
+    #[serde(with = "crate::reais_json")]
+    pub deslocamento_hessiana: NumReal, // Maior deslocamento aplicado à hessiana da restrição 1c
+}
+
+// Historico das iterações não lineares de um problema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Historico {
+    pub registros: Vec<RegistroIteracao>,
+}
+
+impl Historico {
+    pub fn novo() -> Self {
+        Self {
+            registros: Vec::new(),
+        }
+    }
+
+    pub fn registrar(&mut self, registro: RegistroIteracao) {
+        self.registros.push(registro);
+    }
+
+    // Mostra uma linha por iteração
+    pub fn mostrar(&self) {
+        println!("Historico:");
+        for registro in &self.registros {
+            println!(
+                "k = {}, x = {:?}, f(x) = {:?}, α = {:?}, deslocamento da hessiana = {:?}",
+                registro.k,
+                registro.x,
+                registro.valor_objetivo,
+                registro.alpha,
+                registro.deslocamento_hessiana
+            );
+        }
+    }
+}
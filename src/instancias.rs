@@ -1,98 +1,200 @@
-use crate::defs::{Funcao, Ponto, Problema, A, B, CC, D, DIM, E, F, L1, L2, O1, O2};
+use std::sync::Arc;
+
+use crate::complexo::Complexo;
+use crate::construtor_problema::ConstrutorProblema;
+use crate::defs::{
+    Caixa, Funcao, FuncaoIntervalar, Gradiente, NumReal, Ponto, Problema, RestricoesVetoriais, DIM,
+};
+use crate::intervalo::Intervalo;
+
+// Elipse a(x₀ - c₀)² + b(x₁ - c₁)² + c(x₀ - c₀)(x₁ - c₁) + d(x₀ - c₀) + e(x₁ - c₁) + f,
+// com os coeficientes [a, b, c, d, e, f] e o centro c
+#[derive(Debug, Clone, Copy)]
+struct Elipse {
+    coeficientes: [NumReal; 6],
+    centro: Ponto,
+}
 
-fn _problema_incial() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| (x[0].powi(2) - x[1].powi(2) - 1.0).sqrt();
+impl Elipse {
+    fn valor(&self, x: Ponto) -> NumReal {
+        let [a, b, c, d, e, f] = self.coeficientes;
+        let u = x[0] - self.centro[0];
+        let v = x[1] - self.centro[1];
+        a * u.powi(2) + b * v.powi(2) + c * u * v + d * u + e * v + f
+    }
 
-    // f3: https://www.sfu.ca/~ssurjano/boha.html
+    fn gradiente(&self, x: Ponto) -> Ponto {
+        let [a, b, c, d, e, _] = self.coeficientes;
+        let u = x[0] - self.centro[0];
+        let v = x[1] - self.centro[1];
+        [2.0 * a * u + c * v + d, 2.0 * b * v + c * u + e]
+    }
 
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[1]];
+    fn valor_intervalar(&self, x: Caixa) -> Intervalo {
+        let [a, b, c, d, e, f] = self.coeficientes;
+        let u = x[0] - self.centro[0];
+        let v = x[1] - self.centro[1];
+        a * u.powi(2) + b * v.powi(2) + c * u * v + d * u + e * v + f
+    }
 
-    let restricoes_desigualdades: Vec<Funcao> = vec![
-        // Fechar a caixinha toda
-        |x: Ponto| x[0] + x[1] - 15.0,
-        |x: Ponto| x[0] - x[1] - 15.0,
-        |x: Ponto| -x[0] + x[1] - 15.0,
-        |x: Ponto| -x[0] - x[1] - 15.0,
-        |x: Ponto| {
-            A * (x[0] - L1).powi(2) + B * (x[1] - L2).powi(2) - CC * (x[0] - L1) * (x[1] - L2)
-                + D * (x[0] - L1)
-                + E * (x[1] - L2)
-                + F
-        },
-        |x: Ponto| {
-            A * (x[0] - O1).powi(2)
-                + B * (x[1] - O2).powi(2)
-                + CC * (x[0] - O1) * (x[1] - O2)
-                + D * (x[0] - O1)
-                + E * (x[1] - O2)
-                + F
-        },
-    ];
+    // Restrição elipse(x) ≤ 0, com a elipse capturada pela closure
+    fn restricao(self) -> Funcao {
+        Arc::new(move |x: Ponto| self.valor(x))
+    }
 
-    let p = Problema::novo(
-        funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
-        [-4.0, -4.0],
-        [4.0, 4.0],
-        [1.0, 0.0],
-        None,
-        "Inicial".into(),
-    );
+    fn gradiente_restricao(self) -> Gradiente {
+        Arc::new(move |x: Ponto| self.gradiente(x))
+    }
 
-    return p;
+    fn restricao_intervalar(self) -> FuncaoIntervalar {
+        Arc::new(move |x: Caixa| self.valor_intervalar(x))
+    }
 }
 
-fn _problema_bohachevsky() -> Problema {
-    // f3: https://www.sfu.ca/~ssurjano/boha.html
-    let funcao_objetivo: Funcao = |x: Ponto| {
-        x[0].powi(2) + 2.0 * x[1].powi(2)
-            - 0.3 * (3.0 * 3.1415926 * x[0] + 4.0 * 3.1415926 * x[1]).cos()
-            + 0.3
-    };
-
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[1]];
-
-    let restricoes_desigualdades: Vec<Funcao> = vec![
-        // Fechar a caixinha toda
-        |x: Ponto| x[0] + x[1] - 15.0,
-        |x: Ponto| x[0] - x[1] - 15.0,
-        |x: Ponto| -x[0] + x[1] - 15.0,
-        |x: Ponto| -x[0] - x[1] - 15.0,
-        |x: Ponto| {
-            A * (x[0] - L1).powi(2) + B * (x[1] - L2).powi(2) - CC * (x[0] - L1) * (x[1] - L2)
-                + D * (x[0] - L1)
-                + E * (x[1] - L2)
-                + F
-        },
-        |x: Ponto| {
-            A * (x[0] - O1).powi(2)
-                + B * (x[1] - O2).powi(2)
-                + CC * (x[0] - O1) * (x[1] - O2)
-                + D * (x[0] - O1)
-                + E * (x[1] - O2)
-                + F
-        },
-    ];
+// π e e truncados como no texto das instancias, mantidos para que os
+// resultados não mudem
+#[allow(clippy::approx_constant)]
+const PI_TRUNCADO: NumReal = 3.1415926;
+#[allow(clippy::approx_constant)]
+const E_TRUNCADO: NumReal = 2.7182818;
+
+// Elipses das restrições de desigualdades das primeiras instancias
+const ELIPSE_L: Elipse = Elipse {
+    coeficientes: [1.0, 1.0, -1.0, -0.4, E_TRUNCADO, -9.0],
+    centro: [4.0, 4.0],
+};
+const ELIPSE_O: Elipse = Elipse {
+    coeficientes: [1.0, 1.0, 1.0, -0.4, E_TRUNCADO, -9.0],
+    centro: [2.0, 2.0],
+};
+
+// As quatro restrições ±x₀ ± x₁ - raio ≤ 0, que fecham um losango, com os
+// gradientes e as formas intervalares
+fn losango_nomeado(construtor: ConstrutorProblema, raio: NumReal) -> ConstrutorProblema {
+    let mut construtor = construtor;
+    let mut k = 1;
+    for &s0 in &[1.0, -1.0] {
+        for &s1 in &[1.0, -1.0] {
+            let nome = format!("losango_{}", k);
+            construtor = construtor
+                .desigualdade_com_gradiente(
+                    &nome,
+                    Arc::new(move |x: Ponto| s0 * x[0] + s1 * x[1] - raio),
+                    Arc::new(move |_: Ponto| [s0, s1]),
+                )
+                .intervalar(
+                    &nome,
+                    Arc::new(move |x: Caixa| s0 * x[0] + s1 * x[1] - raio),
+                );
+            k += 1;
+        }
+    }
+    construtor
+}
 
-    let p = Problema::novo(
-        funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
-        [-4.0, -4.0],
-        [4.0, 4.0],
-        [1.0, 0.0],
-        Some([0.0, 0.0]),
-        "Bohachevsky f3".into(),
-    );
+fn _problema_incial() -> Problema {
+    // f3: https://www.sfu.ca/~ssurjano/boha.html
+    let construtor = ConstrutorProblema::novo("Inicial")
+        .variavel_livre("x", 1.0)
+        .variavel_livre("y", 0.0)
+        .regiao_de_confianca([-4.0, -4.0], [4.0, 4.0])
+        .objetivo(
+            "f",
+            Arc::new(|x: Ponto| (x[0].powi(2) - x[1].powi(2) - 1.0).sqrt()),
+        )
+        .intervalar(
+            "f",
+            Arc::new(|x: Caixa| (x[0].powi(2) - x[1].powi(2) - 1.0).sqrt()),
+        )
+        .igualdade("eixo_x", Arc::new(|x: Ponto| x[1]))
+        .intervalar("eixo_x", Arc::new(|x: Caixa| x[1]));
+
+    // Fechar a caixinha toda, e as elipses
+    losango_nomeado(construtor, 15.0)
+        .desigualdade_com_gradiente(
+            "elipse_L",
+            ELIPSE_L.restricao(),
+            ELIPSE_L.gradiente_restricao(),
+        )
+        .desigualdade_com_gradiente(
+            "elipse_O",
+            ELIPSE_O.restricao(),
+            ELIPSE_O.gradiente_restricao(),
+        )
+        .intervalar("elipse_L", ELIPSE_L.restricao_intervalar())
+        .intervalar("elipse_O", ELIPSE_O.restricao_intervalar())
+        .construir()
+        .unwrap()
+}
 
-    return p;
+fn _problema_bohachevsky() -> Problema {
+    // f3: https://www.sfu.ca/~ssurjano/boha.html
+    let construtor = ConstrutorProblema::novo("Bohachevsky f3")
+        .variavel_livre("x", 1.0)
+        .variavel_livre("y", 0.0)
+        .regiao_de_confianca([-4.0, -4.0], [4.0, 4.0])
+        .objetivo_com_gradiente(
+            "bohachevsky",
+            Arc::new(|x: Ponto| {
+                x[0].powi(2) + 2.0 * x[1].powi(2)
+                    - 0.3 * (3.0 * PI_TRUNCADO * x[0] + 4.0 * PI_TRUNCADO * x[1]).cos()
+                    + 0.3
+            }),
+            Arc::new(|x: Ponto| {
+                let seno = (3.0 * PI_TRUNCADO * x[0] + 4.0 * PI_TRUNCADO * x[1]).sin();
+                [
+                    2.0 * x[0] + 0.3 * 3.0 * PI_TRUNCADO * seno,
+                    4.0 * x[1] + 0.3 * 4.0 * PI_TRUNCADO * seno,
+                ]
+            }),
+        )
+        .intervalar(
+            "bohachevsky",
+            Arc::new(|x: Caixa| {
+                x[0].powi(2) + 2.0 * x[1].powi(2)
+                    - 0.3 * (3.0 * PI_TRUNCADO * x[0] + 4.0 * PI_TRUNCADO * x[1]).cos()
+                    + 0.3
+            }),
+        )
+        .complexa(
+            "bohachevsky",
+            Arc::new(|x: [Complexo; DIM]| {
+                let c = Complexo::real;
+                x[0].powi(2) + c(2.0) * x[1].powi(2)
+                    - c(0.3) * (c(3.0 * PI_TRUNCADO) * x[0] + c(4.0 * PI_TRUNCADO) * x[1]).cos()
+                    + c(0.3)
+            }),
+        )
+        .igualdade_com_gradiente(
+            "eixo_x",
+            Arc::new(|x: Ponto| x[1]),
+            Arc::new(|_: Ponto| [0.0, 1.0]),
+        )
+        .intervalar("eixo_x", Arc::new(|x: Caixa| x[1]));
+
+    // Fechar a caixinha toda, e as elipses
+    losango_nomeado(construtor, 15.0)
+        .desigualdade_com_gradiente(
+            "elipse_L",
+            ELIPSE_L.restricao(),
+            ELIPSE_L.gradiente_restricao(),
+        )
+        .desigualdade_com_gradiente(
+            "elipse_O",
+            ELIPSE_O.restricao(),
+            ELIPSE_O.gradiente_restricao(),
+        )
+        .intervalar("elipse_L", ELIPSE_L.restricao_intervalar())
+        .intervalar("elipse_O", ELIPSE_O.restricao_intervalar())
+        .solucao([0.0, 0.0])
+        .construir()
+        .unwrap()
 }
 
 fn _problema_perm_function() -> Problema {
     // https://www.sfu.ca/~ssurjano/perm0db.html
 
-    let funcao_objetivo: Funcao = |x: Ponto| {
+    let funcao_objetivo: Funcao = Arc::new(|x: Ponto| {
         let beta = 2.0;
         let mut soma_1 = 0.0;
         for i in 1..(DIM + 1) {
@@ -107,32 +209,20 @@ fn _problema_perm_function() -> Problema {
         }
 
         soma_1
-    };
+    });
 
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0] - 1.0];
+    let restricoes_igualdades: Vec<Funcao> = vec![Arc::new(|x: Ponto| x[0] - 1.0)];
 
     let restricoes_desigualdades: Vec<Funcao> = vec![
-        |x: Ponto| x[0] - DIM as f64,
-        |x: Ponto| x[1] - DIM as f64,
-        |x: Ponto| x[0] + DIM as f64,
-        |x: Ponto| x[1] + DIM as f64,
-        |x: Ponto| {
-            A * (x[0] - L1).powi(2) + B * (x[1] - L2).powi(2) - CC * (x[0] - L1) * (x[1] - L2)
-                + D * (x[0] - L1)
-                + E * (x[1] - L2)
-                + F
-        },
-        |x: Ponto| {
-            A * (x[0] - O1).powi(2)
-                + B * (x[1] - O2).powi(2)
-                + CC * (x[0] - O1) * (x[1] - O2)
-                + D * (x[0] - O1)
-                + E * (x[1] - O2)
-                + F
-        },
+        Arc::new(|x: Ponto| x[0] - DIM as f64),
+        Arc::new(|x: Ponto| x[1] - DIM as f64),
+        Arc::new(|x: Ponto| x[0] + DIM as f64),
+        Arc::new(|x: Ponto| x[1] + DIM as f64),
+        ELIPSE_L.restricao(),
+        ELIPSE_O.restricao(),
     ];
 
-    let p = Problema::novo(
+    Problema::novo(
         funcao_objetivo,
         restricoes_desigualdades,
         restricoes_igualdades,
@@ -141,86 +231,108 @@ fn _problema_perm_function() -> Problema {
         [1.5, 1.5],
         Some([1.0, 0.5]),
         "Perm".into(),
-    );
-
-    return p;
+    )
 }
 
 fn _problema_trid_function() -> Problema {
     // https://www.sfu.ca/~ssurjano/trid.html
-
-    let funcao_objetivo: Funcao = |x: Ponto| {
-        let mut val_1 = 0.0;
-        for i in 1..(DIM + 1) {
-            val_1 += (x[i - 1] - 1.0).powi(2);
-        }
-
-        let mut val_2 = 0.0;
-        for i in 2..(DIM + 1) {
-            val_2 += x[i - 1] * x[i - 1 - 1];
-        }
-
-        val_1 - val_2
-    };
-
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0] + x[1] - 4.0];
-
-    let restricoes_desigualdades: Vec<Funcao> = vec![
-        |x: Ponto| x[0] - (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[1] - (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[0] + (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[1] + (DIM as f64).powi(DIM as i32),
-    ];
-
-    let p = Problema::novo(
-        funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
-        [-10.0, -10.0],
-        [10.0, 10.0],
-        [15.0, 7.5],
-        Some([2.0, 2.0]),
-        "Trid".into(),
-    );
-
-    return p;
+    let limite = (DIM as f64).powi(DIM as i32);
+
+    ConstrutorProblema::novo("Trid")
+        .variavel_livre("x", 15.0)
+        .variavel_livre("y", 7.5)
+        .regiao_de_confianca([-10.0, -10.0], [10.0, 10.0])
+        .objetivo_com_gradiente(
+            "trid",
+            Arc::new(|x: Ponto| {
+                let mut val_1 = 0.0;
+                for i in 1..(DIM + 1) {
+                    val_1 += (x[i - 1] - 1.0).powi(2);
+                }
+
+                let mut val_2 = 0.0;
+                for i in 2..(DIM + 1) {
+                    val_2 += x[i - 1] * x[i - 1 - 1];
+                }
+
+                val_1 - val_2
+            }),
+            Arc::new(|x: Ponto| [2.0 * (x[0] - 1.0) - x[1], 2.0 * (x[1] - 1.0) - x[0]]),
+        )
+        .intervalar(
+            "trid",
+            Arc::new(|x: Caixa| {
+                let mut val_1 = Intervalo::ponto(0.0);
+                for i in 1..(DIM + 1) {
+                    val_1 = val_1 + (x[i - 1] - 1.0).powi(2);
+                }
+
+                let mut val_2 = Intervalo::ponto(0.0);
+                for i in 2..(DIM + 1) {
+                    val_2 = val_2 + x[i - 1] * x[i - 1 - 1];
+                }
+
+                val_1 - val_2
+            }),
+        )
+        .desigualdade_com_gradiente(
+            "limite_x_1",
+            Arc::new(move |x: Ponto| x[0] - limite),
+            Arc::new(|_: Ponto| [1.0, 0.0]),
+        )
+        .intervalar("limite_x_1", Arc::new(move |x: Caixa| x[0] - limite))
+        .desigualdade_com_gradiente(
+            "limite_y_1",
+            Arc::new(move |x: Ponto| x[1] - limite),
+            Arc::new(|_: Ponto| [0.0, 1.0]),
+        )
+        .intervalar("limite_y_1", Arc::new(move |x: Caixa| x[1] - limite))
+        .desigualdade_com_gradiente(
+            "limite_x_2",
+            Arc::new(move |x: Ponto| x[0] + limite),
+            Arc::new(|_: Ponto| [1.0, 0.0]),
+        )
+        .intervalar("limite_x_2", Arc::new(move |x: Caixa| x[0] + limite))
+        .desigualdade_com_gradiente(
+            "limite_y_2",
+            Arc::new(move |x: Ponto| x[1] + limite),
+            Arc::new(|_: Ponto| [0.0, 1.0]),
+        )
+        .intervalar("limite_y_2", Arc::new(move |x: Caixa| x[1] + limite))
+        .igualdade_com_gradiente(
+            "soma",
+            Arc::new(|x: Ponto| x[0] + x[1] - 4.0),
+            Arc::new(|_: Ponto| [1.0, 1.0]),
+        )
+        .intervalar("soma", Arc::new(|x: Caixa| x[0] + x[1] - 4.0))
+        .solucao([2.0, 2.0])
+        .construir()
+        .unwrap()
 }
 
 fn _problema_sum_squares() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| {
+    let funcao_objetivo: Funcao = Arc::new(|x: Ponto| {
         let mut soma = 0.0;
 
         for i in 1..(DIM + 1) {
             soma += (i as f64) * x[i - 1].powi(2);
         }
 
-        return soma;
-    };
+        soma
+    });
 
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0]];
+    let restricoes_igualdades: Vec<Funcao> = vec![Arc::new(|x: Ponto| x[0])];
 
     let restricoes_desigualdades: Vec<Funcao> = vec![
-        |x: Ponto| x[0] - (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[1] - (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[0] + (DIM as f64).powi(DIM as i32),
-        |x: Ponto| x[1] + (DIM as f64).powi(DIM as i32),
-        |x: Ponto| {
-            A * (x[0] - L1).powi(2) + B * (x[1] - L2).powi(2) - CC * (x[0] - L1) * (x[1] - L2)
-                + D * (x[0] - L1)
-                + E * (x[1] - L2)
-                + F
-        },
-        |x: Ponto| {
-            A * (x[0] - O1).powi(2)
-                + B * (x[1] - O2).powi(2)
-                + CC * (x[0] - O1) * (x[1] - O2)
-                + D * (x[0] - O1)
-                + E * (x[1] - O2)
-                + F
-        },
+        Arc::new(|x: Ponto| x[0] - (DIM as f64).powi(DIM as i32)),
+        Arc::new(|x: Ponto| x[1] - (DIM as f64).powi(DIM as i32)),
+        Arc::new(|x: Ponto| x[0] + (DIM as f64).powi(DIM as i32)),
+        Arc::new(|x: Ponto| x[1] + (DIM as f64).powi(DIM as i32)),
+        ELIPSE_L.restricao(),
+        ELIPSE_O.restricao(),
     ];
 
-    let p = Problema::novo(
+    Problema::novo(
         funcao_objetivo,
         restricoes_desigualdades,
         restricoes_igualdades,
@@ -229,40 +341,49 @@ fn _problema_sum_squares() -> Problema {
         [1.5, 1.5],
         Some([0.0, 0.0]),
         "Sum squares".into(),
-    );
-
-    return p;
+    )
 }
 
 fn _problema_217() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| -x[1];
-
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0] * x[0] + x[1] * x[1] - 1.0];
-
-    let restricoes_desigualdades: Vec<Funcao> = vec![|x: Ponto| -(1.0 + x[0] - 2.0 * x[1])];
-
-    let p = Problema::novo(
-        funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
-        [-100.0, -100.0],
-        [100.0, 100.0],
-        [10.0, 10.0],
-        Some([0.6, 0.8]),
-        "217".into(),
-    );
-
-    return p;
+    ConstrutorProblema::novo("217")
+        .variavel_livre("x", 10.0)
+        .variavel_livre("y", 10.0)
+        .regiao_de_confianca([-100.0, -100.0], [100.0, 100.0])
+        .objetivo_com_gradiente(
+            "-y",
+            Arc::new(|x: Ponto| -x[1]),
+            Arc::new(|_: Ponto| [0.0, -1.0]),
+        )
+        .intervalar("-y", Arc::new(|x: Caixa| -x[1]))
+        .desigualdade_com_gradiente(
+            "semiplano",
+            Arc::new(|x: Ponto| -(1.0 + x[0] - 2.0 * x[1])),
+            Arc::new(|_: Ponto| [-1.0, 2.0]),
+        )
+        .intervalar("semiplano", Arc::new(|x: Caixa| -(1.0 + x[0] - 2.0 * x[1])))
+        .igualdade_com_gradiente(
+            "circulo",
+            Arc::new(|x: Ponto| x[0] * x[0] + x[1] * x[1] - 1.0),
+            Arc::new(|x: Ponto| [2.0 * x[0], 2.0 * x[1]]),
+        )
+        .intervalar(
+            "circulo",
+            Arc::new(|x: Caixa| x[0].powi(2) + x[1].powi(2) - 1.0),
+        )
+        .solucao([0.6, 0.8])
+        .construir()
+        .unwrap()
 }
 
 fn _problema_221() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| -x[0];
+    let funcao_objetivo: Funcao = Arc::new(|x: Ponto| -x[0]);
 
     let restricoes_igualdades: Vec<Funcao> = vec![];
 
-    let restricoes_desigualdades: Vec<Funcao> = vec![|x: Ponto| -((1.0 - x[0]).powi(3) - x[1])];
+    let restricoes_desigualdades: Vec<Funcao> =
+        vec![Arc::new(|x: Ponto| -((1.0 - x[0]).powi(3) - x[1]))];
 
-    let p = Problema::novo(
+    Problema::novo(
         funcao_objetivo,
         restricoes_desigualdades,
         restricoes_igualdades,
@@ -271,49 +392,91 @@ fn _problema_221() -> Problema {
         [0.25, 0.25],
         Some([1.0, 0.0]),
         "221".into(),
-    );
-
-    return p;
+    )
 }
 
 fn _problema_313() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| (x[0] - 20.0).powi(2) + (x[1] + 20.0).powi(2);
-
-    let restricoes_igualdades: Vec<Funcao> =
-        vec![|x: Ponto| ((x[0].powi(2)) / 100.0) + ((x[1].powi(2)) / 36.0) - 1.0];
+    let construtor = ConstrutorProblema::novo("313")
+        .variavel_livre("x", 0.0)
+        .variavel_livre("y", 0.0)
+        .regiao_de_confianca([-100.0, -100.0], [100.0, 100.0])
+        .objetivo_com_gradiente(
+            "distancia",
+            Arc::new(|x: Ponto| (x[0] - 20.0).powi(2) + (x[1] + 20.0).powi(2)),
+            Arc::new(|x: Ponto| [2.0 * (x[0] - 20.0), 2.0 * (x[1] + 20.0)]),
+        )
+        .intervalar(
+            "distancia",
+            Arc::new(|x: Caixa| (x[0] - 20.0).powi(2) + (x[1] + 20.0).powi(2)),
+        )
+        .igualdade_com_gradiente(
+            "elipse",
+            Arc::new(|x: Ponto| ((x[0].powi(2)) / 100.0) + ((x[1].powi(2)) / 36.0) - 1.0),
+            Arc::new(|x: Ponto| [x[0] / 50.0, x[1] / 18.0]),
+        )
+        .intervalar(
+            "elipse",
+            Arc::new(|x: Caixa| ((x[0].powi(2)) / 100.0) + ((x[1].powi(2)) / 36.0) - 1.0),
+        );
+
+    losango_nomeado(construtor, 15.0)
+        .solucao([7.809, -3.748])
+        .construir()
+        .unwrap()
+}
 
-    let restricoes_desigualdades: Vec<Funcao> = vec![
-        |x: Ponto| x[0] + x[1] - 15.0,
-        |x: Ponto| x[0] - x[1] - 15.0,
-        |x: Ponto| -x[0] + x[1] - 15.0,
-        |x: Ponto| -x[0] - x[1] - 15.0,
-    ];
+// O problema 313 com as restrições avaliadas juntas por uma só função
+// vetorial, c(x) = (g₁(x), ..., g₄(x), h(x))
+fn _problema_313_vetorial() -> Problema {
+    let funcao_objetivo: Funcao =
+        Arc::new(|x: Ponto| (x[0] - 20.0).powi(2) + (x[1] + 20.0).powi(2));
+
+    let restricoes = RestricoesVetoriais {
+        funcao: Arc::new(|x: Ponto| {
+            vec![
+                x[0] + x[1] - 15.0,
+                x[0] - x[1] - 15.0,
+                -x[0] + x[1] - 15.0,
+                -x[0] - x[1] - 15.0,
+                ((x[0].powi(2)) / 100.0) + ((x[1].powi(2)) / 36.0) - 1.0,
+            ]
+        }),
+        jacobiana: Some(Arc::new(|x: Ponto| {
+            vec![
+                [1.0, 1.0],
+                [1.0, -1.0],
+                [-1.0, 1.0],
+                [-1.0, -1.0],
+                [x[0] / 50.0, x[1] / 18.0],
+            ]
+        })),
+        mi: 4,
+        me: 1,
+    };
 
-    let p = Problema::novo(
+    Problema::novo_vetorial(
         funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
+        restricoes,
         [-100.0, -100.0],
         [100.0, 100.0],
         [0.0, 0.0],
         Some([7.809, -3.748]),
-        "313".into(),
-    );
-
-    return p;
+        "313 vetorial".into(),
+    )
 }
 
 fn _problema_325() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| x[0].powi(2) + x[1];
+    let funcao_objetivo: Funcao = Arc::new(|x: Ponto| x[0].powi(2) + x[1]);
 
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0].powi(2) + x[1].powi(2) - 9.0];
+    let restricoes_igualdades: Vec<Funcao> =
+        vec![Arc::new(|x: Ponto| x[0].powi(2) + x[1].powi(2) - 9.0)];
 
-    let restricoes_desigualdades: Vec<Funcao> =
-        vec![|x: Ponto| -(-(x[0] + x[1]) + 1.0), |x: Ponto| {
-            -(-(x[0] + x[1].powi(2)) + 1.0)
-        }];
+    let restricoes_desigualdades: Vec<Funcao> = vec![
+        Arc::new(|x: Ponto| -(-(x[0] + x[1]) + 1.0)),
+        Arc::new(|x: Ponto| -(-(x[0] + x[1].powi(2)) + 1.0)),
+    ];
 
-    let p = Problema::novo(
+    let mut problema = Problema::novo(
         funcao_objetivo,
         restricoes_desigualdades,
         restricoes_igualdades,
@@ -324,40 +487,65 @@ fn _problema_325() -> Problema {
         "325".into(),
     );
 
-    return p;
+    // Formas complexas, para as derivadas pelo passo complexo
+    let um = Complexo::real(1.0);
+    problema.objetivo_complexo = Some(Arc::new(|x: [Complexo; DIM]| x[0].powi(2) + x[1]));
+    problema.desigualdades_complexas = vec![
+        Some(Arc::new(move |x: [Complexo; DIM]| -(-(x[0] + x[1]) + um))),
+        Some(Arc::new(move |x: [Complexo; DIM]| {
+            -(-(x[0] + x[1].powi(2)) + um)
+        })),
+    ];
+    problema.igualdades_complexas = vec![Some(Arc::new(|x: [Complexo; DIM]| {
+        x[0].powi(2) + x[1].powi(2) - Complexo::real(9.0)
+    }))];
+
+    problema
 }
 
 fn _problema_14() -> Problema {
-    let funcao_objetivo: Funcao = |x: Ponto| (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2);
-
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0] - 2.0 * x[1] + 1.0];
-
-    let restricoes_desigualdades: Vec<Funcao> =
-        vec![|x: Ponto| -((-0.25 * (x[0].powi(2))) - x[1].powi(2) + 1.0)];
-
-    let p = Problema::novo(
-        funcao_objetivo,
-        restricoes_desigualdades,
-        restricoes_igualdades,
-        [-100.0, -100.0],
-        [100.0, 100.0],
-        [2.0, 2.0],
-        Some([0.8228756555322954, 0.9114378277661477]),
-        "14".into(),
-    );
-
-    return p;
+    ConstrutorProblema::novo("14")
+        .variavel_livre("x", 2.0)
+        .variavel_livre("y", 2.0)
+        .regiao_de_confianca([-100.0, -100.0], [100.0, 100.0])
+        .objetivo_com_gradiente(
+            "distancia",
+            Arc::new(|x: Ponto| (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2)),
+            Arc::new(|x: Ponto| [2.0 * (x[0] - 2.0), 2.0 * (x[1] - 1.0)]),
+        )
+        .intervalar(
+            "distancia",
+            Arc::new(|x: Caixa| (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2)),
+        )
+        .desigualdade_com_gradiente(
+            "elipse",
+            Arc::new(|x: Ponto| -((-0.25 * (x[0].powi(2))) - x[1].powi(2) + 1.0)),
+            Arc::new(|x: Ponto| [0.5 * x[0], 2.0 * x[1]]),
+        )
+        .intervalar(
+            "elipse",
+            Arc::new(|x: Caixa| -((-0.25 * (x[0].powi(2))) - x[1].powi(2) + 1.0)),
+        )
+        .igualdade_com_gradiente(
+            "reta",
+            Arc::new(|x: Ponto| x[0] - 2.0 * x[1] + 1.0),
+            Arc::new(|_: Ponto| [1.0, -2.0]),
+        )
+        .intervalar("reta", Arc::new(|x: Caixa| x[0] - 2.0 * x[1] + 1.0))
+        .solucao([0.8228756555322954, 0.9114378277661477])
+        .construir()
+        .unwrap()
 }
 
 fn _problema_1() -> Problema {
     let funcao_objetivo: Funcao =
-        |x: Ponto| 100.0 * (x[1] - x[0].powi(2)).powi(2) + (1.0 - x[0]).powi(2);
+        Arc::new(|x: Ponto| 100.0 * (x[1] - x[0].powi(2)).powi(2) + (1.0 - x[0]).powi(2));
 
-    let restricoes_igualdades: Vec<Funcao> = vec![|x: Ponto| x[0] - 1.0];
+    let restricoes_igualdades: Vec<Funcao> = vec![Arc::new(|x: Ponto| x[0] - 1.0)];
 
-    let restricoes_desigualdades: Vec<Funcao> = vec![|x: Ponto| -x[1] - 1.5];
+    let restricoes_desigualdades: Vec<Funcao> = vec![Arc::new(|x: Ponto| -x[1] - 1.5)];
 
-    let p = Problema::novo(
+    let mut problema = Problema::novo(
         funcao_objetivo,
         restricoes_desigualdades,
         restricoes_igualdades,
@@ -368,7 +556,49 @@ fn _problema_1() -> Problema {
         "1".into(),
     );
 
-    return p;
+    // Formas complexas, para as derivadas pelo passo complexo
+    problema.objetivo_complexo = Some(Arc::new(|x: [Complexo; DIM]| {
+        Complexo::real(100.0) * (x[1] - x[0].powi(2)).powi(2) + (Complexo::real(1.0) - x[0]).powi(2)
+    }));
+    problema.desigualdades_complexas = vec![Some(Arc::new(|x: [Complexo; DIM]| {
+        -x[1] - Complexo::real(1.5)
+    }))];
+    problema.igualdades_complexas = vec![Some(Arc::new(|x: [Complexo; DIM]| {
+        x[0] - Complexo::real(1.0)
+    }))];
+
+    problema
+}
+
+// Problema inteiro misto: o ponto mais proximo de (2.4, 1.4) no disco de
+// raio 3, com x inteiro. A relaxação cai no meio do disco, e x ≥ 3 só deixa
+// (3, 0), então o otimo é (2, 1.4). Nesse nó o SCP não converge, e a cota
+// vem das formas intervalares
+fn _problema_inteiro_disco() -> Problema {
+    ConstrutorProblema::novo("Inteiro disco")
+        .variavel_inteira("x", -3.0, 3.0, 0.0)
+        .variavel("y", -3.0, 3.0, 0.0)
+        .objetivo_com_gradiente(
+            "distancia",
+            Arc::new(|x: Ponto| (x[0] - 2.4).powi(2) + (x[1] - 1.4).powi(2)),
+            Arc::new(|x: Ponto| [2.0 * (x[0] - 2.4), 2.0 * (x[1] - 1.4)]),
+        )
+        .intervalar(
+            "distancia",
+            Arc::new(|x: Caixa| (x[0] - 2.4).powi(2) + (x[1] - 1.4).powi(2)),
+        )
+        .desigualdade_com_gradiente(
+            "disco",
+            Arc::new(|x: Ponto| x[0].powi(2) + x[1].powi(2) - 9.0),
+            Arc::new(|x: Ponto| [2.0 * x[0], 2.0 * x[1]]),
+        )
+        .intervalar(
+            "disco",
+            Arc::new(|x: Caixa| x[0].powi(2) + x[1].powi(2) - 9.0),
+        )
+        .solucao([2.0, 1.4])
+        .construir()
+        .unwrap()
 }
 
 fn problemas_cuia() -> Vec<Problema> {
@@ -392,10 +622,17 @@ fn problemas_17() -> Vec<Problema> {
         // _problema_217(), // FUNCIONA
         // _problema_221(), // NÃO FUNCIONA? NEM ENCONTRA SEM 1C
         // _problema_313(), // NÃO FUNCIONA??
+        // _problema_313_vetorial(), // NÃO FUNCIONA??, o mesmo que o 313
         // _problema_325(), // NÃO FUNCIONA?? FUNCIONA MELHOR SEM 1C
     ]
 }
 
+fn problemas_inteiros() -> Vec<Problema> {
+    vec![
+        // _problema_inteiro_disco(), // FUNCIONA
+    ]
+}
+
 pub fn gerar_instancias() -> Vec<Problema> {
     let mut lista: Vec<Problema> = Vec::new();
 
@@ -408,6 +645,30 @@ pub fn gerar_instancias() -> Vec<Problema> {
     for _p in problemas_17() {
         lista.push(_p);
     }
+    for _p in problemas_inteiros() {
+        lista.push(_p);
+    }
 
     lista
 }
+
+// Todas as instancias, inclusive as que estão fora das listas acima,
+// usadas pelos testes
+#[cfg(test)]
+pub fn todas_as_instancias() -> Vec<Problema> {
+    vec![
+        _problema_incial(),
+        _problema_bohachevsky(),
+        _problema_perm_function(),
+        _problema_trid_function(),
+        _problema_sum_squares(),
+        _problema_217(),
+        _problema_221(),
+        _problema_313(),
+        _problema_313_vetorial(),
+        _problema_325(),
+        _problema_14(),
+        _problema_1(),
+        _problema_inteiro_disco(),
+    ]
+}
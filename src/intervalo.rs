@@ -0,0 +1,373 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::defs::NumReal;
+
+// Intervalo fechado [inf, sup] com arredondamento para fora: cada
+// operação afasta os extremos em um ulp, e as funções da libm, que podem
+// errar em mais de um, em dois. O resultado sempre contém todos os valores
+// da operação sobre pontos dos intervalos. Serve para escrever formas
+// intervalares das funções dos problemas, usadas nas cotas da busca global
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intervalo {
+    pub inf: NumReal,
+    pub sup: NumReal,
+}
+
+// Afasta os extremos em n ulps, sem mexer nos infinitos
+fn para_fora(inf: NumReal, sup: NumReal, n: usize) -> Intervalo {
+    let mut resultado = Intervalo { inf, sup };
+    for _ in 0..n {
+        resultado.inf = resultado.inf.next_down();
+        resultado.sup = resultado.sup.next_up();
+    }
+    resultado
+}
+
+fn minimo(valores: &[NumReal]) -> NumReal {
+    valores
+        .iter()
+        .copied()
+        .fold(NumReal::INFINITY, NumReal::min)
+}
+
+fn maximo(valores: &[NumReal]) -> NumReal {
+    valores
+        .iter()
+        .copied()
+        .fold(NumReal::NEG_INFINITY, NumReal::max)
+}
+
+#[allow(dead_code)]
+impl Intervalo {
+    pub fn novo(inf: NumReal, sup: NumReal) -> Self {
+        Self { inf, sup }
+    }
+
+    // Numero real visto como intervalo degenerado
+    pub fn ponto(x: NumReal) -> Self {
+        Self { inf: x, sup: x }
+    }
+
+    // A reta toda, resultado das operações sem cota finita
+    pub fn reta() -> Self {
+        Self {
+            inf: NumReal::NEG_INFINITY,
+            sup: NumReal::INFINITY,
+        }
+    }
+
+    pub fn largura(&self) -> NumReal {
+        self.sup - self.inf
+    }
+
+    pub fn meio(&self) -> NumReal {
+        self.inf + (self.sup - self.inf) / 2.0
+    }
+
+    pub fn contem(&self, x: NumReal) -> bool {
+        self.inf <= x && x <= self.sup
+    }
+
+    // Metades [inf, meio] e [meio, sup]
+    pub fn dividir(&self) -> (Self, Self) {
+        let meio = self.meio();
+        (
+            Intervalo::novo(self.inf, meio),
+            Intervalo::novo(meio, self.sup),
+        )
+    }
+
+    pub fn abs(self) -> Self {
+        if self.inf >= 0.0 {
+            self
+        } else if self.sup <= 0.0 {
+            -self
+        } else {
+            Intervalo::novo(0.0, (-self.inf).max(self.sup))
+        }
+    }
+
+    // Potencia inteira. As pares não descem abaixo de zero, o que a
+    // multiplicação repetida não consegue ver
+    pub fn powi(self, n: i32) -> Self {
+        if n == 0 {
+            return Intervalo::ponto(1.0);
+        }
+        if n < 0 {
+            return Intervalo::ponto(1.0) / self.powi(-n);
+        }
+
+        let base = if n % 2 == 0 { self.abs() } else { self };
+        let mut inf = base.inf.powi(n);
+        let mut sup = base.sup.powi(n);
+        if n % 2 == 0 && base.inf == 0.0 {
+            inf = 0.0;
+        }
+        if inf > sup {
+            std::mem::swap(&mut inf, &mut sup);
+        }
+        let resultado = para_fora(inf, sup, 2);
+        if n % 2 == 0 {
+            Intervalo::novo(resultado.inf.max(0.0), resultado.sup)
+        } else {
+            resultado
+        }
+    }
+
+    // Raiz da parte não negativa do intervalo. Sem parte não negativa, a
+    // raiz não existe e o resultado é [NaN, NaN]
+    pub fn sqrt(self) -> Self {
+        if self.sup < 0.0 {
+            return Intervalo::novo(NumReal::NAN, NumReal::NAN);
+        }
+        let resultado = para_fora(self.inf.max(0.0).sqrt(), self.sup.sqrt(), 1);
+        Intervalo::novo(resultado.inf.max(0.0), resultado.sup)
+    }
+
+    pub fn exp(self) -> Self {
+        let resultado = para_fora(self.inf.exp(), self.sup.exp(), 2);
+        Intervalo::novo(resultado.inf.max(0.0), resultado.sup)
+    }
+
+    // O cosseno é monotono entre os multiplos de π, então os extremos são
+    // os dos pontos das pontas ou ±1, quando o intervalo passa por um
+    // multiplo de π
+    pub fn cos(self) -> Self {
+        // Uma volta inteira, ou extremos NaN ou infinitos
+        let largura = self.largura();
+        if largura.is_nan() || largura >= 2.0 * PI {
+            return Intervalo::novo(-1.0, 1.0);
+        }
+
+        let pontas = [self.inf.cos(), self.sup.cos()];
+        let mut inf = minimo(&pontas);
+        let mut sup = maximo(&pontas);
+
+        // Multiplos pares de π dão 1, e impares -1. A conta de k é feita
+        // com folga, já que incluir um extremo a mais só alarga o resultado
+        let primeiro = (self.inf / PI).floor() as i64;
+        let ultimo = (self.sup / PI).ceil() as i64;
+        for k in primeiro..=ultimo {
+            let x = k as NumReal * PI;
+            if x >= self.inf - PI * NumReal::EPSILON && x <= self.sup + PI * NumReal::EPSILON {
+                if k % 2 == 0 {
+                    sup = 1.0;
+                } else {
+                    inf = -1.0;
+                }
+            }
+        }
+
+        let resultado = para_fora(inf, sup, 2);
+        Intervalo::novo(resultado.inf.max(-1.0), resultado.sup.min(1.0))
+    }
+
+    // sen x = cos(x - π/2)
+    pub fn sin(self) -> Self {
+        (self - PI / 2.0).cos()
+    }
+}
+
+impl Add for Intervalo {
+    type Output = Intervalo;
+    fn add(self, outro: Intervalo) -> Intervalo {
+        para_fora(self.inf + outro.inf, self.sup + outro.sup, 1)
+    }
+}
+
+impl Sub for Intervalo {
+    type Output = Intervalo;
+    fn sub(self, outro: Intervalo) -> Intervalo {
+        para_fora(self.inf - outro.sup, self.sup - outro.inf, 1)
+    }
+}
+
+impl Mul for Intervalo {
+    type Output = Intervalo;
+    fn mul(self, outro: Intervalo) -> Intervalo {
+        // 0 · ∞ é tratado como 0, já que o zero do intervalo é exato
+        let produto = |a: NumReal, b: NumReal| {
+            if a == 0.0 || b == 0.0 {
+                0.0
+            } else {
+                a * b
+            }
+        };
+        let produtos = [
+            produto(self.inf, outro.inf),
+            produto(self.inf, outro.sup),
+            produto(self.sup, outro.inf),
+            produto(self.sup, outro.sup),
+        ];
+        para_fora(minimo(&produtos), maximo(&produtos), 1)
+    }
+}
+
+impl Div for Intervalo {
+    type Output = Intervalo;
+    fn div(self, outro: Intervalo) -> Intervalo {
+        // Divisor com zero dá a reta toda
+        if outro.contem(0.0) {
+            return Intervalo::reta();
+        }
+        let quocientes = [
+            self.inf / outro.inf,
+            self.inf / outro.sup,
+            self.sup / outro.inf,
+            self.sup / outro.sup,
+        ];
+        para_fora(minimo(&quocientes), maximo(&quocientes), 1)
+    }
+}
+
+impl Neg for Intervalo {
+    type Output = Intervalo;
+    fn neg(self) -> Intervalo {
+        Intervalo::novo(-self.sup, -self.inf)
+    }
+}
+
+// Operações com constantes reais, para escrever as funções como as reais
+
+impl Add<NumReal> for Intervalo {
+    type Output = Intervalo;
+    fn add(self, outro: NumReal) -> Intervalo {
+        self + Intervalo::ponto(outro)
+    }
+}
+
+impl Sub<NumReal> for Intervalo {
+    type Output = Intervalo;
+    fn sub(self, outro: NumReal) -> Intervalo {
+        self - Intervalo::ponto(outro)
+    }
+}
+
+impl Mul<NumReal> for Intervalo {
+    type Output = Intervalo;
+    fn mul(self, outro: NumReal) -> Intervalo {
+        self * Intervalo::ponto(outro)
+    }
+}
+
+impl Div<NumReal> for Intervalo {
+    type Output = Intervalo;
+    fn div(self, outro: NumReal) -> Intervalo {
+        self / Intervalo::ponto(outro)
+    }
+}
+
+impl Add<Intervalo> for NumReal {
+    type Output = Intervalo;
+    fn add(self, outro: Intervalo) -> Intervalo {
+        Intervalo::ponto(self) + outro
+    }
+}
+
+impl Sub<Intervalo> for NumReal {
+    type Output = Intervalo;
+    fn sub(self, outro: Intervalo) -> Intervalo {
+        Intervalo::ponto(self) - outro
+    }
+}
+
+impl Mul<Intervalo> for NumReal {
+    type Output = Intervalo;
+    fn mul(self, outro: Intervalo) -> Intervalo {
+        Intervalo::ponto(self) * outro
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    const TOL_TESTE: NumReal = 1e-12;
+
+    // Confere que o resultado intervalar contém a função real em pontos
+    // espalhados pelo intervalo, incluindo as pontas
+    fn contem_amostras(
+        intervalo: Intervalo,
+        intervalar: impl Fn(Intervalo) -> Intervalo,
+        real: impl Fn(NumReal) -> NumReal,
+    ) {
+        let resultado = intervalar(intervalo);
+        let amostras = 1000;
+        for k in 0..=amostras {
+            let x = intervalo.inf + intervalo.largura() * k as NumReal / amostras as NumReal;
+            assert!(
+                resultado.contem(real(x)),
+                "{:?} não contém f({}) = {}",
+                resultado,
+                x,
+                real(x)
+            );
+        }
+    }
+
+    #[test]
+    fn potencias_pares_e_impares_contem_a_imagem() {
+        let intervalos = [
+            Intervalo::novo(-2.0, 1.0),
+            Intervalo::novo(-3.0, -0.5),
+            Intervalo::novo(0.0, 2.5),
+            Intervalo::novo(0.25, 0.75),
+        ];
+        for intervalo in intervalos {
+            for n in 1..=5 {
+                contem_amostras(intervalo, |x| x.powi(n), |x| x.powi(n));
+            }
+        }
+
+        // A par não desce abaixo de zero, e a impar mantém o sinal
+        let quadrado = Intervalo::novo(-2.0, 1.0).powi(2);
+        assert_eq!(quadrado.inf, 0.0);
+        assert!((quadrado.sup - 4.0).abs() < TOL_TESTE);
+        let cubo = Intervalo::novo(-2.0, 1.0).powi(3);
+        assert!((cubo.inf + 8.0).abs() < TOL_TESTE);
+        assert!((cubo.sup - 1.0).abs() < TOL_TESTE);
+    }
+
+    #[test]
+    fn seno_e_cosseno_passam_pelos_multiplos_de_pi() {
+        let intervalos = [
+            Intervalo::novo(-0.1, 0.1),
+            Intervalo::novo(0.0, PI),
+            Intervalo::novo(PI / 2.0, 3.0 * PI / 2.0),
+            Intervalo::novo(-3.0 * PI, -2.5 * PI),
+            Intervalo::novo(2.0 * PI - 0.01, 2.0 * PI + 0.01),
+            Intervalo::novo(1.0, 7.0),
+        ];
+        for intervalo in intervalos {
+            contem_amostras(intervalo, Intervalo::cos, NumReal::cos);
+            contem_amostras(intervalo, Intervalo::sin, NumReal::sin);
+        }
+
+        // O cosseno chega a -1 em π e o seno a 1 em π/2, que são pontos
+        // interiores, e não das pontas
+        let cosseno = Intervalo::novo(PI / 2.0, 3.0 * PI / 2.0).cos();
+        assert_eq!(cosseno.inf, -1.0);
+        assert!(cosseno.sup.abs() < TOL_TESTE);
+        let seno = Intervalo::novo(0.0, PI).sin();
+        assert_eq!(seno.sup, 1.0);
+        assert!(seno.inf.abs() < TOL_TESTE);
+    }
+
+    #[test]
+    fn divisao_por_intervalo_com_zero_da_a_reta() {
+        let numerador = Intervalo::novo(1.0, 2.0);
+        for divisor in [
+            Intervalo::novo(-1.0, 2.0),
+            Intervalo::novo(0.0, 3.0),
+            Intervalo::novo(-3.0, 0.0),
+        ] {
+            assert_eq!(numerador / divisor, Intervalo::reta());
+        }
+
+        let quociente = numerador / Intervalo::novo(-2.0, -1.0);
+        assert!(quociente.contem(-2.0) && quociente.contem(-0.5));
+        assert!((quociente.inf + 2.0).abs() < TOL_TESTE);
+        assert!((quociente.sup + 0.5).abs() < TOL_TESTE);
+    }
+}
@@ -1,82 +1,116 @@
 use crate::{
-    defs::{MultiplicadoresDeLagrange, NumReal, Ponto, Problema, DIM},
-    estimativa_mul_lagrange::extrair_multiplicadores_de_lagrange,
-    lagrangianas::{lagrangiana, lagrangiana_penalizada},
-    matricial::matriz_e_vetores_problema_linear,
+    arquivo_linear::ProblemaLinearArquivo,
+    backend_linear::EstadoProblemaLinear,
+    defs::{Configuracao, MultiplicadoresDeLagrange, NumReal, Ponto, Problema, DIM},
+    estimativa_mul_lagrange::estimar_multiplicadores,
+    lagrangianas::{gradiente_lagrangiana, hessiana_lagrangiana, lagrangiana_penalizada},
+    matricial::{matriz_e_vetores_problema_linear, nomes_colunas_problema_linear},
     ponto_estacionario::checar_ponto_estacionario,
-    prob_linear::{resolver_problema_dual_matriz, resolver_problema_linear_matriz},
+    prob_linear::SessaoProblemaLinear,
+    quase_newton::AproximacaoHessiana,
     utils::{
-        auto_grad, bfgs, hessiana_exata, line_search, norma, produto_escalar,
-        prox_o_suficiente_de_zero, quase_iguais, soma_pontos, subtracao_pontos, vec_arr_fixo,
+        line_search, norma, produto_escalar, prox_o_suficiente_de_zero, soma_pontos,
+        subtracao_pontos, vec_arr_fixo,
     },
 };
 
 // Computa o resultado das iterações de subproblemas
-// lineares
+// lineares, ou o estado do subproblema linear que não teve solução
+#[allow(clippy::type_complexity)]
 pub fn iteracoes_lineares(
     problema: &Problema,
     x: Ponto,
-    hessiana: Vec<Vec<NumReal>>,
-) -> (
-    Ponto,                     // Ponto encontrado
-    Ponto,                     // Direção de descida d
-    Vec<NumReal>,              // Vetor de relaxamentos tg
-    Vec<NumReal>,              // Vetor de relaxamentos th⁺
-    Vec<NumReal>,              // Vetor de relaxamentos th⁻
-    MultiplicadoresDeLagrange, // Estimativa dos multiplicadores de lagrange
-    Vec<Vec<NumReal>>,         // Aproximação da Hessiana no ponto
-    bool,                      // Encontrado ponto KKT estacionario
-    NumReal, // Tamanho do passo tomado tomado na direção d para chegar ao ponto encontrado
-) {
+    hessiana: AproximacaoHessiana,
+    k: usize,
+    configuracao: &Configuracao,
+) -> Result<
+    (
+        Ponto,                     // Ponto encontrado
+        Ponto,                     // Direção de descida d
+        Vec<NumReal>,              // Vetor de relaxamentos tg
+        Vec<NumReal>,              // Vetor de relaxamentos th⁺
+        Vec<NumReal>,              // Vetor de relaxamentos th⁻
+        MultiplicadoresDeLagrange, // Estimativa dos multiplicadores de lagrange
+        AproximacaoHessiana,       // Aproximação da Hessiana no ponto
+        bool,                      // Encontrado ponto KKT estacionario
+        NumReal, // Tamanho do passo tomado tomado na direção d para chegar ao ponto encontrado
+        NumReal, // Maior deslocamento aplicado à hessiana para torná-la definida positiva
+    ),
+    EstadoProblemaLinear,
+> {
     let mut ponto_atual = x;
     let mut hessiana_atual = hessiana;
-    let mut solucao_primal;
+    let mut solucao;
     let mut multiplicadores_de_lagrange: MultiplicadoresDeLagrange;
     let mut direcoes = Vec::new();
     let mut alpha;
+    let mut maior_deslocamento: NumReal = 0.0;
     let mut i = 1;
 
+    // Sessão do problema linear, que reaproveita a base otima de uma
+    // subiteração para a seguinte
+    let mut sessao = SessaoProblemaLinear::nova(configuracao);
+
     loop {
         // Vetores de coeficientes de um problema de minimização do seguinte tipo:
         // min cᵀx
         // s. a: a·x ≥ b
-        let (matriz_a, vetor_b, vetor_c) =
-            matriz_e_vetores_problema_linear(problema, ponto_atual, &direcoes, &hessiana_atual);
+        // A restrição 1c usa a hessiana como produto interno, que precisa ser
+        // definido positivo para que as direções sejam de fato conjugadas
+        let (hessiana_1c, deslocamento) =
+            hessiana_atual.regularizada(configuracao.regularizacao_hessiana);
+        maior_deslocamento = maior_deslocamento.max(deslocamento);
 
-        // let ti = SystemTime::now();
-        solucao_primal = resolver_problema_linear_matriz(problema, &matriz_a, &vetor_b, &vetor_c);
-        // let tf = ti.elapsed().unwrap();
-        // println!("Solução do problema linear no ponto {:?}: {:?}", ponto_atual, solucao_primal);
-        // println!("Resolvido em: {}ns", tf.as_nanos());
-        // println!("Resolvido em: {}s", tf.as_secs_f64());
+        let (matriz_a, vetor_b, vetor_c, nomes_linhas) =
+            matriz_e_vetores_problema_linear(problema, ponto_atual, &direcoes, &hessiana_1c);
 
+        // Grava o subproblema antes de resolver, para que possa ser inspecionado
+        // mesmo quando não tem solução
+        if let Some(diretorio) = &configuracao.diretorio_exportacao_linear {
+            let arquivo = ProblemaLinearArquivo {
+                nome: format!("{}_k{}_i{}", problema.nome.replace(' ', "_"), k, i),
+                a: matriz_a.clone(),
+                b: vetor_b.clone(),
+                c: vetor_c.clone(),
+                nomes_linhas: nomes_linhas.clone(),
+                nomes_colunas: nomes_colunas_problema_linear(problema),
+            };
+            if let Err(erro) = arquivo.exportar(diretorio) {
+                println!("Falha ao gravar o subproblema linear ... {}", erro);
+            }
+        }
+
+        // Uma unica resolução fornece tanto a solução primal quanto os valores duais
         // let ti = SystemTime::now();
-        let solucao_dual = resolver_problema_dual_matriz(&matriz_a, &vetor_b, &vetor_c);
+        solucao = sessao.resolver(problema, &matriz_a, &vetor_b, &vetor_c, &nomes_linhas)?;
         // let tf = ti.elapsed().unwrap();
-        // println!("Solução do problema dual no ponto {:?}: {:?}", ponto_atual, solucao_dual);
+        // println!("Solução do problema linear no ponto {:?}: {:?}", ponto_atual, solucao);
         // println!("Resolvido em: {}ns", tf.as_nanos());
         // println!("Resolvido em: {}s", tf.as_secs_f64());
-        // println!();
-
-        // Solução do problema primal e dual deve ser igual a menos de um
-        // erro computacional
-        assert!(quase_iguais(
-            &Vec::from([solucao_dual.0]),
-            &Vec::from([solucao_primal.0]),
-            1
-        ));
-
-        // Extração da aproximação dos multiplicadores de lagrange da solução do problema dual
-        multiplicadores_de_lagrange = extrair_multiplicadores_de_lagrange(problema, solucao_dual);
+
+        // Aproximação dos multiplicadores de lagrange, dos valores duais ou por
+        // minimos quadrados, conforme a configuração
+        multiplicadores_de_lagrange =
+            estimar_multiplicadores(problema, ponto_atual, &solucao, configuracao);
         // dbg!(&multiplicadores_de_lagrange);
 
         // Separa informações da solução do problema primal
-        let (_obj, d, tg, thp, thm) = solucao_primal.clone();
+        let (d, tg, thp, thm) = (
+            solucao.d.clone(),
+            solucao.tg.clone(),
+            solucao.thp.clone(),
+            solucao.thm.clone(),
+        );
 
         // Já tendo extraido os multiplicadores de lagrange, verifica se é um ponto KKT
         // estacionario
-        if checar_ponto_estacionario(problema, &ponto_atual, &multiplicadores_de_lagrange) {
-            return (
+        if checar_ponto_estacionario(
+            problema,
+            &ponto_atual,
+            &multiplicadores_de_lagrange,
+            &configuracao.tolerancias_kkt,
+        ) {
+            return Ok((
                 ponto_atual,
                 vec_arr_fixo(d),
                 tg,
@@ -86,15 +120,18 @@ pub fn iteracoes_lineares(
                 hessiana_atual,
                 true,
                 0.0,
-            );
+                maior_deslocamento,
+            ));
         }
 
         // Gera a função lagrangiana penalizada a partir das informações
-        let funcao_lagrangiana_penalizada =
-            lagrangiana_penalizada(problema.clone(), multiplicadores_de_lagrange.clone());
+        let funcao_lagrangiana_penalizada = lagrangiana_penalizada(
+            problema.copia_compartilhada(),
+            multiplicadores_de_lagrange.clone(),
+        );
 
         // Copia a salva a direção de descida encontrada
-        let d_tmp = vec_arr_fixo(solucao_primal.1.clone());
+        let d_tmp = vec_arr_fixo(solucao.d.clone());
         direcoes.push(d_tmp);
 
         // Faz uma busca em linha na direção de descida, encontrando um tamanho otimo para o passo
@@ -109,18 +146,19 @@ pub fn iteracoes_lineares(
 
         // println!("ponto subiter lp = {:?}", ponto_atual);
 
-        // Gera a funcção lagrangiana do problema, já sabendo dos multiplicadores
-        let funcao_lagrangiana = lagrangiana(problema.clone(), multiplicadores_de_lagrange.clone());
-
         // Calcula a diferença entre os gradientes em cada ponto
-        let g_i = auto_grad(ponto_anterior, &funcao_lagrangiana);
-        let g_i1 = auto_grad(ponto_atual, &funcao_lagrangiana);
+        let g_i = gradiente_lagrangiana(problema, &multiplicadores_de_lagrange, ponto_anterior);
+        let g_i1 = gradiente_lagrangiana(problema, &multiplicadores_de_lagrange, ponto_atual);
         let yi = subtracao_pontos(g_i1, g_i);
 
         // Usa a variação do gradiente e do ponto para calcular
-        // a atualização da hessiana
-        hessiana_atual = bfgs(hessiana_atual, aidi, yi);
-        hessiana_atual = hessiana_exata(&funcao_lagrangiana, ponto_atual);
+        // a atualização da hessiana, da forma escolhida na configuração
+        hessiana_atual.atualizar(
+            aidi,
+            yi,
+            |x: Ponto| hessiana_lagrangiana(problema, &multiplicadores_de_lagrange, x),
+            ponto_atual,
+        );
 
         // Condições de parada das subiterações lineares
 
@@ -141,9 +179,10 @@ pub fn iteracoes_lineares(
 
         // Condição de parada linear 3
         // Para se todas as variavéis t de relaxamento não relaxam mais o problema
+        let (g_atual, h_atual) = problema.restricoes_em(ponto_atual);
         let mut parar_na_cond_3 = true;
         for j in 0..problema.mi() {
-            if tg[j] < problema.restricoes_desigualdades[j](ponto_atual) {
+            if tg[j] < g_atual[j] {
                 parar_na_cond_3 = false;
                 break;
             }
@@ -151,7 +190,7 @@ pub fn iteracoes_lineares(
 
         if parar_na_cond_3 {
             for r in 0..problema.me() {
-                let val = problema.restricoes_igualdades[r](ponto_atual).abs();
+                let val = h_atual[r].abs();
                 if thp[r] < val {
                     parar_na_cond_3 = false;
                     break;
@@ -210,20 +249,26 @@ pub fn iteracoes_lineares(
             break;
         }
 
+        // Condição de parada linear 6
+        // Se o orçamento de avaliações acabou, a iteração não linear decide o que fazer
+        if problema.orcamento_esgotado() {
+            break;
+        }
+
         i += 1;
     }
 
     // Extrai os dados até o momento e retorna
-    let (_obj, d, tg, thp, thm) = solucao_primal;
-    return (
+    Ok((
         ponto_atual,
-        vec_arr_fixo(d),
-        tg,
-        thp,
-        thm,
+        vec_arr_fixo(solucao.d),
+        solucao.tg,
+        solucao.thp,
+        solucao.thm,
         multiplicadores_de_lagrange,
         hessiana_atual,
         false,
         alpha,
-    );
+        maior_deslocamento,
+    ))
 }
@@ -1,6 +1,6 @@
 use crate::{
     defs::{MultiplicadoresDeLagrange, NumReal, Ponto, Problema, RHO},
-    utils::max,
+    utils::{hessiana_exata, max, produto_escalar, soma_pontos},
 };
 
 // Retorna a função lagrangiana como definida no artigo
@@ -10,24 +10,60 @@ pub fn lagrangiana(
 ) -> impl Fn(Ponto) -> NumReal {
     move |x: Ponto| {
         // f(x)
-        let f_x = (problema.funcao_objetivo)(x);
+        let f_x = problema.avaliar_objetivo(x);
+
+        // g(x) e h(x), avaliadas juntas
+        let (g_x, h_x) = problema.avaliar_restricoes(x);
 
         // λⱼgⱼ(x)⁺
         let mut lbd_g_x = 0.0;
-        for j in 0..multiplicadores.lambdas.len() {
-            lbd_g_x += multiplicadores.lambdas[j] * problema.restricoes_desigualdades[j](x)
+        for (lambda, g) in multiplicadores.lambdas.iter().zip(&g_x) {
+            lbd_g_x += lambda * g
         }
 
         // μᵣhᵣ(x)
         let mut mu_h_x = 0.0;
-        for r in 0..multiplicadores.mus.len() {
-            mu_h_x += multiplicadores.mus[r] * problema.restricoes_igualdades[r](x);
+        for (mu, h) in multiplicadores.mus.iter().zip(&h_x) {
+            mu_h_x += mu * h;
         }
 
         f_x + lbd_g_x + mu_h_x
     }
 }
 
+// Gradiente da lagrangiana, ∇f(x) + Σ λⱼ∇gⱼ(x) + Σ μᵣ∇hᵣ(x), usando as
+// derivadas analiticas do problema quando existirem
+pub fn gradiente_lagrangiana(
+    problema: &Problema,
+    multiplicadores: &MultiplicadoresDeLagrange,
+    x: Ponto,
+) -> Ponto {
+    let mut gradiente = problema.gradiente_objetivo_em(x);
+
+    let (grads_g, grads_h) = problema.jacobianas_em(x);
+    for (&lambda, &grad_g) in multiplicadores.lambdas.iter().zip(&grads_g) {
+        gradiente = soma_pontos(gradiente, produto_escalar(lambda, grad_g));
+    }
+
+    for (&mu, &grad_h) in multiplicadores.mus.iter().zip(&grads_h) {
+        gradiente = soma_pontos(gradiente, produto_escalar(mu, grad_h));
+    }
+
+    gradiente
+}
+
+// Hessiana da lagrangiana por diferenças finitas de segunda ordem,
+// contada nas avaliações do problema
+pub fn hessiana_lagrangiana(
+    problema: &Problema,
+    multiplicadores: &MultiplicadoresDeLagrange,
+    x: Ponto,
+) -> Vec<Vec<NumReal>> {
+    problema.contar_hessiana();
+    let funcao_lagrangiana = lagrangiana(problema.copia_compartilhada(), multiplicadores.clone());
+    hessiana_exata(&funcao_lagrangiana, x, &problema.diferencas)
+}
+
 // Retorna uma função semelhente à lagrangiana, chamada de
 // L̃, que pode vista como uma forma "exagerada" da lagrangiana,
 // uma lagrangiana penalizada.
@@ -37,34 +73,34 @@ pub fn lagrangiana_penalizada(
 ) -> impl Fn(Ponto) -> NumReal {
     move |x: Ponto| {
         // f(x)
-        let f_x = (problema.funcao_objetivo)(x);
+        let f_x = problema.avaliar_objetivo(x);
+
+        // g(x) e h(x), avaliadas juntas
+        let (g_x, h_x) = problema.avaliar_restricoes(x);
 
         // λⱼgⱼ(x)⁺
         // gⱼ(x)⁺ = max(0, gⱼ(x))
         let mut lbd_g_x = 0.0;
-        for j in 0..multiplicadores.lambdas.len() {
-            lbd_g_x +=
-                multiplicadores.lambdas[j] * max(0.0, problema.restricoes_desigualdades[j](x));
+        for (&lambda, &g) in multiplicadores.lambdas.iter().zip(&g_x) {
+            lbd_g_x += lambda * max(0.0, g);
         }
 
         // μᵣ|hᵣ(x)|
         let mut mu_h_x = 0.0;
-        for r in 0..multiplicadores.mus.len() {
-            mu_h_x = (multiplicadores.mus[r] * problema.restricoes_igualdades[r](x)).abs();
+        for (mu, h) in multiplicadores.mus.iter().zip(&h_x) {
+            mu_h_x = (mu * h).abs();
         }
 
         // λⱼ(gⱼ(x)⁺)²
         let mut lbd_g_x_penalizado = 0.0;
-        for j in 0..multiplicadores.lambdas.len() {
-            lbd_g_x_penalizado += multiplicadores.lambdas[j]
-                * (max(0.0, problema.restricoes_desigualdades[j](x))).powi(2);
+        for (&lambda, &g) in multiplicadores.lambdas.iter().zip(&g_x) {
+            lbd_g_x_penalizado += lambda * (max(0.0, g)).powi(2);
         }
 
         // |μᵣ|(hᵣ(x))²
         let mut mu_h_x_penalizado = 0.0;
-        for r in 0..multiplicadores.mus.len() {
-            mu_h_x_penalizado +=
-                multiplicadores.mus[r].abs() * (problema.restricoes_igualdades[r](x)).powi(2);
+        for (mu, h) in multiplicadores.mus.iter().zip(&h_x) {
+            mu_h_x_penalizado += mu.abs() * h.powi(2);
         }
 
         f_x + lbd_g_x + mu_h_x + (RHO * lbd_g_x_penalizado) + (RHO * mu_h_x_penalizado)
@@ -0,0 +1,226 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::aleatorio::GeradorAleatorio;
+use crate::amostragem::pontos_na_caixa;
+use crate::defs::{Configuracao, Problema};
+use crate::ponto_de_controle::estado_para_retomar;
+use crate::resolucao::resolver;
+use crate::resultado::{MetadadosProblema, ResultadoSolucao};
+
+// Desfecho de uma instancia do lote. Uma falha, como um panico dentro da
+// função do usuario, fica restrita à sua instancia
+#[derive(Debug, Clone)]
+pub enum DesfechoInstancia {
+    Resolvida(Box<ResultadoSolucao>),
+    Falhou(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultadoInstancia {
+    pub nome: String,
+    pub tempo: Duration,
+    pub desfecho: DesfechoInstancia,
+}
+
+// Resultados do lote na ordem das instancias, não na ordem de termino
+#[derive(Debug, Clone)]
+pub struct RelatorioLote {
+    pub resultados: Vec<ResultadoInstancia>,
+    pub threads: usize,
+    pub tempo_total: Duration,
+}
+
+impl RelatorioLote {
+    // Uma linha por instancia e um resumo
+    pub fn mostrar(&self, configuracao: &Configuracao) {
+        println!(
+            "Lote de {} instancias em {} threads, {:.3}s",
+            self.resultados.len(),
+            self.threads,
+            self.tempo_total.as_secs_f64()
+        );
+
+        let mut convergidas = 0;
+        let mut falhas = 0;
+        for (i, resultado) in self.resultados.iter().enumerate() {
+            match &resultado.desfecho {
+                DesfechoInstancia::Resolvida(solucao) => match &solucao.solucao {
+                    Some(encontrada) => {
+                        let convergiu = encontrada
+                            .residuo_kkt
+                            .convergiu(&configuracao.tolerancias_kkt);
+                        if convergiu {
+                            convergidas += 1;
+                        }
+                        println!(
+                            "[{}] {}: {} em {} iterações, x* = {:?}, f(x*) = {:?}, KKT {}, {} chamadas, {:.3}s",
+                            i,
+                            resultado.nome,
+                            solucao.parada.nome(),
+                            solucao.iteracoes,
+                            encontrada.x,
+                            encontrada.valor_objetivo,
+                            if convergiu { "satisfeito" } else { "não satisfeito" },
+                            solucao.avaliacoes.chamadas(),
+                            resultado.tempo.as_secs_f64()
+                        );
+                    }
+                    None => println!(
+                        "[{}] {}: otimo não encontrado, {} em {} iterações, {:.3}s",
+                        i,
+                        resultado.nome,
+                        solucao.parada.nome(),
+                        solucao.iteracoes,
+                        resultado.tempo.as_secs_f64()
+                    ),
+                },
+                DesfechoInstancia::Falhou(erro) => {
+                    falhas += 1;
+                    println!(
+                        "[{}] {}: falhou ... {}, {:.3}s",
+                        i,
+                        resultado.nome,
+                        erro,
+                        resultado.tempo.as_secs_f64()
+                    );
+                }
+            }
+        }
+
+        println!(
+            "Pontos KKT: {}, sem convergência: {}, falhas: {}",
+            convergidas,
+            self.resultados.len() - convergidas - falhas,
+            falhas
+        );
+    }
+}
+
+// Cada problema a partir do seu ponto inicial e de mais partidas - 1
+// pontos sorteados na caixa das variaveis, pela amostragem e semente da
+// configuração, com o indice da partida no nome
+pub fn instancias_com_partidas(
+    problemas: Vec<Problema>,
+    configuracao: &Configuracao,
+) -> Vec<Problema> {
+    let partidas = configuracao.partidas;
+    if partidas <= 1 {
+        return problemas;
+    }
+
+    let mut gerador = GeradorAleatorio::novo(configuracao.semente);
+    let mut instancias = Vec::new();
+    for problema in problemas {
+        let (inferior, superior) = problema.caixa_variaveis();
+        let sorteados = pontos_na_caixa(
+            configuracao.amostragem,
+            partidas - 1,
+            &inferior,
+            &superior,
+            &mut gerador,
+        );
+        for partida in 0..partidas {
+            let mut instancia = problema.copia_independente();
+            if partida > 0 {
+                instancia.x_inicial = sorteados[partida - 1];
+            }
+            instancia.nome = format!("{} partida {}", problema.nome, partida);
+            instancias.push(instancia);
+        }
+    }
+    instancias
+}
+
+// Resolve uma instancia com a sua propria copia do problema, gravando o
+// resultado se pedido
+fn resolver_instancia(
+    problema: &Problema,
+    configuracao: &Configuracao,
+) -> Result<ResultadoSolucao, String> {
+    let mut p = problema.copia_independente();
+    p.diferencas = configuracao.diferencas;
+    p.definir_orcamento_avaliacoes(configuracao.orcamento_avaliacoes);
+
+    let estado_inicial = estado_para_retomar(&p.nome, configuracao)?;
+    let resolucao = resolver(&mut p, configuracao, estado_inicial)
+        .map_err(|estado| format!("problema linear sem solução ({})", estado))?;
+
+    let resultado = ResultadoSolucao::novo(
+        MetadadosProblema::do_problema(problema),
+        &p,
+        configuracao,
+        resolucao.parada,
+        resolucao.iteracoes,
+        &resolucao.otimo,
+        p.contadores_avaliacao(),
+        resolucao.historico,
+    );
+
+    if let Some(diretorio) = &configuracao.diretorio_resultados {
+        resultado.exportar(diretorio, configuracao.formato_resultados)?;
+    }
+
+    Ok(resultado)
+}
+
+// Mensagem de um panico, que normalmente é uma &str ou uma String
+fn mensagem_panico(panico: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(mensagem) = panico.downcast_ref::<&str>() {
+        mensagem.to_string()
+    } else if let Some(mensagem) = panico.downcast_ref::<String>() {
+        mensagem.clone()
+    } else {
+        "panico sem mensagem".to_string()
+    }
+}
+
+// Resolve as instancias em paralelo. Cada thread pega a proxima instancia
+// ainda não resolvida, e o resultado é guardado no indice dela
+pub fn resolver_lote(problemas: &[Problema], configuracao: &Configuracao) -> RelatorioLote {
+    let inicio = Instant::now();
+    let threads = configuracao.threads.min(problemas.len()).max(1);
+    let proxima = AtomicUsize::new(0);
+    let resultados: Mutex<Vec<Option<ResultadoInstancia>>> =
+        Mutex::new(vec![None; problemas.len()]);
+
+    thread::scope(|escopo| {
+        for _ in 0..threads {
+            escopo.spawn(|| loop {
+                let i = proxima.fetch_add(1, Ordering::SeqCst);
+                if i >= problemas.len() {
+                    break;
+                }
+
+                let inicio_instancia = Instant::now();
+                let desfecho = match panic::catch_unwind(AssertUnwindSafe(|| {
+                    resolver_instancia(&problemas[i], configuracao)
+                })) {
+                    Ok(Ok(resultado)) => DesfechoInstancia::Resolvida(Box::new(resultado)),
+                    Ok(Err(erro)) => DesfechoInstancia::Falhou(erro),
+                    Err(panico) => DesfechoInstancia::Falhou(mensagem_panico(panico)),
+                };
+
+                resultados.lock().unwrap()[i] = Some(ResultadoInstancia {
+                    nome: problemas[i].nome.clone(),
+                    tempo: inicio_instancia.elapsed(),
+                    desfecho,
+                });
+            });
+        }
+    });
+
+    RelatorioLote {
+        resultados: resultados
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|resultado| resultado.unwrap())
+            .collect(),
+        threads,
+        tempo_total: inicio.elapsed(),
+    }
+}
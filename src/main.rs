@@ -1,166 +1,287 @@
+mod aleatorio;
+mod algebra_linear;
+mod amostragem;
+mod arquivo_linear;
+mod avaliacoes;
+mod backend_linear;
+mod complexo;
+mod construtor_problema;
 mod defs;
-mod emfcq;
+mod diferencas_finitas;
 mod estimativa_mul_lagrange;
 mod funcao_merito;
+mod historico;
 mod instancias;
+mod intervalo;
 mod iter_linear;
 mod lagrangianas;
+mod lote;
 mod matricial;
+mod matriz_esparsa;
+mod multipartida;
+mod ponto_de_controle;
 mod ponto_estacionario;
 mod prob_linear;
+mod qualificacoes;
+mod quase_newton;
+mod ramificacao_espacial;
+mod ramificacao_inteira;
+mod reais_json;
 mod regiao_de_confianca;
+mod regularizacao_hessiana;
+mod resolucao;
+mod resultado;
+mod segunda_ordem;
+mod simplex_dual;
 mod utils;
-
-use iter_linear::*;
+mod verificacao_derivadas;
 
 use crate::{
-    defs::{NumReal, Ponto, DIM},
-    emfcq::emfcq,
-    funcao_merito::verificacao_funcao_merito,
+    arquivo_linear::ler_mps,
+    defs::{Configuracao, NumReal, Ponto},
+    diferencas_finitas::gradiente,
     instancias::gerar_instancias,
-    ponto_estacionario::checar_ponto_estacionario,
-    regiao_de_confianca::verificar_regiao_de_confianca,
-    utils::_iguais,
+    lote::{instancias_com_partidas, resolver_lote},
+    multipartida::busca_multipartida,
+    ponto_de_controle::estado_para_retomar,
+    ponto_estacionario::{mostrar_restricoes, residuo_kkt},
+    prob_linear::SessaoProblemaLinear,
+    qualificacoes::{diagnosticar_qualificacoes, mostrar_mfcq, verificar_mfcq},
+    ramificacao_espacial::{busca_global, caixa_das_variaveis},
+    ramificacao_inteira::ramificacao_inteira,
+    resolucao::{resolver, Resolucao},
+    resultado::{MetadadosProblema, ResultadoSolucao},
+    segunda_ordem::verificar_segunda_ordem,
+    verificacao_derivadas::verificar_derivadas,
 };
 
 fn main() {
-    // Itera sobre uma lista de instancias de problemas
-    for problema in gerar_instancias() {
-        // Cria uma copia mutavel do problema localmente
-        let mut p = problema.clone();
-
-        // Ignora verificação EMFCQ
-        let passar_emfcq = true;
-
-        if !passar_emfcq {
-            let problema_emfcq = emfcq(&p, 10.0);
-            if problema_emfcq {
-                println!("EMFCQ? Sim");
-            } else {
-                println!("EMFCQ? Não");
-                continue;
-            }
+    // Escolhas do solver vindas da linha de comando
+    let configuracao = match Configuracao::dos_argumentos(std::env::args().skip(1)) {
+        Ok(configuracao) => configuracao,
+        Err(erro) => {
+            println!("{}", erro);
+            std::process::exit(1);
         }
+    };
 
-        // Lista de pontos em cada iteção não linear
-        let mut passos_tomados: Vec<Vec<NumReal>> = Vec::new();
+    // Resolve um subproblema linear gravado, no lugar das instancias
+    if let Some(arquivo) = &configuracao.arquivo_reproducao_linear {
+        reproduzir_problema_linear(arquivo, &configuracao);
+        return;
+    }
 
-        // Contador de iterações não lineares
-        let mut k = 1;
+    // Solução de cada instancia com as variaveis inteiras, pela ramificação
+    // sobre as relaxações continuas
+    if configuracao.inteiro {
+        for problema in gerar_instancias() {
+            ramificacao_inteira(&problema, &configuracao).mostrar();
+            println!();
+        }
+        return;
+    }
 
-        // Variavel de armazenamento do ponto corrente
-        let mut x = p.x_inicial;
+    // Otimo global certificado de cada instancia, pela ramificação espacial
+    if configuracao.global {
+        for problema in gerar_instancias() {
+            let caixa = caixa_das_variaveis(&problema);
+            match busca_global(&problema, caixa, &configuracao) {
+                Ok(resultado) => resultado.mostrar(),
+                Err(erro) => println!("Busca global ... {}", erro),
+            }
+            println!();
+        }
+        return;
+    }
 
-        // Estado de cada iteração não linear
+    // Busca global em cada instancia, pelas partidas em paralelo
+    if configuracao.multipartida {
+        for problema in gerar_instancias() {
+            busca_multipartida(&problema, &configuracao).mostrar();
+            println!();
+        }
+        return;
+    }
 
-        // Ponto que possivelmente é melhor que o atual
-        let mut x_novo;
+    // Resolve as instancias em paralelo, mostrando só o relatorio do lote
+    if configuracao.lote {
+        let instancias = instancias_com_partidas(gerar_instancias(), &configuracao);
+        resolver_lote(&instancias, &configuracao).mostrar(&configuracao);
+        return;
+    }
 
-        // Direção tomada para encontrar o ponto x_novo e tamanho do passo na direção
-        let mut d;
-        let mut alpha;
+    // Itera sobre uma lista de instancias de problemas
+    for mut p in gerar_instancias() {
+        p.diferencas = configuracao.diferencas;
+        p.definir_orcamento_avaliacoes(configuracao.orcamento_avaliacoes);
 
-        // Armazenamento do pedido de parada nas subiterções lineares
-        let mut verificacao_ponto_estacionario;
+        println!("Problema {}", p.nome);
 
-        // Armazenamento para os multiplicadores_de e matriz aproximada da função lagrangiana
-        // Como é usado o método BFGS, que faz aproximações iteradas à hessiana. Usar a
-        // identidade é o recomendado
-        let mut multiplicadores_de_lagrange;
-        let mut hessiana_lagrangiana = vec![vec![0.0; DIM]; DIM];
-        for i in 0..DIM {
-            hessiana_lagrangiana[i][i] = 1.0;
+        if p.tem_variaveis_inteiras() {
+            println!("Variaveis inteiras resolvidas como continuas, sem --inteiro");
         }
 
-        // Armazenamento do possivel otimo
-        let mut otimo: Option<Ponto> = None;
-
-        // Limite de 100 iterações não lineares
-        while k < 100 {
-            // Calcula e extrai as informações das subiterações lineares
-            let resultado_iteracoes_lineares =
-                iteracoes_lineares(&p, x, hessiana_lagrangiana.clone());
-
-            x_novo = resultado_iteracoes_lineares.0;
-            d = resultado_iteracoes_lineares.1;
-            multiplicadores_de_lagrange = resultado_iteracoes_lineares.5;
-            hessiana_lagrangiana = resultado_iteracoes_lineares.6;
-            verificacao_ponto_estacionario = resultado_iteracoes_lineares.7;
-            alpha = resultado_iteracoes_lineares.8;
-
-            // Se foi encontrado um ponto kkt estacionario nas iterações lineares
-            if verificacao_ponto_estacionario {
-                otimo = Some(x_novo);
-                println!("Parada subiteração linear");
-                break;
+        // Confere as derivadas analiticas antes de resolver, avisando
+        // sempre que elas não conferem
+        if p.tem_derivadas_analiticas() {
+            let relatorio = verificar_derivadas(&p);
+            if configuracao.verificar_derivadas || !relatorio.aprovado() {
+                relatorio.mostrar(configuracao.verificar_derivadas);
             }
+        }
+
+        // Ignora verificação MFCQ
+        let passar_mfcq = true;
 
-            // Verifica se o proximo ponto reduz suficientemente a função de mérido
-            // Caso não reduza, um ponto diferente é retornado
-            // Caso reduza, retorna o mesmo ponto entregue para a verificação
-            x_novo =
-                verificacao_funcao_merito(&p, x_novo, x, alpha, d, &multiplicadores_de_lagrange);
-
-            // Comparando o movimento do ponto observado entre iteraões e
-            // atualizando as regiões de confiança para a busca das direções
-            // de acordo com esse movimento
-            let (d_l, d_u) = verificar_regiao_de_confianca(&p, &x_novo, &x);
-            p.atualizar_regiao_de_confianca(d_l, d_u);
-
-            // Verifica se o novo ponto encontrado é um kkt estacionario
-            if checar_ponto_estacionario(&p, &x_novo, &multiplicadores_de_lagrange) {
-                otimo = Some(x_novo);
-                println!("Parada iterção não linear");
-                break;
+        if !passar_mfcq {
+            // Verifica no ponto inicial
+            let resultado_mfcq = verificar_mfcq(&p, p.x_inicial, &configuracao);
+            mostrar_mfcq(&resultado_mfcq);
+            if !resultado_mfcq.satisfeita() {
+                continue;
             }
+        }
 
-            // Verifica se passou-se duas iterações não lineares e o ponto não se moveu
-            // Para o algoritmo, mesmo que não seja um ponto kkt estacionario
-            if k > 2 {
-                let x_teste = Vec::from(x_novo);
-                let x_ant = Vec::from(passos_tomados[k - 2].clone()).clone();
-                let x_ant2 = Vec::from(passos_tomados[k - 3].clone()).clone();
-
-                // Verifica se o proximo, o atual e o anterior são iguais
-                if _iguais(&x_teste, &x_ant, DIM) && _iguais(&x_teste, &x_ant2, DIM) {
-                    otimo = Some(x_novo);
-                    println!("Parada por passos repetidos--");
-                    break;
-                }
+        // Retoma do ponto de controle gravado do problema, se houver
+        let estado_inicial = match estado_para_retomar(&p.nome, &configuracao) {
+            Ok(estado) => estado,
+            Err(erro) => {
+                println!("Falha ao retomar ... {}", erro);
+                continue;
             }
+        };
+        if let Some(estado) = &estado_inicial {
+            println!("Retomando da iteração {}", estado.k);
+        }
 
-            if false {
-                println!("vvvvvvvvvvvvv");
-                println!("Iterção NLP");
-                println!("x =      {:?}", x);
-                println!("f(x) =   {:?}", (p.funcao_objetivo)(x));
-                println!("d =      {:?}", d);
-                println!("x_novo = {:?}", x_novo);
-                println!("^^^^^^^^^^^^^");
+        // Um subproblema linear sem solução encerra só este problema
+        let Resolucao {
+            otimo,
+            parada,
+            iteracoes,
+            historico,
+        } = match resolver(&mut p, &configuracao, estado_inicial) {
+            Ok(resolucao) => resolucao,
+            Err(estado) => {
+                println!(
+                    "Problema linear não tem solução ({}) ... {}",
+                    configuracao.backend_linear.nome(),
+                    estado
+                );
+                println!();
+                println!();
+                continue;
             }
+        };
 
-            // Atualiza o ponto
-            x = x_novo;
+        if let Some(mensagem) = parada.mensagem() {
+            println!("{}", mensagem);
+        }
 
-            // Armazena esse novo
-            passos_tomados.push(Vec::from(x));
+        let contadores_avaliacao = p.contadores_avaliacao();
 
-            // Proxima iteração
-            k += 1;
+        if let Some(diretorio) = &configuracao.diretorio_resultados {
+            let resultado = ResultadoSolucao::novo(
+                MetadadosProblema::do_problema(&p),
+                &p,
+                &configuracao,
+                parada,
+                iteracoes,
+                &otimo,
+                contadores_avaliacao.clone(),
+                historico.clone(),
+            );
+            if let Err(erro) = resultado.exportar(diretorio, configuracao.formato_resultados) {
+                println!("Falha ao gravar o resultado ... {}", erro);
+            }
         }
 
         // Verifica o otimo
         match otimo {
-            Some(ponto) => {
+            Some((ponto, multiplicadores)) => {
                 println!("x* = {:?}", ponto);
-                println!("f(x*) = {:?}", (p.funcao_objetivo)(ponto));
+                println!("f(x*) = {:?}", p.objetivo_em(ponto));
+
+                // Gradiente por diferenças finitas, com a estimativa do erro de truncamento
+                let estimado = gradiente(ponto, |y: Ponto| p.avaliar_objetivo(y), &p.diferencas);
+                println!("∇f(x*) = {:?} ± {:?}", estimado.gradiente, estimado.erro);
                 println!("\nx* real = {:?}", p.solucao);
                 println!("real f(x*) = {:?}", (p.funcao_objetivo)(p.solucao.unwrap()));
+                residuo_kkt(&p, &ponto, &multiplicadores).mostrar();
+                mostrar_restricoes(&p, &ponto, &multiplicadores);
+
+                // Qualificações de restrições na solução, que dizem se os
+                // multiplicadores encontrados têm significado
+                println!();
+                diagnosticar_qualificacoes(&p, ponto, &configuracao).mostrar(&p);
+
+                // Condições de segunda ordem, que separam minimos de pontos de sela
+                verificar_segunda_ordem(&p, ponto, &multiplicadores, &configuracao).mostrar();
             }
             _ => println!("Otimo não encontrado"),
         }
 
+        // Avaliações gastas pelo solver, sem as dos diagnosticos da solução
+        contadores_avaliacao.mostrar();
+
+        if configuracao.mostrar_historico {
+            println!();
+            historico.mostrar();
+        }
+
         println!();
         println!();
     }
 }
+
+// Lê um subproblema linear gravado com --exportar-lp e o resolve pelo
+// mesmo caminho das subiterações lineares, mostrando a solução e os
+// valores duais pelos nomes das colunas e linhas
+fn reproduzir_problema_linear(arquivo: &str, configuracao: &Configuracao) {
+    let problema_linear = match ler_mps(arquivo) {
+        Ok(problema_linear) => problema_linear,
+        Err(erro) => {
+            println!("Falha ao ler o subproblema linear ... {}", erro);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Subproblema linear {}", problema_linear.nome);
+
+    let mut sessao = SessaoProblemaLinear::nova(configuracao);
+    let (x, duais) = match sessao.resolver_matriz(
+        &problema_linear.a,
+        &problema_linear.b,
+        &problema_linear.c,
+        &problema_linear.nomes_linhas,
+    ) {
+        Ok(solucao) => solucao,
+        Err(estado) => {
+            println!(
+                "Problema linear não tem solução ({}) ... {}",
+                configuracao.backend_linear.nome(),
+                estado
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let valor_objetivo: NumReal = problema_linear
+        .c
+        .iter()
+        .zip(x.iter())
+        .map(|(ci, xi)| ci * xi)
+        .sum();
+    println!("cᵀx = {:?}", valor_objetivo);
+
+    for (nome, valor) in problema_linear.nomes_colunas.iter().zip(x.iter()) {
+        println!("{} = {:?}", nome, valor);
+    }
+
+    // Apenas as linhas com valor dual não nulo
+    for (nome, valor) in problema_linear.nomes_linhas.iter().zip(duais.iter()) {
+        if *valor != 0.0 {
+            println!("y[{}] = {:?}", nome, valor);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::defs::*;
+use crate::matriz_esparsa::MatrizEsparsa;
+use crate::quase_newton::AproximacaoHessiana;
 use crate::utils::*;
-use std::slice::Iter;
 
 // Registro de informações uteis durante a geração
 // das matrizes e vetores
@@ -16,15 +17,15 @@ struct InformacoesProblema {
     dl: Ponto,
     du: Ponto,
     direcoes_encontradas: Vec<Ponto>,
-    hessiana_atual: Vec<Vec<NumReal>>,
+    hessiana_atual: AproximacaoHessiana,
 }
 
 impl InformacoesProblema {
     fn novo(
         problema: &Problema,
         x: Ponto,
-        direcoes_encontradas: &Vec<Ponto>,
-        hessiana_atual: &Vec<Vec<NumReal>>,
+        direcoes_encontradas: &[Ponto],
+        hessiana_atual: &AproximacaoHessiana,
     ) -> InformacoesProblema {
         // Gera algumas informações a partir
         // das funções avaliadas no ponto atual
@@ -47,7 +48,7 @@ impl InformacoesProblema {
         let du = problema.d_u;
 
         // Direcoes encontradas durante as iterações lineares
-        let direcoes_encontradas = direcoes_encontradas.clone();
+        let direcoes_encontradas = direcoes_encontradas.to_vec();
 
         // Hessiana da iteração atual (restrição 1c)
         let hessiana_atual = hessiana_atual.clone();
@@ -68,29 +69,53 @@ impl InformacoesProblema {
             hessiana_atual,
         }
     }
+
+    // Numero total de variaveis do problema linear, na ordem d, tᵍ, tʰ⁺, tʰ⁻
+    fn n_variaveis(&self) -> usize {
+        self.n + self.mi + self.me + self.me
+    }
+
+    // Indices das colunas de cada bloco de variaveis
+    fn col_d(&self, i: usize) -> usize {
+        i
+    }
+    fn col_tg(&self, j: usize) -> usize {
+        self.n + j
+    }
+    fn col_thp(&self, r: usize) -> usize {
+        self.n + self.mi + r
+    }
+    fn col_thm(&self, r: usize) -> usize {
+        self.n + self.mi + self.me + r
+    }
 }
 
-// Gera uma linha da matriz A a partir das listas dos coeficientes
-// Não necessária, mas fica melhor por questões de legibilidade
+// Gera uma linha esparsa da matriz A a partir dos coeficientes de d
+// e das entradas não nulas das variaveis de relaxamento.
+// Os coeficientes de d são dados densamente, já que vêm de gradientes,
+// enquanto as de tᵍ, tʰ⁺ e tʰ⁻ são pares (coluna, valor) já posicionados
 fn gerar_linha_matriz(
-    coeficientes_d: Iter<NumReal>,
-    coeficientes_tg: Iter<NumReal>,
-    coeficientes_thp: Iter<NumReal>,
-    coeficientes_thm: Iter<NumReal>,
-) -> Vec<NumReal> {
-    let mut linha: Vec<NumReal> = Vec::new();
-
-    linha.extend(coeficientes_d); // Coeficientes de d
-    linha.extend(coeficientes_tg); // Coeficientes de tᵍ
-    linha.extend(coeficientes_thp); // Coeficientes de tʰ⁺
-    linha.extend(coeficientes_thm); // Coeficientes de tʰ⁻
+    info: &InformacoesProblema,
+    coeficientes_d: &[NumReal],
+    coeficientes_t: &[(usize, NumReal)],
+) -> Vec<(usize, NumReal)> {
+    let mut linha: Vec<(usize, NumReal)> = Vec::new();
+
+    // Coeficientes de d
+    for (i, &cof) in coeficientes_d.iter().enumerate() {
+        linha.push((info.col_d(i), cof));
+    }
+
+    // Coeficientes de tᵍ, tʰ⁺ e tʰ⁻
+    linha.extend(coeficientes_t);
 
     linha
 }
 
-fn restricao_1a(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1a(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1a
     // gⱼ(x) + ∇gⱼ(x)ᵀd ≤ tᵍⱼ
@@ -106,21 +131,22 @@ fn restricao_1a(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
 
     // Para cada linha da restrição
     for j in 0..info.mi {
-        a.push(gerar_linha_matriz(
-            info.grads_funcao_desigualdades[j].iter(), // Coeficientes de d
-            fn_aux_vtgj(j, info.mi).iter(),            // Coeficientes de tᵍ
-            vec![0.0; info.me].iter(),                 // Coeficientes de tʰ⁺
-            vec![0.0; info.me].iter(),                 // Coeficientes de tʰ⁻
+        a.adicionar_linha(&gerar_linha_matriz(
+            info,
+            &info.grads_funcao_desigualdades[j], // Coeficientes de d
+            &[(info.col_tg(j), -1.0)],           // Coeficiente não nulo de tᵍ
         ));
-        b.push(-info.funcao_desigualdades_avaliadas[j]); // Valor de b
+        b.push(-info.funcao_desigualdades_avaliadas[j]); // Valor de b;
+        nomes.push(format!("1a_{}", j)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1b(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1b(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1b
     // hᵣ(x) + ∇hᵣ(x)ᵀd = tʰ⁺ᵣ - tʰ⁻ᵣ
@@ -141,14 +167,14 @@ fn restricao_1b(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // de igualdade avaliada no ponto
 
     for r in 0..info.me {
-        a.push(gerar_linha_matriz(
-            info.grads_funcao_igualdades[r].iter(),
-            vec![0.0; info.mi].iter(),
-            fn_aux_vrh_n(r, info.me).iter(),
-            fn_aux_vrh_p(r, info.me).iter(),
+        a.adicionar_linha(&gerar_linha_matriz(
+            info,
+            &info.grads_funcao_igualdades[r],
+            &[(info.col_thp(r), -1.0), (info.col_thm(r), 1.0)],
         ));
 
         b.push(-info.funcao_igualdades_avaliadas[r]);
+        nomes.push(format!("1b_{}+", r)); // Nome da linha
     }
 
     // Para -∇hᵣ(x)ᵀd + tʰ⁺ᵣ - tʰ⁻ᵣ ≤ hᵣ(x):
@@ -165,22 +191,23 @@ fn restricao_1b(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
             .map(|el| -1.0 * el)
             .collect();
 
-        a.push(gerar_linha_matriz(
-            grads_invertidos.iter(),
-            vec![0.0; info.mi].iter(),
-            fn_aux_vrh_p(r, info.me).iter(),
-            fn_aux_vrh_n(r, info.me).iter(),
+        a.adicionar_linha(&gerar_linha_matriz(
+            info,
+            &grads_invertidos,
+            &[(info.col_thp(r), 1.0), (info.col_thm(r), -1.0)],
         ));
 
         b.push(info.funcao_igualdades_avaliadas[r]);
+        nomes.push(format!("1b_{}-", r)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1c(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1c(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1c
     // (dᵣ)ᵀHd = 0, r =1, ..., i-1
@@ -200,33 +227,26 @@ fn restricao_1c(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante de desigualdade da restrição é 0 para ambos os casos
 
     // Para cada direção já encontrada
-    for d_r in &info.direcoes_encontradas {
-        let h_dr = produto_matriz_vetor(&info.hessiana_atual, &d_r.clone());
+    for (k, d_r) in info.direcoes_encontradas.iter().enumerate() {
+        let h_dr = info.hessiana_atual.produto(d_r);
         let h_dr_i: Vec<NumReal> = h_dr.iter().map(|el| -1.0 * el).collect();
 
-        a.push(gerar_linha_matriz(
-            h_dr.iter(),               // Coeficientes de d
-            vec![0.0; info.mi].iter(), // Coeficientes de tᵍ
-            vec![0.0; info.me].iter(), // Coeficientes de tʰ⁺
-            vec![0.0; info.me].iter(), // Coeficientes de tʰ⁻
-        ));
-        b.push(0.0); // Valor de b
+        a.adicionar_linha(&gerar_linha_matriz(info, &h_dr, &[])); // Coeficientes de d
+        b.push(0.0); // Valor de b;
+        nomes.push(format!("1c_{}+", k)); // Nome da linha
 
-        a.push(gerar_linha_matriz(
-            h_dr_i.iter(),             // Coeficientes de d
-            vec![0.0; info.mi].iter(), // Coeficientes de tᵍ
-            vec![0.0; info.me].iter(), // Coeficientes de tʰ⁺
-            vec![0.0; info.me].iter(), // Coeficientes de tʰ⁻
-        ));
-        b.push(0.0); // Valor de b
+        a.adicionar_linha(&gerar_linha_matriz(info, &h_dr_i, &[])); // Coeficientes de d
+        b.push(0.0); // Valor de b;
+        nomes.push(format!("1c_{}-", k)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1d(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1d(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1d
     // dₗ ≤ d ≤ dᵤ
@@ -246,13 +266,9 @@ fn restricao_1d(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante da restrição é o valor inverso da i-ésima componente de dₗ
 
     for i in 0..info.n {
-        a.push(gerar_linha_matriz(
-            fn_aux_vetor_nulo_exceto(i, info.n, -1.0).iter(),
-            vec![0.0; info.mi].iter(),
-            vec![0.0; info.me].iter(),
-            vec![0.0; info.me].iter(),
-        ));
-        b.push(-info.dl[i])
+        a.adicionar_linha(&[(info.col_d(i), -1.0)]);
+        b.push(-info.dl[i]);
+        nomes.push(format!("1d_{}l", i)); // Nome da linha
     }
 
     // Para d ≤ dᵤ:
@@ -263,21 +279,18 @@ fn restricao_1d(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante da restrição é o valor da i-ésima componente de dᵤ
 
     for i in 0..info.n {
-        a.push(gerar_linha_matriz(
-            fn_aux_vetor_nulo_exceto(i, info.n, 1.0).iter(),
-            vec![0.0; info.mi].iter(),
-            vec![0.0; info.me].iter(),
-            vec![0.0; info.me].iter(),
-        ));
-        b.push(info.du[i])
+        a.adicionar_linha(&[(info.col_d(i), 1.0)]);
+        b.push(info.du[i]);
+        nomes.push(format!("1d_{}u", i)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1e(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1e(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1e
     // 0 ≤ tᵍⱼ ≤ max(0, gⱼ(x))
@@ -296,14 +309,10 @@ fn restricao_1e(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // Os coeficientes dos tʰ⁻ são todos nulos
     // A constante da restrição é 0
     for j in 0..info.mi {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            fn_aux_vetor_nulo_exceto(j, info.mi, -1.0).iter(),
-            vec![0.0; info.me].iter(),
-            vec![0.0; info.me].iter(),
-        ));
+        a.adicionar_linha(&[(info.col_tg(j), -1.0)]);
 
         b.push(0.0);
+        nomes.push(format!("1e_{}l", j)); // Nome da linha
     }
 
     // Para tᵍⱼ ≤ max(0, gⱼ(x)):
@@ -314,22 +323,19 @@ fn restricao_1e(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante da restrição é o maior valor entre 0 e a j-ésima restriçao de desigualdade
     // avaliada no ponto
     for j in 0..info.mi {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            fn_aux_vetor_nulo_exceto(j, info.mi, 1.0).iter(),
-            vec![0.0; info.me].iter(),
-            vec![0.0; info.me].iter(),
-        ));
+        a.adicionar_linha(&[(info.col_tg(j), 1.0)]);
 
         b.push(max(info.funcao_desigualdades_avaliadas[j], 0.0));
+        nomes.push(format!("1e_{}u", j)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1f(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1f(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1g
     // 0 ≤ tʰ⁺ᵣ ≤ |hᵣ(x)|
@@ -348,14 +354,10 @@ fn restricao_1f(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // Os coeficientes dos tʰ⁻ são todos nulos
     // A constante da restrição é 0
     for r in 0..info.me {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            vec![0.0; info.mi].iter(),
-            fn_aux_vetor_nulo_exceto(r, info.me, -1.0).iter(),
-            vec![0.0; info.me].iter(),
-        ));
+        a.adicionar_linha(&[(info.col_thp(r), -1.0)]);
 
         b.push(0.0);
+        nomes.push(format!("1f_{}l", r)); // Nome da linha
     }
 
     // Para tʰ⁺ᵣ ≤ |hᵣ(x)|:
@@ -366,22 +368,19 @@ fn restricao_1f(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante da restrição é o valor absoluto da r-ésima restriçao de igualdade
     // avaliada no ponto
     for r in 0..info.me {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            vec![0.0; info.mi].iter(),
-            fn_aux_vetor_nulo_exceto(r, info.me, 1.0).iter(),
-            vec![0.0; info.me].iter(),
-        ));
+        a.adicionar_linha(&[(info.col_thp(r), 1.0)]);
 
         b.push(info.funcao_igualdades_avaliadas[r].abs());
+        nomes.push(format!("1f_{}u", r)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
-fn restricao_1g(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>) {
-    let mut a = Vec::new();
+fn restricao_1g(info: &InformacoesProblema) -> (MatrizEsparsa, Vec<NumReal>, Vec<String>) {
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
     let mut b = Vec::new();
+    let mut nomes = Vec::new();
 
     // Restrição 1e
     // 0 ≤ tʰ⁻ᵣ ≤ |hᵣ(x)|
@@ -400,14 +399,10 @@ fn restricao_1g(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // Os coeficientes dos tʰ⁻ são todos nulos, com exceção do r-ésimo, que é -1
     // A constante da restrição é 0
     for r in 0..info.me {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            vec![0.0; info.mi].iter(),
-            vec![0.0; info.me].iter(),
-            fn_aux_vetor_nulo_exceto(r, info.me, -1.0).iter(),
-        ));
+        a.adicionar_linha(&[(info.col_thm(r), -1.0)]);
 
         b.push(0.0);
+        nomes.push(format!("1g_{}l", r)); // Nome da linha
     }
 
     // Para tʰ⁻ᵣ ≤ |hᵣ(x)|:
@@ -418,50 +413,50 @@ fn restricao_1g(info: &InformacoesProblema) -> (Vec<Vec<NumReal>>, Vec<NumReal>)
     // A constante da restrição é o valor absoluto da r-ésima restriçao de igualdade
     // avaliada no ponto
     for r in 0..info.me {
-        a.push(gerar_linha_matriz(
-            vec![0.0; info.n].iter(),
-            vec![0.0; info.mi].iter(),
-            vec![0.0; info.me].iter(),
-            fn_aux_vetor_nulo_exceto(r, info.me, 1.0).iter(),
-        ));
+        a.adicionar_linha(&[(info.col_thm(r), 1.0)]);
 
         b.push(info.funcao_igualdades_avaliadas[r].abs());
+        nomes.push(format!("1g_{}u", r)); // Nome da linha
     }
 
-    (a, b)
+    (a, b, nomes)
 }
 
 // Gera as matrizes e vetores para um problema linear
 // do tipo
 // min cᵀx
 // s.a.: Ax ≥ b
+// Junto, retorna o nome de cada linha de A, que identifica a
+// restrição do artigo e o indice de onde a linha veio, como
+// 1a_j, 1b_r+, 1c_k-, 1d_il, ...
 pub fn matriz_e_vetores_problema_linear(
     problema: &Problema,
     x: Ponto,
-    lista_direcoes: &Vec<Ponto>,
-    hessiana_atual: &Vec<Vec<NumReal>>,
-) -> (Vec<Vec<NumReal>>, Vec<NumReal>, Vec<NumReal>) {
+    lista_direcoes: &[Ponto],
+    hessiana_atual: &AproximacaoHessiana,
+) -> (MatrizEsparsa, Vec<NumReal>, Vec<NumReal>, Vec<String>) {
     // Informações uteis durante o processo de geração das informções
     let info = InformacoesProblema::novo(problema, x, lista_direcoes, hessiana_atual);
 
-    // a é uma lista de lista de números reais, isto é, uma matriz
-    // que representa A, que são os coeficientes de cada expressão
-    // linear do problema linear
-    let mut a: Vec<Vec<NumReal>> = Vec::new();
+    // a é uma matriz esparsa que representa A, que são os coeficientes
+    // de cada expressão linear do problema linear. Apenas os coeficientes
+    // não nulos de cada linha são guardados
+    let mut a = MatrizEsparsa::nova(info.n_variaveis());
 
     // b é uma lista de números reais, um vetor, representando b,
     // que são uma lista de números reais para cada expressão linear
     // em A deve ser maior
     let mut b: Vec<NumReal> = Vec::new();
 
+    // Nomes das linhas de A, na mesma ordem
+    let mut nomes_linhas: Vec<String> = Vec::new();
+
     // c é uma lista de números reais, um vetor, representando c,
     // que são os coeficientes da função objetivo do problema linear
     // Todos os componentes são a constante C, exceto pelos n primeiros elementos
-    let mut c: Vec<NumReal> = vec![C; info.n + info.mi + info.me + info.me];
-    for i in 0..info.n {
-        // Troca a constante C por componentes do gradiente da função objetivo
-        c[i] = info.grad_funcao_objetivo[i];
-    }
+    let mut c: Vec<NumReal> = vec![C; info.n_variaveis()];
+    // Troca a constante C por componentes do gradiente da função objetivo
+    c[..info.n].copy_from_slice(&info.grad_funcao_objetivo[..info.n]);
 
     // Restrições a serem aplicadas, construidas de arcordo com o artigo
     let restricoes = [
@@ -478,24 +473,40 @@ pub fn matriz_e_vetores_problema_linear(
     // referentes a restição
     for restricao in restricoes.iter() {
         // Adiquirindo os blocos
-        let (ba, bb) = restricao(&info);
+        let (ba, bb, nomes) = restricao(&info);
         // Adicionando os blocos à matriz A e o vetor b
-        let _: Vec<()> = ba.iter().map(|el| a.push(el.clone())).collect();
-        let _: Vec<()> = bb.iter().map(|el| b.push(el.clone())).collect();
-        // É necessário salvar a lista em branco em uma variavél por causa de lazy-evaluation
+        a.concatenar(&ba);
+        b.extend(bb);
+        nomes_linhas.extend(nomes);
     }
 
     // Por segurança, verifica se o numero de linhas de A é igual ao número de elementos em b
-    assert_eq!(a.len(), b.len());
+    assert_eq!(a.n_linhas(), b.len());
 
     // Transforma o problema de Ax ≤ b em Ax ≥ b
     // multiplicando todos os elementos de A e b por -1
     b = b.iter().map(|el| -1.0 * el).collect();
-    a = a
-        .iter()
-        .map(|linha| linha.iter().map(|el| -1.0 * el).collect())
-        .collect();
+    a.escalar(-1.0);
+
+    // Retorna a, b, c e os nomes das linhas
+    (a, b, c, nomes_linhas)
+}
 
-    // Retorna a, b e c
-    (a, b, c)
+// Nomes das colunas de A, isto é, das variaveis do problema linear,
+// na ordem d_i, tg_j, th+_r e th-_r
+pub fn nomes_colunas_problema_linear(problema: &Problema) -> Vec<String> {
+    let mut nomes = Vec::new();
+    for i in 0..DIM {
+        nomes.push(format!("d_{}", i));
+    }
+    for j in 0..problema.mi() {
+        nomes.push(format!("tg_{}", j));
+    }
+    for r in 0..problema.me() {
+        nomes.push(format!("th+_{}", r));
+    }
+    for r in 0..problema.me() {
+        nomes.push(format!("th-_{}", r));
+    }
+    nomes
 }
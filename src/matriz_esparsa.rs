@@ -0,0 +1,95 @@
+use crate::defs::NumReal;
+
+// Matriz esparsa armazenada por linhas (CSR, compressed sparse row).
+// Apenas as entradas não nulas são guardadas, o que evita que os
+// blocos identidade das variaveis de relaxamento tᵍ, tʰ⁺ e tʰ⁻, que
+// são quase todos zeros, sejam materializados.
+// Os valores da linha i estão em valores[inicio_linhas[i]..inicio_linhas[i + 1]],
+// com as colunas correspondentes em indices_colunas no mesmo intervalo.
+#[derive(Clone, Debug)]
+pub struct MatrizEsparsa {
+    pub n_colunas: usize,
+    pub inicio_linhas: Vec<usize>,
+    pub indices_colunas: Vec<usize>,
+    pub valores: Vec<NumReal>,
+}
+
+impl MatrizEsparsa {
+    // Cria uma matriz sem linhas, com o numero de colunas dado
+    pub fn nova(n_colunas: usize) -> Self {
+        Self {
+            n_colunas,
+            inicio_linhas: vec![0],
+            indices_colunas: Vec::new(),
+            valores: Vec::new(),
+        }
+    }
+
+    // Numero de linhas da matriz
+    pub fn n_linhas(&self) -> usize {
+        self.inicio_linhas.len() - 1
+    }
+
+    // Adiciona uma linha ao final da matriz a partir de uma lista
+    // de pares (coluna, valor). Entradas nulas são descartadas
+    pub fn adicionar_linha(&mut self, entradas: &[(usize, NumReal)]) {
+        for &(coluna, valor) in entradas {
+            assert!(coluna < self.n_colunas);
+            if valor != 0.0 {
+                self.indices_colunas.push(coluna);
+                self.valores.push(valor);
+            }
+        }
+        self.inicio_linhas.push(self.valores.len());
+    }
+
+    // Adiciona ao final todas as linhas de uma outra matriz
+    // com o mesmo numero de colunas
+    pub fn concatenar(&mut self, outra: &MatrizEsparsa) {
+        assert_eq!(self.n_colunas, outra.n_colunas);
+        let deslocamento = self.valores.len();
+        self.indices_colunas.extend(outra.indices_colunas.iter());
+        self.valores.extend(outra.valores.iter());
+        for &inicio in outra.inicio_linhas.iter().skip(1) {
+            self.inicio_linhas.push(inicio + deslocamento);
+        }
+    }
+
+    // Itera sobre as entradas não nulas da linha i, como pares (coluna, valor)
+    pub fn linha(&self, i: usize) -> impl Iterator<Item = (usize, NumReal)> + '_ {
+        let (inicio, fim) = (self.inicio_linhas[i], self.inicio_linhas[i + 1]);
+        self.indices_colunas[inicio..fim]
+            .iter()
+            .cloned()
+            .zip(self.valores[inicio..fim].iter().cloned())
+    }
+
+    // Retorna a linha i em forma densa
+    pub fn linha_densa(&self, i: usize) -> Vec<NumReal> {
+        let mut linha = vec![0.0; self.n_colunas];
+        for (j, valor) in self.linha(i) {
+            linha[j] = valor;
+        }
+        linha
+    }
+
+    // Multiplica todas as entradas por um escalar
+    pub fn escalar(&mut self, a: NumReal) {
+        for valor in self.valores.iter_mut() {
+            *valor *= a;
+        }
+    }
+
+    // Calcula o produto Aᵀy sem construir a transposta, acumulando
+    // cada linha de A escalada pelo respectivo yᵢ
+    pub fn produto_transposta_vetor(&self, y: &[NumReal]) -> Vec<NumReal> {
+        assert_eq!(y.len(), self.n_linhas());
+        let mut resultante = vec![0.0; self.n_colunas];
+        for (i, &yi) in y.iter().enumerate().take(self.n_linhas()) {
+            for (j, valor) in self.linha(i) {
+                resultante[j] += valor * yi;
+            }
+        }
+        resultante
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::{Duration, Instant};
+
+use crate::amostragem::Amostragem;
+use crate::defs::{Configuracao, NumReal, Ponto, Problema, DIM};
+use crate::lote::{instancias_com_partidas, resolver_lote, DesfechoInstancia};
+
+// Distancia maxima entre pontos KKT do mesmo otimo local, em cada
+// variavel, relativa à largura da caixa das variaveis
+const TOL_AGRUPAMENTO: NumReal = 1e-4;
+
+// Otimo local encontrado, com as partidas que caíram na sua bacia
+#[derive(Debug, Clone)]
+pub struct OtimoLocal {
+    pub x: Ponto, // Melhor ponto do grupo
+    pub valor_objetivo: NumReal,
+    pub partidas: Vec<usize>,
+}
+
+// Resultado da busca por multiplas partidas de um problema
+#[derive(Debug, Clone)]
+pub struct ResultadoMultipartida {
+    pub nome: String,
+    pub amostragem: Amostragem,
+    pub semente: u64,
+    pub partidas: usize,
+
+    // Otimos locais distintos, do menor valor do objetivo para o maior
+    pub otimos: Vec<OtimoLocal>,
+
+    // Partidas que pararam fora de um ponto KKT, e as que falharam
+    pub sem_convergencia: Vec<usize>,
+    pub falhas: Vec<(usize, String)>,
+
+    pub tempo: Duration,
+}
+
+impl ResultadoMultipartida {
+    // Melhor ponto KKT entre todas as partidas
+    pub fn melhor(&self) -> Option<&OtimoLocal> {
+        self.otimos.first()
+    }
+
+    pub fn mostrar(&self) {
+        println!(
+            "Multipartida {}: {} partidas, amostragem {}, semente {}, {:.3}s",
+            self.nome,
+            self.partidas,
+            self.amostragem.nome(),
+            self.semente,
+            self.tempo.as_secs_f64()
+        );
+
+        match self.melhor() {
+            Some(melhor) => {
+                println!("x* = {:?}", melhor.x);
+                println!("f(x*) = {:?}", melhor.valor_objetivo);
+            }
+            None => println!("Nenhuma partida chegou a um ponto KKT"),
+        }
+
+        println!("{} otimos locais distintos", self.otimos.len());
+        for (i, otimo) in self.otimos.iter().enumerate() {
+            println!(
+                "[{}] x = {:?}, f(x) = {:?}, {} partidas ({:.1}%): {:?}",
+                i,
+                otimo.x,
+                otimo.valor_objetivo,
+                otimo.partidas.len(),
+                100.0 * otimo.partidas.len() as NumReal / self.partidas as NumReal,
+                otimo.partidas
+            );
+        }
+
+        println!(
+            "Sem convergência: {} {:?}, falhas: {}",
+            self.sem_convergencia.len(),
+            self.sem_convergencia,
+            self.falhas.len()
+        );
+        for (partida, erro) in &self.falhas {
+            println!("Partida {} falhou ... {}", partida, erro);
+        }
+    }
+}
+
+// Se dois pontos estão a menos de TOL_AGRUPAMENTO da largura da caixa em
+// todas as variaveis
+fn mesmo_otimo(a: &Ponto, b: &Ponto, inferior: &Ponto, superior: &Ponto) -> bool {
+    (0..DIM).all(|i| {
+        let largura = (superior[i] - inferior[i]).abs().max(NumReal::EPSILON);
+        (a[i] - b[i]).abs() <= TOL_AGRUPAMENTO * largura
+    })
+}
+
+// Resolve o problema a partir do ponto inicial e de mais partidas - 1
+// pontos sorteados na caixa das variaveis, em paralelo, e agrupa os
+// pontos KKT encontrados em otimos locais
+pub fn busca_multipartida(
+    problema: &Problema,
+    configuracao: &Configuracao,
+) -> ResultadoMultipartida {
+    let inicio = Instant::now();
+    let instancias = instancias_com_partidas(vec![problema.copia_independente()], configuracao);
+    let relatorio = resolver_lote(&instancias, configuracao);
+
+    let (inferior, superior) = problema.caixa_variaveis();
+    let mut otimos: Vec<OtimoLocal> = Vec::new();
+    let mut sem_convergencia = Vec::new();
+    let mut falhas = Vec::new();
+    for (partida, resultado) in relatorio.resultados.into_iter().enumerate() {
+        let solucao = match resultado.desfecho {
+            DesfechoInstancia::Resolvida(solucao) => solucao,
+            DesfechoInstancia::Falhou(erro) => {
+                falhas.push((partida, erro));
+                continue;
+            }
+        };
+
+        let encontrada = match &solucao.solucao {
+            Some(encontrada)
+                if encontrada
+                    .residuo_kkt
+                    .convergiu(&configuracao.tolerancias_kkt) =>
+            {
+                encontrada
+            }
+            _ => {
+                sem_convergencia.push(partida);
+                continue;
+            }
+        };
+
+        // Entra no primeiro grupo proximo, ou começa um novo
+        match otimos
+            .iter_mut()
+            .find(|otimo| mesmo_otimo(&otimo.x, &encontrada.x, &inferior, &superior))
+        {
+            Some(otimo) => {
+                otimo.partidas.push(partida);
+                if encontrada.valor_objetivo < otimo.valor_objetivo {
+                    otimo.x = encontrada.x;
+                    otimo.valor_objetivo = encontrada.valor_objetivo;
+                }
+            }
+            None => otimos.push(OtimoLocal {
+                x: encontrada.x,
+                valor_objetivo: encontrada.valor_objetivo,
+                partidas: vec![partida],
+            }),
+        }
+    }
+
+    otimos.sort_by(|a, b| a.valor_objetivo.total_cmp(&b.valor_objetivo));
+
+    ResultadoMultipartida {
+        nome: problema.nome.clone(),
+        amostragem: configuracao.amostragem,
+        semente: configuracao.semente,
+        partidas: instancias.len(),
+        otimos,
+        sem_convergencia,
+        falhas,
+        tempo: inicio.elapsed(),
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::avaliacoes::EstadoAvaliacoes;
+use crate::defs::{Configuracao, MultiplicadoresDeLagrange, NumReal, Ponto};
+use crate::historico::Historico;
+use crate::quase_newton::AproximacaoHessiana;
+
+// Versão do formato dos pontos de controle, independente da dos resultados
+pub const VERSAO_PONTO_DE_CONTROLE: u32 = 1;
+
+// Estado das iterações não lineares no inicio da iteração k. É tudo o que
+// o laço carrega de uma iteração para a outra, então retomar dele refaz
+// exatamente as mesmas iterações. Os parametros de penalidade (C e ρ) são
+// constantes, e os da função de mérito vêm dos multiplicadores
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstadoIteracoes {
+    pub k: usize,
+    #[serde(with = "crate::reais_json")]
+    pub x: Ponto,
+    #[serde(with = "crate::reais_json")]
+    pub d_l: Ponto, // Região de confiança
+    #[serde(with = "crate::reais_json")]
+    pub d_u: Ponto,
+    pub hessiana_lagrangiana: AproximacaoHessiana,
+    pub multiplicadores_de_lagrange: Option<MultiplicadoresDeLagrange>, // Da ultima iteração
+    #[serde(with = "crate::reais_json")]
+    pub passos_tomados: Vec<Vec<NumReal>>,
+    pub historico: Historico,
+    pub avaliacoes: EstadoAvaliacoes,
+}
+
+// Ponto de controle gravado, sempre em JSON. Os reais são lidos de volta
+// exatamente, pelo float_roundtrip do serde_json, e os NaN e infinitos,
+// que o JSON não tem, são gravados como texto pelo reais_json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PontoDeControle {
+    pub versao: u32,
+    pub nome_problema: String,
+    pub configuracao: Configuracao,
+    pub estado: EstadoIteracoes,
+}
+
+impl PontoDeControle {
+    pub fn novo(nome_problema: &str, configuracao: &Configuracao, estado: EstadoIteracoes) -> Self {
+        Self {
+            versao: VERSAO_PONTO_DE_CONTROLE,
+            nome_problema: nome_problema.to_string(),
+            configuracao: configuracao.clone(),
+            estado,
+        }
+    }
+
+    fn caminho(diretorio: &str, nome_problema: &str) -> String {
+        format!("{}/{}.json", diretorio, nome_problema.replace(' ', "_"))
+    }
+
+    // Grava em <diretorio>/<nome>.json, primeiro em um arquivo temporario
+    // que então substitui o anterior, para que uma interrupção durante a
+    // escrita não estrague o ultimo ponto de controle
+    pub fn gravar(&self, diretorio: &str) -> Result<(), String> {
+        fs::create_dir_all(diretorio).map_err(|erro| format!("{}: {}", diretorio, erro))?;
+
+        let caminho = PontoDeControle::caminho(diretorio, &self.nome_problema);
+        let temporario = format!("{}.tmp", caminho);
+        let texto = serde_json::to_string(self).map_err(|erro| erro.to_string())?;
+        fs::write(&temporario, texto).map_err(|erro| format!("{}: {}", temporario, erro))?;
+        fs::rename(&temporario, &caminho).map_err(|erro| format!("{}: {}", caminho, erro))
+    }
+
+    // Lê o ponto de controle de um problema, se houver, recusando os de
+    // outra versão, de outro problema ou de uma configuração que mude as
+    // iterações
+    pub fn ler(
+        diretorio: &str,
+        nome_problema: &str,
+        configuracao: &Configuracao,
+    ) -> Result<Option<Self>, String> {
+        let caminho = PontoDeControle::caminho(diretorio, nome_problema);
+        if !Path::new(&caminho).exists() {
+            return Ok(None);
+        }
+
+        let texto =
+            fs::read_to_string(&caminho).map_err(|erro| format!("{}: {}", caminho, erro))?;
+        let ponto: PontoDeControle =
+            serde_json::from_str(&texto).map_err(|erro| format!("{}: {}", caminho, erro))?;
+
+        if ponto.versao != VERSAO_PONTO_DE_CONTROLE {
+            return Err(format!(
+                "{}: versão {} não suportada (esperada {})",
+                caminho, ponto.versao, VERSAO_PONTO_DE_CONTROLE
+            ));
+        }
+        if ponto.nome_problema != nome_problema {
+            return Err(format!(
+                "{}: ponto de controle do problema {}",
+                caminho, ponto.nome_problema
+            ));
+        }
+        let diferencas = ponto.configuracao.diferencas_de_solver(configuracao);
+        if !diferencas.is_empty() {
+            return Err(format!(
+                "{}: gravado com outra configuração de {}",
+                caminho,
+                diferencas.join(", ")
+            ));
+        }
+
+        Ok(Some(ponto))
+    }
+}
+
+// Estado de onde retomar um problema, quando a configuração pede a retomada
+pub fn estado_para_retomar(
+    nome_problema: &str,
+    configuracao: &Configuracao,
+) -> Result<Option<EstadoIteracoes>, String> {
+    match &configuracao.diretorio_retomada {
+        Some(diretorio) => Ok(
+            PontoDeControle::ler(diretorio, nome_problema, configuracao)?
+                .map(|ponto_de_controle| ponto_de_controle.estado),
+        ),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::avaliacoes::EstadoAvaliacoes;
+    use crate::historico::RegistroIteracao;
+    use crate::instancias::gerar_instancias;
+    use crate::resolucao::resolver;
+
+    fn diretorio_temporario(nome: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("tcc_prog_{}_{}", nome, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn mesmos_bits(a: &[NumReal], b: &[NumReal]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+
+    fn mesmo_registro(a: &RegistroIteracao, b: &RegistroIteracao) -> bool {
+        a.k == b.k
+            && mesmos_bits(&a.x, &b.x)
+            && mesmos_bits(
+                &[a.valor_objetivo, a.alpha, a.deslocamento_hessiana],
+                &[b.valor_objetivo, b.alpha, b.deslocamento_hessiana],
+            )
+    }
+
+    // Os dois ultimos reais finitos são lidos um bit errado sem o
+    // float_roundtrip do serde_json
+    #[test]
+    fn reais_voltam_iguais() {
+        let configuracao = Configuracao::padrao();
+        let estado = EstadoIteracoes {
+            k: 3,
+            x: [0.1 + 0.2, NumReal::NAN],
+            d_l: [NumReal::NEG_INFINITY, -6.801672438380202e+47],
+            d_u: [NumReal::INFINITY, 1.0 / 3.0],
+            hessiana_lagrangiana: AproximacaoHessiana::nova(&configuracao),
+            multiplicadores_de_lagrange: None,
+            passos_tomados: vec![vec![NumReal::NAN, 8.383658838626809e-78]],
+            historico: Historico::novo(),
+            avaliacoes: EstadoAvaliacoes::default(),
+        };
+
+        let diretorio = diretorio_temporario("reais");
+        PontoDeControle::novo("teste", &configuracao, estado.clone())
+            .gravar(&diretorio)
+            .unwrap();
+        let lido = PontoDeControle::ler(&diretorio, "teste", &configuracao)
+            .unwrap()
+            .unwrap()
+            .estado;
+        fs::remove_dir_all(&diretorio).unwrap();
+
+        assert!(mesmos_bits(&estado.x, &lido.x));
+        assert!(mesmos_bits(&estado.d_l, &lido.d_l));
+        assert!(mesmos_bits(&estado.d_u, &lido.d_u));
+        assert!(mesmos_bits(
+            &estado.passos_tomados[0],
+            &lido.passos_tomados[0]
+        ));
+    }
+
+    // Resolver do zero e retomar do ultimo ponto de controle gravado tem
+    // que dar exatamente as mesmas iterações
+    #[test]
+    fn retomada_refaz_as_mesmas_iteracoes() {
+        for problema in gerar_instancias() {
+            let diretorio = diretorio_temporario(&problema.nome.replace(' ', "_"));
+            let mut configuracao = Configuracao::padrao();
+            configuracao.diretorio_ponto_de_controle = Some(diretorio.clone());
+
+            let mut p = problema.copia_independente();
+            let completa = resolver(&mut p, &configuracao, None).unwrap();
+
+            let estado = PontoDeControle::ler(&diretorio, &problema.nome, &configuracao)
+                .unwrap()
+                .expect("nenhum ponto de controle gravado")
+                .estado;
+            fs::remove_dir_all(&diretorio).unwrap();
+            assert!(estado.k > 1, "{}", problema.nome);
+
+            let mut p = problema.copia_independente();
+            let retomada = resolver(&mut p, &configuracao, Some(estado)).unwrap();
+
+            let registros = &completa.historico.registros;
+            let retomados = &retomada.historico.registros;
+            assert_eq!(registros.len(), retomados.len(), "{}", problema.nome);
+            for (a, b) in registros.iter().zip(retomados) {
+                assert!(mesmo_registro(a, b), "{}: {:?} e {:?}", problema.nome, a, b);
+            }
+        }
+    }
+}
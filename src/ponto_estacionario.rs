@@ -1,105 +1,235 @@
-use crate::{
-    defs::{MultiplicadoresDeLagrange, NumReal, Ponto, Problema, DIM},
-    utils::prox_o_suficiente_de_zero,
-};
+use serde::{Deserialize, Serialize};
 
-// Verifica se o ponto é kkt estacionario de acordo
-// com os criterios descritos no artigo
-pub fn checar_ponto_estacionario(
+use crate::defs::{MultiplicadoresDeLagrange, NumReal, Ponto, Problema, ToleranciasKkt, DIM};
+use crate::qualificacoes::TOL_ATIVA;
+
+// Residuos das condições KKT em um ponto, com os multiplicadores dados.
+// Cada residuo é a norma do maximo da violação de uma das condições,
+// acompanhado de uma referencia da escala das grandezas envolvidas, usada
+// pela tolerancia relativa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResiduoKkt {
+    // ‖∇f(x) + Σ λⱼ∇gⱼ(x) + Σ μᵣ∇hᵣ(x)‖∞
+    pub estacionariedade: NumReal,
+    // max(‖∇f‖∞, ‖λⱼ∇gⱼ‖∞, ‖μᵣ∇hᵣ‖∞), já que o gradiente da lagrangiana é
+    // uma soma com cancelamentos entre parcelas dessa ordem
+    pub referencia_estacionariedade: NumReal,
+
+    // max(gⱼ(x)⁺, |hᵣ(x)|)
+    pub viabilidade_primal: NumReal,
+    // max(1, ‖x‖∞)
+    pub referencia_primal: NumReal,
+
+    // max((-λⱼ)⁺)
+    pub viabilidade_dual: NumReal,
+
+    // max |λⱼgⱼ(x)|
+    pub complementaridade: NumReal,
+    // max(1, ‖λ‖∞), usada também pela viabilidade dual
+    pub referencia_dual: NumReal,
+}
+
+impl ResiduoKkt {
+    // Um residuo é aceito se for menor que a maior das duas tolerancias,
+    // a absoluta e a relativa à sua referencia
+    fn aceito(residuo: NumReal, referencia: NumReal, tolerancias: &ToleranciasKkt) -> bool {
+        residuo <= tolerancias.absoluta.max(tolerancias.relativa * referencia)
+    }
+
+    // Verifica se todas as condições KKT são satisfeitas dentro das tolerancias
+    pub fn convergiu(&self, tolerancias: &ToleranciasKkt) -> bool {
+        ResiduoKkt::aceito(
+            self.estacionariedade,
+            self.referencia_estacionariedade,
+            tolerancias,
+        ) && ResiduoKkt::aceito(self.viabilidade_primal, self.referencia_primal, tolerancias)
+            && ResiduoKkt::aceito(self.viabilidade_dual, self.referencia_dual, tolerancias)
+            && ResiduoKkt::aceito(self.complementaridade, self.referencia_dual, tolerancias)
+    }
+
+    // Mostra os residuos de forma legivel
+    pub fn mostrar(&self) {
+        println!(
+            "Residuos KKT: estacionariedade = {:e}, viabilidade primal = {:e}, viabilidade dual = {:e}, complementaridade = {:e}",
+            self.estacionariedade,
+            self.viabilidade_primal,
+            self.viabilidade_dual,
+            self.complementaridade
+        );
+    }
+}
+
+// Calcula os residuos das condições KKT descritas no artigo
+// 1. gⱼ(x) ≤ 0
+// 2. hᵣ(x) = 0
+// 3. λⱼgⱼ(x) = 0
+// 4. λⱼ ≥ 0
+// 5. ∇f(x) + Σ λⱼ∇gⱼ(x) + Σ μᵣ∇hᵣ(x) = 0
+pub fn residuo_kkt(
     problema: &Problema,
     x: &Ponto,
     multiplicadores_de_lagrange: &MultiplicadoresDeLagrange,
-) -> bool {
-    // Condição 4
-    // Todos os lambdas devem ser positivos ou nulos.
-    // Quando não nulos, mantem o sinal do valor das
-    // restrições de desigualdades, que quando não
-    // cumprida, aumenta o valor da função lagrangiana
-    // quando o objetivo é minimizar
-    for lbd_j in &multiplicadores_de_lagrange.lambdas {
-        if *lbd_j < 0.0 {
-            return false;
-        }
-    }
+) -> ResiduoKkt {
+    let lambdas = &multiplicadores_de_lagrange.lambdas;
+    let mus = &multiplicadores_de_lagrange.mus;
 
-    // Para todas as outras restrições, é necessario ter
-    // as informações do problema avalido no ponto em
-    // questão
-    let problema_avaliado_em_x = problema.avaliar_em(x.clone());
+    let (_, grad_fn_obj, gjx, hrx, grads_gj, grads_hr) = problema.avaliar_em(*x);
 
-    // Condição 1
-    // Todas as restrições de desigualdades
-    // devem ter sido cumpridas
-    for j in 0..problema.mi() {
-        if problema_avaliado_em_x.2[j] > 0.0 {
-            return false;
-        }
-    }
+    let norma_max = |v: &[NumReal]| v.iter().fold(0.0, |acc: NumReal, vi| acc.max(vi.abs()));
 
-    // Condição 2
-    // Todas as restrições de igualdades
-    // devem ter sido cumpridas
-    for j in 0..problema.me() {
-        if problema_avaliado_em_x.3[j] != 0.0 {
-            return false;
-        }
+    // Condições 1 e 2
+    let mut viabilidade_primal: NumReal = 0.0;
+    for &g in gjx.iter().take(problema.mi()) {
+        viabilidade_primal = viabilidade_primal.max(g);
+    }
+    for &h in hrx.iter().take(problema.me()) {
+        viabilidade_primal = viabilidade_primal.max(h.abs());
     }
 
     // Condição 3
-    // O produto da restrição de desigualdade e seu multiplicador
-    // deve ser nulo, já que quando no otimo, a função lagrangiana
-    // minimizada é apenas a função objetivo minimizada
+    let mut complementaridade: NumReal = 0.0;
     for j in 0..problema.mi() {
-        let lbd_gjx = multiplicadores_de_lagrange.lambdas[j] * problema_avaliado_em_x.2[j];
-        if lbd_gjx != 0.0 {
-            return false;
-        }
+        complementaridade = complementaridade.max((lambdas[j] * gjx[j]).abs());
     }
 
-    // Condição 5
-    // Condição de ponto de extremo extendido
-    // para todas as funções, objetivo e restrições
-
-    // Gradientes avaliado no ponto
-    let grad_fn_obj = problema_avaliado_em_x.1;
-    let grads_gj = problema_avaliado_em_x.4;
-    let grads_hr = problema_avaliado_em_x.5;
+    // Condição 4
+    let mut viabilidade_dual: NumReal = 0.0;
+    for &lambda in lambdas.iter().take(problema.mi()) {
+        viabilidade_dual = viabilidade_dual.max(-lambda);
+    }
 
-    // Somatorio dos gradientes restrições de desigualdades escalados
-    // por deus respectivos lambdas
-    let mut gj_acumulado: Vec<NumReal> = vec![0.0; DIM];
+    // Condição 5, acumulando também a escala de cada parcela
+    let mut gradiente_lagrangiana = grad_fn_obj;
+    let mut referencia_estacionariedade = norma_max(&grad_fn_obj);
     for j in 0..problema.mi() {
-        let grad = grads_gj[j];
-        let lbd_gj = multiplicadores_de_lagrange.lambdas[j];
         for idx in 0..DIM {
-            gj_acumulado[idx] += lbd_gj * grad[idx];
+            gradiente_lagrangiana[idx] += lambdas[j] * grads_gj[j][idx];
         }
+        referencia_estacionariedade =
+            referencia_estacionariedade.max(lambdas[j].abs() * norma_max(&grads_gj[j]));
     }
-
-    // Somatorio dos gradientes restrições de igualdades escalados
-    // por deus respectivos mus
-    let mut hr_acumulado: Vec<NumReal> = vec![0.0; DIM];
     for r in 0..problema.me() {
-        let grad = grads_hr[r];
-        let mu_hr = multiplicadores_de_lagrange.mus[r];
         for idx in 0..DIM {
-            hr_acumulado[idx] += mu_hr * grad[idx];
+            gradiente_lagrangiana[idx] += mus[r] * grads_hr[r][idx];
         }
+        referencia_estacionariedade =
+            referencia_estacionariedade.max(mus[r].abs() * norma_max(&grads_hr[r]));
     }
 
-    // Soma das três parcelas, componente a componente
-    let mut soma: Vec<NumReal> = vec![0.0; DIM];
-    for idx in 0..DIM {
-        soma[idx] = grad_fn_obj[idx] + gj_acumulado[idx] + hr_acumulado[idx];
+    ResiduoKkt {
+        estacionariedade: norma_max(&gradiente_lagrangiana),
+        referencia_estacionariedade,
+        viabilidade_primal,
+        referencia_primal: norma_max(x).max(1.0),
+        viabilidade_dual,
+        complementaridade,
+        referencia_dual: norma_max(lambdas).max(1.0),
     }
+}
+
+// Situação de uma desigualdade em um ponto
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SituacaoDesigualdade {
+    Inativa,
+    Ativa,
+    Violada,
+}
 
-    // Todos os componentes devem ser nulos, a menos de um erro gerado
-    // pelas computações
-    for idx in 0..DIM {
-        if !prox_o_suficiente_de_zero(soma[idx]) {
-            return false;
+impl SituacaoDesigualdade {
+    pub fn nome(&self) -> &'static str {
+        match self {
+            SituacaoDesigualdade::Inativa => "inativa",
+            SituacaoDesigualdade::Ativa => "ativa",
+            SituacaoDesigualdade::Violada => "violada",
         }
     }
+}
+
+// Valor e multiplicador de uma restrição em um ponto, pelo nome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstadoRestricao {
+    pub nome: String,
+    pub igualdade: bool,
+    pub valor: NumReal,
+    pub multiplicador: NumReal,                 // λⱼ ou μᵣ
+    pub situacao: Option<SituacaoDesigualdade>, // Só das desigualdades
+}
+
+// Estado de cada restrição em x, as desigualdades seguidas das igualdades
+pub fn estados_restricoes(
+    problema: &Problema,
+    x: &Ponto,
+    multiplicadores_de_lagrange: &MultiplicadoresDeLagrange,
+) -> Vec<EstadoRestricao> {
+    let (gjx, hrx) = problema.restricoes_em(*x);
+    let mut estados = Vec::new();
+
+    for (j, &g) in gjx.iter().enumerate().take(problema.mi()) {
+        let situacao = if g > TOL_ATIVA {
+            SituacaoDesigualdade::Violada
+        } else if g >= -TOL_ATIVA {
+            SituacaoDesigualdade::Ativa
+        } else {
+            SituacaoDesigualdade::Inativa
+        };
+        estados.push(EstadoRestricao {
+            nome: problema.nome_desigualdade(j),
+            igualdade: false,
+            valor: g,
+            multiplicador: multiplicadores_de_lagrange.lambdas[j],
+            situacao: Some(situacao),
+        });
+    }
+    for (r, &h) in hrx.iter().enumerate().take(problema.me()) {
+        estados.push(EstadoRestricao {
+            nome: problema.nome_igualdade(r),
+            igualdade: true,
+            valor: h,
+            multiplicador: multiplicadores_de_lagrange.mus[r],
+            situacao: None,
+        });
+    }
+
+    estados
+}
 
-    true
+// Mostra as variaveis pelos nomes, e cada restrição com o seu valor, o seu
+// multiplicador e, para as desigualdades, se está ativa ou violada
+pub fn mostrar_restricoes(
+    problema: &Problema,
+    x: &Ponto,
+    multiplicadores_de_lagrange: &MultiplicadoresDeLagrange,
+) {
+    let variaveis: Vec<String> = (0..DIM)
+        .map(|i| format!("{} = {:?}", problema.nome_variavel(i), x[i]))
+        .collect();
+    println!("Variaveis: {}", variaveis.join(", "));
+
+    for estado in estados_restricoes(problema, x, multiplicadores_de_lagrange) {
+        match estado.situacao {
+            Some(situacao) => println!(
+                "restrição `{}`: g = {:?}, λ = {:?}, {}",
+                estado.nome,
+                estado.valor,
+                estado.multiplicador,
+                situacao.nome()
+            ),
+            None => println!(
+                "restrição `{}`: h = {:?}, μ = {:?}",
+                estado.nome, estado.valor, estado.multiplicador
+            ),
+        }
+    }
+}
+
+// Verifica se o ponto é kkt estacionario de acordo
+// com os criterios descritos no artigo, dentro das tolerancias
+pub fn checar_ponto_estacionario(
+    problema: &Problema,
+    x: &Ponto,
+    multiplicadores_de_lagrange: &MultiplicadoresDeLagrange,
+    tolerancias: &ToleranciasKkt,
+) -> bool {
+    residuo_kkt(problema, x, multiplicadores_de_lagrange).convergiu(tolerancias)
 }
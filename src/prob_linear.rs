@@ -1,240 +1,243 @@
+use crate::backend_linear::{
+    conferir_backends, BackendProblemaLinear, EstadoProblemaLinear, ResultadoBackend,
+};
 use crate::defs::*;
-use crate::utils::*;
+use crate::matriz_esparsa::MatrizEsparsa;
+use crate::simplex_dual::{base_da_solucao, simplex_dual};
+use crate::utils::minimos_quadrados_nao_negativos;
+
+// Tolerancia para considerar uma restrição Ax ≥ b ativa na solução
+const TOL_ATIVA: NumReal = 1e-7;
+
+// Solução completa do problema linear, primal e dual, obtida de uma
+// unica resolução
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct SolucaoProblemaLinear {
+    pub valor_objetivo: NumReal, // Valor de cᵀx na solução
+    pub d: Vec<NumReal>,         // Direção d
+    pub tg: Vec<NumReal>,        // Relaxamentos tᵍ
+    pub thp: Vec<NumReal>,       // Relaxamentos tʰ⁺
+    pub thm: Vec<NumReal>,       // Relaxamentos tʰ⁻
+
+    // Valores duais (preços) de cada linha de Ax ≥ b, na mesma ordem das linhas
+    pub duais: Vec<NumReal>,
+
+    // Custos reduzidos c - Aᵀy de cada variavel, na ordem d, tᵍ, tʰ⁺, tʰ⁻
+    pub custos_reduzidos: Vec<NumReal>,
+}
 
-// Resolve o problema linear da forma
-// min cᵀx
-// s.a.: Ax ≥ b
-// A, b e c já foram gerados, então não é necessário
-// mais nada além de passar para a biblioteca e resolver
-// Retorna os 4 vetores em ordem e o valor objetivo linear,
-// onde:
-// 1 - valor objetivo
-// 2 - d
-// 3 - tg
-// 4 - th+
-// 5 - th-
-pub fn resolver_problema_linear_matriz(
+// Separa as informações da solução x do problema linear, junto
+// dos valores duais de cada linha, calculando também os custos reduzidos
+fn montar_solucao(
     problema: &Problema,
-    a: &Vec<Vec<NumReal>>,
-    b: &Vec<NumReal>,
-    c: &Vec<NumReal>,
-) -> (
-    NumReal,
-    Vec<NumReal>,
-    Vec<NumReal>,
-    Vec<NumReal>,
-    Vec<NumReal>,
-) {
-    // Biblioteca usada
-    use minilp::*;
-
-    // Gera um problema de minimização
-    let mut problema_minilp = Problem::new(OptimizationDirection::Minimize);
-
+    x: Vec<NumReal>,
+    duais: Vec<NumReal>,
+    a: &MatrizEsparsa,
+    c: &[NumReal],
+) -> SolucaoProblemaLinear {
     // Dimensões do problema
     let n = DIM;
-    let mi = problema.restricoes_desigualdades.len();
-    let me = problema.restricoes_igualdades.len();
-
-    // Cria o vetor d de variáveis que vão ser otimizadas
-    let mut d: Vec<Variable> = Vec::with_capacity(n);
-
-    // Cria o vetor tg de variaveis que vão ser otimizadas
-    let mut tg: Vec<Variable> = Vec::with_capacity(mi);
-
-    // Cria o vetor th+ de variaveis que vão ser otimizadas
-    let mut thp: Vec<Variable> = Vec::with_capacity(me);
-
-    // Cria o vetor th- de variaveis que vão ser otimizadas
-    let mut thm: Vec<Variable> = Vec::with_capacity(me);
-
-    // TODAS as variaveis são livres, as restrições já ditam
-    // os dominios das variaveis, então não é necessário
-    // definir limites para cada uma
-    // Livre no que é possivel para o computador representar
-    let dom = (f64::NEG_INFINITY, f64::INFINITY);
-
-    // Adiciona os coeficientes de d armazenados em c
-    // que são os n primeiros elementos
-    for i in 0..n {
-        let cof = c[i];
-        d.push(problema_minilp.add_var(cof, dom));
+    let mi = problema.mi();
+    let me = problema.me();
+
+    // Valor da função objetivo do subproblema linear
+    let valor_objetivo = c.iter().zip(x.iter()).map(|(ci, xi)| ci * xi).sum();
+
+    // Custos reduzidos c - Aᵀy, que devem ser nulos, já que todas as variaveis são livres
+    let aty = a.produto_transposta_vetor(&duais);
+    let custos_reduzidos = c.iter().zip(aty.iter()).map(|(ci, ai)| ci - ai).collect();
+
+    // Separa os valores de cada variável otimizada
+    SolucaoProblemaLinear {
+        valor_objetivo,
+        d: x[0..n].to_vec(),
+        tg: x[n..(n + mi)].to_vec(),
+        thp: x[(n + mi)..(n + mi + me)].to_vec(),
+        thm: x[(n + mi + me)..(n + mi + me + me)].to_vec(),
+        duais,
+        custos_reduzidos,
     }
+}
 
-    // Adiciona os coeficientes de tᵍ armazenados em c
-    // que são mi elementos após os n primeiros
-    for j in 0..mi {
-        let cof = c[n + j];
-        tg.push(problema_minilp.add_var(cof, dom));
-    }
+// Sessão do problema linear mantida entre as subiterações lineares.
+// Entre uma subiteração e outra o problema ganha as duas linhas da
+// restrição 1c da nova direção conjugada, e os coeficientes e o lado
+// direito mudam um pouco com o ponto e a hessiana. A base otima do
+// problema anterior, guardada pelos nomes das linhas, normalmente
+// continua dual viavel, então o novo problema é resolvido pelo simplex
+// dual a partir dela, sem montar o problema do zero na biblioteca.
+// Quando a base anterior não serve mais, o problema é resolvido do zero
+// pelo backend escolhido na configuração.
+pub struct SessaoProblemaLinear {
+    // Base otima do ultimo problema resolvido, pelos nomes das linhas
+    base: Option<Vec<String>>,
+
+    // Backend das resoluções do zero
+    backend: Box<dyn BackendProblemaLinear>,
+    conferir_backends: bool,
+
+    // Contadores de cada tipo de resolução
+    pub resolucoes_completas: usize,
+    pub resolucoes_incrementais: usize,
+}
 
-    // Adiciona os coeficientes de tʰ⁺ armazenados em c
-    // que são me elementos após os n+mi primeiros
-    for r in 0..me {
-        let cof = c[n + mi + r];
-        thp.push(problema_minilp.add_var(cof, dom));
+impl SessaoProblemaLinear {
+    // Cria uma sessão sem nenhum problema resolvido
+    pub fn nova(configuracao: &Configuracao) -> Self {
+        Self {
+            base: None,
+            backend: configuracao.backend_linear.criar(),
+            conferir_backends: configuracao.conferir_backends,
+            resolucoes_completas: 0,
+            resolucoes_incrementais: 0,
+        }
     }
 
-    // Adiciona os coeficientes de tʰ⁻ armazenados em c
-    // que são me elementos após os n+mi+me primeiros
-    for r in 0..me {
-        let cof = c[n + mi + me + r];
-        thm.push(problema_minilp.add_var(cof, dom));
+    // Resolve o problema linear da forma
+    // min cᵀx
+    // s.a.: Ax ≥ b
+    // A, b e c já foram gerados, então não é necessário mais nada além
+    // de resolver, aproveitando a base do problema anterior quando possivel.
+    // Retorna o valor objetivo linear, os 4 vetores de variaveis
+    // (d, tg, th+ e th-) e, a partir da mesma solução, os valores
+    // duais de cada linha e os custos reduzidos de cada variavel, ou o
+    // estado do backend quando o problema não tem solução
+    pub fn resolver(
+        &mut self,
+        problema: &Problema,
+        a: &MatrizEsparsa,
+        b: &[NumReal],
+        c: &[NumReal],
+        nomes_linhas: &[String],
+    ) -> Result<SolucaoProblemaLinear, EstadoProblemaLinear> {
+        let (x, duais) = self.resolver_matriz(a, b, c, nomes_linhas)?;
+        Ok(montar_solucao(problema, x, duais, a, c))
     }
 
-    // Junta todas as variaveis em um unico vetor
-    // junta EM ORDEM
-    let mut vars = Vec::new();
-    vars.extend(d.iter());
-    vars.extend(tg.iter());
-    vars.extend(thp.iter());
-    vars.extend(thm.iter());
-
-    // Para cada linha em A e em b
-    for idx_linha in 0..b.len() {
-        // Cria um expresão linear vazia
-        let mut expressao_linear = LinearExpr::empty();
-
-        for idx_var in 0..vars.len() {
-            // Adiciona à expressão linear a variavel e seu coeficiente
-            expressao_linear.add(vars[idx_var], a[idx_linha][idx_var]);
-        }
+    // Resolve o mesmo problema sem separar as variaveis, o que serve
+    // também para problemas lidos de arquivo, sem o problema original.
+    // Retorna os valores de todas as variaveis e os valores duais de cada linha
+    pub fn resolver_matriz(
+        &mut self,
+        a: &MatrizEsparsa,
+        b: &[NumReal],
+        c: &[NumReal],
+        nomes_linhas: &[String],
+    ) -> Result<(Vec<NumReal>, Vec<NumReal>), EstadoProblemaLinear> {
+        // Encontra as linhas da base anterior no problema atual
+        let base_anterior: Option<Vec<usize>> = self.base.as_ref().and_then(|base| {
+            base.iter()
+                .map(|nome| nomes_linhas.iter().position(|n| n == nome))
+                .collect()
+        });
+
+        // Tenta o simplex dual a partir da base anterior
+        let solucao_incremental = base_anterior.and_then(|base| simplex_dual(a, b, c, base));
+
+        let (x, duais, base) = match solucao_incremental {
+            Some((x, duais, base)) => {
+                // Confere também a solução do simplex dual, com os seus duais
+                if self.conferir_backends {
+                    let resultado = ResultadoBackend {
+                        estado: EstadoProblemaLinear::Otimo,
+                        x: x.clone(),
+                        duais: Some(duais.clone()),
+                    };
+                    conferir(a, b, c, &resultado, "simplex-dual");
+                }
+
+                self.resolucoes_incrementais += 1;
+                (x, duais, Some(base))
+            }
 
-        // Adiciona a expressão linear como restrição relacionada ao valore de b
-        // Então
-        // A_idx_1*x_1 + A_idx_2*x_2 + A_idx_3*x_3 + ... ≥ b_idx
-        problema_minilp.add_constraint(expressao_linear, ComparisonOp::Ge, b[idx_linha]);
-    }
+            // Monta e resolve do zero no backend
+            None => {
+                let resultado = self.backend.resolver(a, b, c);
 
-    // Uma vez as variaveis com seus coeficientes e as restrições adicionadas
-    // basta pedir para a biblioteca resolver o problema
-    let solucao = problema_minilp.solve();
-
-    // Verifica a solução
-    match solucao {
-        // Se a solução foi um sucesso, ok, salva as informações da solução em s
-        Ok(s) => {
-            // Cria os vetores que vão ser retornados como solução
-            let mut ds = Vec::new();
-            let mut tgs = Vec::new();
-            let mut thps = Vec::new();
-            let mut thms = Vec::new();
-
-            // Salva os valores de cada variável otimizada
-            for &dc in &d {
-                ds.push(s[dc]);
-            }
+                // Confere o resultado com os outros backends
+                if self.conferir_backends {
+                    conferir(a, b, c, &resultado, self.backend.nome());
+                }
 
-            for &tgc in &tg {
-                tgs.push(s[tgc]);
-            }
+                if resultado.estado != EstadoProblemaLinear::Otimo {
+                    return Err(resultado.estado);
+                }
 
-            for &thpc in &thp {
-                thps.push(s[thpc]);
-            }
+                // Valores de todas as variaveis, na ordem das colunas de A
+                let x = resultado.x;
 
-            for &thmc in &thm {
-                thms.push(s[thmc]);
-            }
+                // Usa os valores duais do backend, ou os extrai a partir da solução primal
+                let duais = match resultado.duais {
+                    Some(duais) => duais,
+                    None => extrair_duais(a, b, c, &x),
+                };
 
-            // Calcula o valor da função objetivo do subproblema linear
-            let valor_funcao_objetivo = s.objective();
+                // E a base que gera essa solução, para a proxima resolução
+                let base = base_da_solucao(a, b, &x, &duais);
 
-            // Por fim, finalmente, retorna
-            return (valor_funcao_objetivo, ds, tgs, thps, thms);
-        }
+                self.resolucoes_completas += 1;
+                (x, duais, base)
+            }
+        };
 
-        // Caso a solução retorne um erro...
-        Err(erro) => {
-            println!("Problema linear não tem solução ... {}", erro);
+        // Guarda a base otima para a proxima subiteração
+        self.base = base.map(|base| base.iter().map(|&i| nomes_linhas[i].clone()).collect());
 
-            // Encerra o programa, não tem mais sentido continuar
-            std::process::exit(1);
-        }
+        Ok((x, duais))
     }
 }
 
-// Resolve o problema dual do problema linear
-// A partir da solução do problema dual, se é possivel
-// extrair os estimadores dos multiplicadores de lagrange
-// Retorna o valor máximo do problema dual, que dever
-// ser igual ao minimo do problema primal e o vetor com
-// todos os valores das variaveis duais
-pub fn resolver_problema_dual_matriz(
-    a: &Vec<Vec<NumReal>>,
-    b: &Vec<NumReal>,
-    c: &Vec<NumReal>,
-) -> (NumReal, Vec<NumReal>) {
-    // O problema primal é dado por:
-    // min cᵀx
-    // s.a.: Ax ≥ b
-    // Já o dual:
-    // max bᵀy
-    // s.a.: Aᵀy = c, y ≥ 0
-    // (Matteo Fischetti, 2019, introduction to mathematical optimization, pg 69)
-
-    // Biblioteca usada
-    use minilp::*;
-
-    // O problema dual tem a direção de otimização oposta, nesse caso, maximização
-    let mut problema_minilp = Problem::new(OptimizationDirection::Maximize);
-
-    // Vetor das variaveis duais
-    let mut vars: Vec<Variable> = Vec::with_capacity(b.len());
-
-    // Transpões a matriz
-    let at = transpor_matriz(a);
-
-    // Todas as variaveis duais deve ser maiores ou iguais a zero
-    let dom = (0.0, f64::INFINITY);
-
-    for idx in 0..b.len() {
-        let cof = b[idx];
-        // Cada valor de b é o coeficiente da respectiva variavel dual
-        // restringindo os valores das variaveis entre 0 e infinito
-        vars.push(problema_minilp.add_var(cof, dom));
+// Confere o resultado com os backends disponiveis, mostrando as discrepancias
+fn conferir(
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+    resultado: &ResultadoBackend,
+    nome: &str,
+) {
+    for discrepancia in conferir_backends(a, b, c, resultado, nome) {
+        println!("Discrepancia entre backends lineares: {}", discrepancia);
     }
+}
 
-    // Para cada elemento em c
-    for idx in 0..c.len() {
-        // Cria uma expressão linear vazia
-        let mut expr = LinearExpr::empty();
-
-        for (idx_var, &var) in vars.iter().enumerate() {
-            // Adiciona à expressão linear a variavel e seu coeficiente
-            expr.add(var, at[idx][idx_var]);
+// Extrai os valores duais do problema linear a partir da solução primal x
+// O problema primal é dado por:
+// min cᵀx
+// s.a.: Ax ≥ b
+// Já o dual:
+// max bᵀy
+// s.a.: Aᵀy = c, y ≥ 0
+// (Matteo Fischetti, 2019, introduction to mathematical optimization, pg 69)
+// Pela folga complementar, yᵢ só pode ser não nulo nas linhas ativas em x,
+// isto é, onde aᵢᵀx = bᵢ. Então basta encontrar y ≥ 0 apenas nas linhas ativas
+// tal que Aᵀy = c, o que é feito por minimos quadrados não negativos, já que em
+// pontos degenerados podem existir mais linhas ativas que variaveis.
+// Retorna os valores duais de todas as linhas
+fn extrair_duais(a: &MatrizEsparsa, b: &[NumReal], c: &[NumReal], x: &[NumReal]) -> Vec<NumReal> {
+    // Encontra as linhas ativas na solução
+    let mut linhas_ativas = Vec::new();
+    for (idx_linha, &bi) in b.iter().enumerate().take(a.n_linhas()) {
+        let ax: NumReal = a.linha(idx_linha).map(|(j, cof)| cof * x[j]).sum();
+        if (ax - bi).abs() <= TOL_ATIVA * (1.0 + bi.abs()) {
+            linhas_ativas.push(idx_linha);
         }
-
-        // Adiciona a expressão linear como restrição relacionada ao valore de c
-        // Então
-        // Aᵀ_idx_1*y_1 + Aᵀ_idx_2*y_2 + Aᵀ_idx_3*y_3 + ... = c_idx
-        problema_minilp.add_constraint(expr, ComparisonOp::Eq, c[idx]);
     }
 
-    // Uma vez as variaveis com seus coeficientes e as restrições adicionadas
-    // basta pedir para a biblioteca resolver o problema
-    let solucao = problema_minilp.solve();
-
-    match solucao {
-        Ok(s) => {
-            let mut vals = Vec::new();
+    // Cada linha ativa é uma coluna de Aᵀ restrita às linhas ativas
+    let mut colunas = Vec::new();
+    for &idx_linha in &linhas_ativas {
+        colunas.push(a.linha_densa(idx_linha));
+    }
 
-            // Salva os valores de cada variável otimizada
-            for &v in &vars {
-                vals.push(s[v]);
-            }
+    // Resolve min ‖Aᵀy - c‖, y ≥ 0, nas linhas ativas
+    let y_ativas = minimos_quadrados_nao_negativos(&colunas, c);
 
-            // Retorna o valor da função maximizada e a lista das variaveis otimizadas
-            return (s.objective(), vals);
-        }
-        Err(erro) => {
-            println!("Problema dual não tem solução ... {}", erro);
-            // Encerra o programa, não tem mais sentido continuar
-            // Provavel que o codigo nunca execute essa saida,
-            // uma vez que se o dual não tem solução, o problema
-            // primal já não teria, e pra iniciar a busca da solução
-            // do dual o primal deve ter solução
-            std::process::exit(1);
-        }
+    // As linhas inativas têm valor dual nulo
+    let mut duais = vec![0.0; a.n_linhas()];
+    for (k, &idx_linha) in linhas_ativas.iter().enumerate() {
+        duais[idx_linha] = y_ativas[k];
     }
+
+    duais
 }
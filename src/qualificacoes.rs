@@ -0,0 +1,476 @@
+use crate::backend_linear::EstadoProblemaLinear;
+use crate::defs::*;
+use crate::matriz_esparsa::MatrizEsparsa;
+use crate::prob_linear::SessaoProblemaLinear;
+use crate::utils::*;
+
+// Tolerancia para considerar uma restrição de desigualdade ativa no ponto
+pub const TOL_ATIVA: NumReal = 1e-6;
+
+// Folga minima para que a direção z seja aceita como certificado do MFCQ
+const TOL_FOLGA: NumReal = 1e-9;
+
+// Resultado da verificação da qualificação de Mangasarian-Fromovitz em um ponto
+#[derive(Debug, Clone)]
+pub enum ResultadoMfcq {
+    // Existe z com ∇hᵣᵀz = 0 e ∇gⱼᵀz ≤ -folga < 0 para toda gⱼ ativa
+    Satisfeita { z: Ponto, folga: NumReal },
+
+    // Os gradientes das igualdades são linearmente dependentes,
+    // com Σ vᵣ∇hᵣ = 0 e v não nulo
+    IgualdadesDependentes { v: Vec<NumReal> },
+
+    // Não existe z, e a combinação Σ uⱼ∇gⱼ + Σ vᵣ∇hᵣ = 0, com u ≥ 0,
+    // Σ uⱼ = 1 e uⱼ não nulo apenas nas ativas, prova isso
+    Violada { u: Vec<NumReal>, v: Vec<NumReal> },
+
+    // O backend não resolveu o problema linear da verificação
+    NaoVerificada { estado: EstadoProblemaLinear },
+}
+
+impl ResultadoMfcq {
+    pub fn satisfeita(&self) -> bool {
+        matches!(self, ResultadoMfcq::Satisfeita { .. })
+    }
+}
+
+// Indices das restrições de desigualdade ativas em x, isto é, com
+// |gⱼ(x)| ≤ tolerancia. As violadas ficam de fora
+pub fn desigualdades_ativas(problema: &Problema, x: Ponto) -> Vec<usize> {
+    let (g_x, _) = problema.restricoes_em(x);
+    (0..problema.mi())
+        .filter(|&j| g_x[j].abs() <= TOL_ATIVA)
+        .collect()
+}
+
+// Indices das restrições de desigualdade violadas em x, com gⱼ(x) > tolerancia
+pub fn desigualdades_violadas(problema: &Problema, x: Ponto) -> Vec<usize> {
+    let (g_x, _) = problema.restricoes_em(x);
+    (0..problema.mi()).filter(|&j| g_x[j] > TOL_ATIVA).collect()
+}
+
+// Verifica o MFCQ no ponto x, considerando as desigualdades ativas ou
+// violadas e todas as igualdades. Como no EMFCQ, as violadas também
+// são consideradas, então a verificação vale fora da região viavel.
+// Os u do resultado são indexados por todas as mᵢ desigualdades, nulos
+// nas demais
+pub fn verificar_mfcq(problema: &Problema, x: Ponto, configuracao: &Configuracao) -> ResultadoMfcq {
+    let mut consideradas = desigualdades_ativas(problema, x);
+    consideradas.extend(desigualdades_violadas(problema, x));
+    consideradas.sort_unstable();
+    mfcq_nas_desigualdades(problema, x, &consideradas, configuracao)
+}
+
+// Verifica o MFCQ no ponto x com as desigualdades dadas e todas as igualdades
+fn mfcq_nas_desigualdades(
+    problema: &Problema,
+    x: Ponto,
+    ativas: &[usize],
+    configuracao: &Configuracao,
+) -> ResultadoMfcq {
+    let (_, _, _, _, grads_g, grads_h) = problema.avaliar_em(x);
+
+    let grads_g_ativas = ativas.iter().map(|&j| grads_g[j]).collect();
+    match mfcq_gradientes(&grads_g_ativas, &grads_h, configuracao) {
+        ResultadoMfcq::Violada { u: u_ativas, v } => {
+            let mut u = vec![0.0; problema.mi()];
+            for (k, &j) in ativas.iter().enumerate() {
+                u[j] = u_ativas[k];
+            }
+            ResultadoMfcq::Violada { u, v }
+        }
+        resultado => resultado,
+    }
+}
+
+// Verifica o MFCQ para uma lista de gradientes de desigualdades ativas
+// e de igualdades, resolvendo o problema linear
+// max s
+// s.a.: ∇hᵣᵀz = 0,          r = 1, ..., mₑ
+//       ∇gⱼᵀz + s ≤ 0,      j ativa
+//       -1 ≤ zᵢ ≤ 1,  s ≤ 1
+// O MFCQ vale se e somente se o otimo tem s > 0, e então z é o certificado.
+// Caso contrario, pelo lema de Farkas, os valores duais das linhas formam
+// a combinação não negativa dos gradientes ativos que se anula.
+// Antes, a independencia linear dos ∇hᵣ é verificada diretamente.
+// Falhar o MFCQ é o mesmo que os gradientes serem positivo-linearmente
+// dependentes, o que também é usado pelo CPLD
+fn mfcq_gradientes(
+    grads_g: &Vec<Ponto>,
+    grads_h: &Vec<Ponto>,
+    configuracao: &Configuracao,
+) -> ResultadoMfcq {
+    if let Some(v) = combinacao_nula(grads_h) {
+        return ResultadoMfcq::IgualdadesDependentes { v };
+    }
+
+    // Sem desigualdades ativas, qualquer z no nucleo dos ∇hᵣ serve, inclusive z = 0
+    if grads_g.is_empty() {
+        return ResultadoMfcq::Satisfeita {
+            z: [0.0; DIM],
+            folga: NumReal::INFINITY,
+        };
+    }
+
+    // Variaveis livres z₁, ..., z_DIM e s, na forma
+    // min -s
+    // s.a.: Ax ≥ b
+    let col_s = DIM;
+    let mut a = MatrizEsparsa::nova(DIM + 1);
+    let mut b = Vec::new();
+    let mut nomes_linhas = Vec::new();
+
+    let linha_z = |grad: &Ponto, sinal: NumReal| -> Vec<(usize, NumReal)> {
+        (0..DIM).map(|i| (i, sinal * grad[i])).collect()
+    };
+
+    // ∇hᵣᵀz = 0 como duas linhas ≥
+    for (r, grad) in grads_h.iter().enumerate() {
+        a.adicionar_linha(&linha_z(grad, 1.0));
+        b.push(0.0);
+        nomes_linhas.push(format!("h_{}+", r));
+        a.adicionar_linha(&linha_z(grad, -1.0));
+        b.push(0.0);
+        nomes_linhas.push(format!("h_{}-", r));
+    }
+
+    // -∇gⱼᵀz - s ≥ 0
+    for (j, grad) in grads_g.iter().enumerate() {
+        let mut linha = linha_z(grad, -1.0);
+        linha.push((col_s, -1.0));
+        a.adicionar_linha(&linha);
+        b.push(0.0);
+        nomes_linhas.push(format!("g_{}", j));
+    }
+
+    // Limites de z e de s, que tornam o problema limitado
+    for i in 0..DIM {
+        a.adicionar_linha(&[(i, 1.0)]);
+        b.push(-1.0);
+        nomes_linhas.push(format!("z_{}l", i));
+        a.adicionar_linha(&[(i, -1.0)]);
+        b.push(-1.0);
+        nomes_linhas.push(format!("z_{}u", i));
+    }
+    a.adicionar_linha(&[(col_s, -1.0)]);
+    b.push(-1.0);
+    nomes_linhas.push("s_u".to_string());
+
+    let mut c = vec![0.0; DIM + 1];
+    c[col_s] = -1.0;
+
+    // O problema sempre tem solução, já que z = 0 e s = 0 são viaveis e
+    // as variaveis são limitadas, então só um erro do backend o impede
+    let mut sessao = SessaoProblemaLinear::nova(configuracao);
+    let (solucao, duais) = match sessao.resolver_matriz(&a, &b, &c, &nomes_linhas) {
+        Ok(solucao) => solucao,
+        Err(estado) => return ResultadoMfcq::NaoVerificada { estado },
+    };
+
+    let folga = solucao[col_s];
+    if folga > TOL_FOLGA {
+        let mut z = [0.0; DIM];
+        z.copy_from_slice(&solucao[0..DIM]);
+        return ResultadoMfcq::Satisfeita { z, folga };
+    }
+
+    // Com s = 0 no otimo, os duais das linhas de gⱼ somam 1 e os dos
+    // limites são nulos, então da coluna z de Aᵀy = c sai
+    // Σ uⱼ∇gⱼ + Σ vᵣ∇hᵣ = 0, com uⱼ = yⱼ e vᵣ = y⁻ᵣ - y⁺ᵣ
+    let me = grads_h.len();
+    let v: Vec<NumReal> = (0..me).map(|r| duais[2 * r + 1] - duais[2 * r]).collect();
+    let u: Vec<NumReal> = (0..grads_g.len()).map(|k| duais[2 * me + k]).collect();
+
+    ResultadoMfcq::Violada { u, v }
+}
+
+// Mostra o resultado da verificação de forma legivel
+pub fn mostrar_mfcq(resultado: &ResultadoMfcq) {
+    match resultado {
+        ResultadoMfcq::Satisfeita { z, folga } => {
+            if folga.is_infinite() {
+                println!("MFCQ? Sim, nenhuma desigualdade ativa");
+            } else {
+                println!("MFCQ? Sim, z = {:?}, max ∇gⱼᵀz = {:?}", z, -folga);
+            }
+        }
+        ResultadoMfcq::IgualdadesDependentes { v } => {
+            println!(
+                "MFCQ? Não, gradientes de h_r(x) são linearmente dependentes, Σ vᵣ∇hᵣ = 0 com v = {:?}",
+                v
+            );
+        }
+        ResultadoMfcq::Violada { u, v } => {
+            println!(
+                "MFCQ? Não, Σ uⱼ∇gⱼ + Σ vᵣ∇hᵣ = 0 com u = {:?} e v = {:?}",
+                u, v
+            );
+        }
+        ResultadoMfcq::NaoVerificada { estado } => {
+            println!(
+                "MFCQ? Não verificado, problema linear sem solução ... {}",
+                estado
+            );
+        }
+    }
+}
+
+// Limite de restrições ativas para a verificação do CRCQ e do CPLD, que
+// percorrem todos os subconjuntos de gradientes ativos
+const MAX_ATIVAS_SUBCONJUNTOS: usize = 12;
+
+// Raio da vizinhança de x usada no CRCQ e no CPLD, relativo a 1 + ‖x‖
+const RAIO_VIZINHANCA: NumReal = 1e-3;
+
+// Resultado de uma qualificação verificada sobre todos os subconjuntos
+// dos gradientes ativos, como o CRCQ e o CPLD. A vizinhança de x só é
+// vista nos pontos amostrados a um raio, então o resultado é uma
+// indicação e não uma prova
+#[derive(Debug, Clone)]
+pub enum ResultadoSubconjuntos {
+    // Nenhum ponto amostrado quebra a qualificação
+    SatisfeitaNaAmostra,
+
+    // Subconjunto que quebra a qualificação em algum ponto amostrado,
+    // pelos nomes g_j e h_r
+    Violada { subconjunto: Vec<String> },
+
+    // Restrições ativas demais para percorrer todos os subconjuntos
+    NaoVerificada,
+}
+
+impl std::fmt::Display for ResultadoSubconjuntos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResultadoSubconjuntos::SatisfeitaNaAmostra => write!(f, "Sim (amostrado)"),
+            ResultadoSubconjuntos::Violada { subconjunto } => write!(
+                f,
+                "Não (amostrado), subconjunto {{{}}}",
+                subconjunto.join(", ")
+            ),
+            ResultadoSubconjuntos::NaoVerificada => write!(
+                f,
+                "não verificado, mais de {} restrições ativas",
+                MAX_ATIVAS_SUBCONJUNTOS
+            ),
+        }
+    }
+}
+
+// Diagnostico das qualificações de restrições em um ponto.
+// As implicações conhecidas são LICQ ⇒ MFCQ ⇒ CPLD e LICQ ⇒ CRCQ ⇒ CPLD,
+// e os multiplicadores só são garantidamente limitados sob o MFCQ, e
+// unicos sob o LICQ
+#[derive(Debug, Clone)]
+pub struct DiagnosticoQualificacoes {
+    pub ativas: Vec<usize>,     // Desigualdades ativas
+    pub violadas: Vec<usize>,   // Desigualdades violadas, fora das qualificações
+    pub posto_jacobiana: usize, // Posto da jacobiana das restrições ativas
+    pub licq: bool,
+    pub mfcq: ResultadoMfcq,
+    pub crcq: ResultadoSubconjuntos,
+    pub cpld: ResultadoSubconjuntos,
+}
+
+impl DiagnosticoQualificacoes {
+    // Nomes das qualificações que falharam
+    pub fn falhas(&self) -> Vec<&'static str> {
+        let mut falhas = Vec::new();
+        if !self.licq {
+            falhas.push("LICQ");
+        }
+        if !self.mfcq.satisfeita() {
+            falhas.push("MFCQ");
+        }
+        if let ResultadoSubconjuntos::Violada { .. } = self.crcq {
+            falhas.push("CRCQ");
+        }
+        if let ResultadoSubconjuntos::Violada { .. } = self.cpld {
+            falhas.push("CPLD");
+        }
+        falhas
+    }
+
+    // Mostra o diagnostico de forma legivel
+    pub fn mostrar(&self, problema: &Problema) {
+        let nomes_ativas: Vec<String> = self
+            .ativas
+            .iter()
+            .map(|&j| problema.nome_desigualdade(j))
+            .collect();
+        println!(
+            "Restrições ativas: {{{}}} e {} igualdades",
+            nomes_ativas.join(", "),
+            problema.me()
+        );
+        if !self.violadas.is_empty() {
+            let nomes_violadas: Vec<String> = self
+                .violadas
+                .iter()
+                .map(|&j| problema.nome_desigualdade(j))
+                .collect();
+            println!(
+                "Restrições violadas: {{{}}}, o ponto não é viavel",
+                nomes_violadas.join(", ")
+            );
+        }
+        println!(
+            "LICQ? {}, posto da jacobiana ativa {} de {}",
+            if self.licq { "Sim" } else { "Não" },
+            self.posto_jacobiana,
+            self.ativas.len() + problema.me()
+        );
+        mostrar_mfcq(&self.mfcq);
+        println!("CRCQ? {}", self.crcq);
+        println!("CPLD? {}", self.cpld);
+
+        let falhas = self.falhas();
+        if !falhas.is_empty() {
+            println!("Qualificações que falharam: {}", falhas.join(", "));
+        }
+    }
+}
+
+// Identifica as restrições ativas em x e verifica o LICQ, o MFCQ, o CRCQ
+// e o CPLD. As restrições violadas não entram nas qualificações e são
+// mostradas à parte
+pub fn diagnosticar_qualificacoes(
+    problema: &Problema,
+    x: Ponto,
+    configuracao: &Configuracao,
+) -> DiagnosticoQualificacoes {
+    let (_, _, _, _, grads_g, grads_h) = problema.avaliar_em(x);
+    let ativas = desigualdades_ativas(problema, x);
+    let violadas = desigualdades_violadas(problema, x);
+
+    // Jacobiana das restrições ativas, uma linha por gradiente
+    let mut jacobiana_ativa: Vec<Ponto> = ativas.iter().map(|&j| grads_g[j]).collect();
+    jacobiana_ativa.extend(grads_h.iter());
+
+    // LICQ: os gradientes ativos são linearmente independentes
+    let posto_jacobiana = posto(&jacobiana_ativa);
+    let licq = !sao_linearmente_dependentes(&jacobiana_ativa);
+
+    let mfcq = mfcq_nas_desigualdades(problema, x, &ativas, configuracao);
+
+    let (crcq, cpld) = if ativas.len() + problema.me() > MAX_ATIVAS_SUBCONJUNTOS {
+        (
+            ResultadoSubconjuntos::NaoVerificada,
+            ResultadoSubconjuntos::NaoVerificada,
+        )
+    } else {
+        verificar_crcq_cpld(problema, x, &ativas, configuracao)
+    };
+
+    DiagnosticoQualificacoes {
+        ativas,
+        violadas,
+        posto_jacobiana,
+        licq,
+        mfcq,
+        crcq,
+        cpld,
+    }
+}
+
+// Pontos da vizinhança de x, x + εs para todo s ∈ {-1, 0, 1}ⁿ não nulo
+fn vizinhanca(x: Ponto) -> Vec<Ponto> {
+    let raio = RAIO_VIZINHANCA * (1.0 + norma(&x));
+
+    let mut pontos = Vec::new();
+    for codigo in 1..3usize.pow(DIM as u32) {
+        let mut ponto = x;
+        let mut resto = codigo;
+        for componente in ponto.iter_mut() {
+            *componente += raio * ((resto % 3) as NumReal - 1.0);
+            resto /= 3;
+        }
+        if ponto != x {
+            pontos.push(ponto);
+        }
+    }
+    pontos
+}
+
+// Verifica o CRCQ e o CPLD percorrendo todos os subconjuntos S dos
+// gradientes ativos em x, onde o bit k do codigo marca o k-esimo gradiente,
+// primeiro as desigualdades ativas e depois as igualdades.
+// CRCQ: o posto de S é o mesmo em toda a vizinhança de x.
+// CPLD: se S é positivo-linearmente dependente em x, continua linearmente
+// dependente em toda a vizinhança.
+// Como o posto só pode aumentar perto de x, basta comparar com o posto em x
+// nos pontos da vizinhança. Só os 3ⁿ - 1 pontos de um raio são vistos,
+// então o resultado vale para essa amostra
+fn verificar_crcq_cpld(
+    problema: &Problema,
+    x: Ponto,
+    ativas: &[usize],
+    configuracao: &Configuracao,
+) -> (ResultadoSubconjuntos, ResultadoSubconjuntos) {
+    // Gradientes ativos em x e em cada ponto da vizinhança, na mesma ordem
+    let gradientes_ativos = |ponto: Ponto| -> Vec<Ponto> {
+        let (_, _, _, _, grads_g, grads_h) = problema.avaliar_em(ponto);
+        let mut grads: Vec<Ponto> = ativas.iter().map(|&j| grads_g[j]).collect();
+        grads.extend(grads_h);
+        grads
+    };
+    let grads_x = gradientes_ativos(x);
+    let grads_vizinhanca: Vec<Vec<Ponto>> =
+        vizinhanca(x).into_iter().map(gradientes_ativos).collect();
+
+    let nomes: Vec<String> = ativas
+        .iter()
+        .map(|&j| problema.nome_desigualdade(j))
+        .chain((0..problema.me()).map(|r| problema.nome_igualdade(r)))
+        .collect();
+    let n_ativas = ativas.len();
+
+    let mut crcq = ResultadoSubconjuntos::SatisfeitaNaAmostra;
+    let mut cpld = ResultadoSubconjuntos::SatisfeitaNaAmostra;
+
+    for codigo in 1..(1usize << grads_x.len()) {
+        let indices: Vec<usize> = (0..grads_x.len())
+            .filter(|k| codigo & (1 << k) != 0)
+            .collect();
+        let subconjunto =
+            |grads: &Vec<Ponto>| -> Vec<Ponto> { indices.iter().map(|&k| grads[k]).collect() };
+        let nomes_subconjunto = || indices.iter().map(|&k| nomes[k].clone()).collect();
+
+        let posto_x = posto(&subconjunto(&grads_x));
+        let postos_vizinhanca: Vec<usize> = grads_vizinhanca
+            .iter()
+            .map(|grads| posto(&subconjunto(grads)))
+            .collect();
+
+        if let ResultadoSubconjuntos::SatisfeitaNaAmostra = crcq {
+            if postos_vizinhanca.iter().any(|&p| p != posto_x) {
+                crcq = ResultadoSubconjuntos::Violada {
+                    subconjunto: nomes_subconjunto(),
+                };
+            }
+        }
+
+        if let ResultadoSubconjuntos::SatisfeitaNaAmostra = cpld {
+            // Só interessa se em algum ponto vizinho S fica independente
+            if postos_vizinhanca.contains(&indices.len()) {
+                let grads_g: Vec<Ponto> = indices
+                    .iter()
+                    .filter(|&&k| k < n_ativas)
+                    .map(|&k| grads_x[k])
+                    .collect();
+                let grads_h: Vec<Ponto> = indices
+                    .iter()
+                    .filter(|&&k| k >= n_ativas)
+                    .map(|&k| grads_x[k])
+                    .collect();
+                if !mfcq_gradientes(&grads_g, &grads_h, configuracao).satisfeita() {
+                    cpld = ResultadoSubconjuntos::Violada {
+                        subconjunto: nomes_subconjunto(),
+                    };
+                }
+            }
+        }
+    }
+
+    (crcq, cpld)
+}
@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::algebra_linear::Matriz;
+use crate::defs::*;
+use crate::regularizacao_hessiana::{regularizar_hessiana, RegularizacaoHessiana};
+use crate::utils::{
+    matriz_por_escalar, norma, produto_escalar, produto_externo, produto_interno,
+    produto_matriz_vetor, soma_matriz, soma_pontos, subtracao_pontos,
+};
+
+// Fração minima de sᵀBs que sᵀy precisa ter para o BFGS não ser amortecido
+const FRACAO_AMORTECIMENTO: NumReal = 0.2;
+
+// O SR1 pula a atualização quando |sᵀ(y - Bs)| < r‖s‖‖y - Bs‖
+const TOL_PULO_SR1: NumReal = 1e-8;
+
+// O L-BFGS descarta os pares com sᵀy ≤ tol‖s‖‖y‖, que estragariam a
+// definição positiva da aproximação
+const TOL_CURVATURA_LBFGS: NumReal = 1e-10;
+
+// Forma de obter a hessiana da lagrangiana usada na restrição 1c
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TipoHessiana {
+    #[serde(rename = "exata")]
+    Exata, // Diferenças finitas da lagrangiana no novo ponto
+    #[serde(rename = "bfgs")]
+    BfgsAmortecido, // BFGS com o amortecimento de Powell
+    #[serde(rename = "sr1")]
+    Sr1, // Atualização simetrica de posto 1, pulando as instaveis
+    #[serde(rename = "lbfgs")]
+    LBfgs, // BFGS de memoria limitada, na representação compacta
+}
+
+impl TipoHessiana {
+    // Nome usado para escolher a forma
+    pub fn nome(&self) -> &'static str {
+        match self {
+            TipoHessiana::Exata => "exata",
+            TipoHessiana::BfgsAmortecido => "bfgs",
+            TipoHessiana::Sr1 => "sr1",
+            TipoHessiana::LBfgs => "lbfgs",
+        }
+    }
+
+    // Encontra a forma pelo nome
+    pub fn por_nome(nome: &str) -> Option<TipoHessiana> {
+        [
+            TipoHessiana::Exata,
+            TipoHessiana::BfgsAmortecido,
+            TipoHessiana::Sr1,
+            TipoHessiana::LBfgs,
+        ]
+        .iter()
+        .find(|tipo| tipo.nome() == nome)
+        .copied()
+    }
+}
+
+// Aproximação da hessiana da lagrangiana mantida entre as iterações.
+// As formas densas guardam a matriz, e o L-BFGS guarda apenas os ultimos
+// pares (s, y), sendo usado só pelo produto Hv, que é o que a restrição
+// 1c precisa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AproximacaoHessiana {
+    tipo: TipoHessiana,
+    #[serde(with = "crate::reais_json")]
+    densa: Vec<Vec<NumReal>>,
+    #[serde(with = "crate::reais_json")]
+    pares: VecDeque<(Ponto, Ponto)>,
+    memoria: usize,
+    #[serde(with = "crate::reais_json")]
+    tau: NumReal, // Ultimo multiplo da identidade usado na regularização
+}
+
+impl AproximacaoHessiana {
+    // Começa pela identidade, que é o recomendado para os quase Newton
+    pub fn nova(configuracao: &Configuracao) -> Self {
+        let mut densa = vec![vec![0.0; DIM]; DIM];
+        for (i, linha) in densa.iter_mut().enumerate() {
+            linha[i] = 1.0;
+        }
+
+        Self {
+            tipo: configuracao.tipo_hessiana,
+            densa,
+            pares: VecDeque::new(),
+            memoria: configuracao.memoria_lbfgs,
+            tau: 0.0,
+        }
+    }
+
+    // Produto Hv
+    pub fn produto(&self, v: &Ponto) -> Ponto {
+        match self.tipo {
+            TipoHessiana::LBfgs => self.produto_lbfgs(v),
+            _ => produto_matriz_vetor(&self.densa, v),
+        }
+    }
+
+    // Atualiza a aproximação depois do passo s = x_novo - x, com
+    // y = ∇L(x_novo) - ∇L(x). A forma exata apenas recalcula a hessiana
+    // da lagrangiana no novo ponto, com a função dada
+    pub fn atualizar(
+        &mut self,
+        s: Ponto,
+        y: Ponto,
+        hessiana_lagrangiana: impl FnOnce(Ponto) -> Vec<Vec<NumReal>>,
+        x_novo: Ponto,
+    ) {
+        match self.tipo {
+            TipoHessiana::Exata => self.densa = hessiana_lagrangiana(x_novo),
+            TipoHessiana::BfgsAmortecido => {
+                self.densa = bfgs_amortecido(self.densa.clone(), s, y);
+            }
+            TipoHessiana::Sr1 => self.densa = sr1(self.densa.clone(), s, y),
+            TipoHessiana::LBfgs => {
+                if produto_interno(&s, &y) > TOL_CURVATURA_LBFGS * norma(&s) * norma(&y) {
+                    self.pares.push_back((s, y));
+                    if self.pares.len() > self.memoria {
+                        self.pares.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    // Copia definida positiva da aproximação, para ser consumida pela
+    // restrição 1c, junto do deslocamento aplicado. A aproximação em si não
+    // é alterada, para não interferir nas atualizações quase Newton.
+    // O L-BFGS já é definido positivo pela escolha dos pares
+    pub fn regularizada(
+        &mut self,
+        regularizacao: RegularizacaoHessiana,
+    ) -> (AproximacaoHessiana, NumReal) {
+        let mut regularizada = self.clone();
+        if self.tipo == TipoHessiana::LBfgs {
+            return (regularizada, 0.0);
+        }
+
+        let (densa, deslocamento) = regularizar_hessiana(&self.densa, regularizacao, &mut self.tau);
+        regularizada.densa = densa;
+        (regularizada, deslocamento)
+    }
+
+    // Produto pela representação compacta do L-BFGS
+    // B = σI - W M⁻¹ Wᵀ, W = [σS  Y], M = [σSᵀS  L; Lᵀ  -D]
+    // com S e Y tendo os pares nas colunas, D = diag(sᵢᵀyᵢ), Lᵢⱼ = sᵢᵀyⱼ
+    // para i > j e nulo do contrario, e σ = yᵀy/sᵀy do ultimo par
+    // (Byrd, Nocedal e Schnabel, 1994, Representations of quasi-Newton
+    // matrices and their use in limited memory methods)
+    fn produto_lbfgs(&self, v: &Ponto) -> Ponto {
+        let (s_ult, y_ult) = match self.pares.back() {
+            Some(par) => *par,
+            None => return *v,
+        };
+        let sigma = produto_interno(&y_ult, &y_ult) / produto_interno(&s_ult, &y_ult);
+        let m = self.pares.len();
+
+        let mut matriz_m = Matriz::nula(2 * m, 2 * m);
+        for (i, (s_i, y_i)) in self.pares.iter().enumerate() {
+            for (j, (s_j, y_j)) in self.pares.iter().enumerate() {
+                matriz_m[(i, j)] = sigma * produto_interno(s_i, s_j);
+                if i > j {
+                    matriz_m[(i, m + j)] = produto_interno(s_i, y_j);
+                    matriz_m[(m + j, i)] = produto_interno(s_i, y_j);
+                }
+            }
+            matriz_m[(m + i, m + i)] = -produto_interno(s_i, y_i);
+        }
+
+        // Wᵀv
+        let mut wtv = vec![0.0; 2 * m];
+        for (i, (s_i, y_i)) in self.pares.iter().enumerate() {
+            wtv[i] = sigma * produto_interno(s_i, v);
+            wtv[m + i] = produto_interno(y_i, v);
+        }
+
+        // M é invertivel enquanto os pares satisfazem sᵀy > 0 e os s são
+        // independentes. Do contrario se usa só σI
+        let p = match matriz_m.lu() {
+            Some(lu) => lu.resolver(&wtv),
+            None => return produto_escalar(sigma, *v),
+        };
+
+        // σv - Wp
+        let mut resultado = produto_escalar(sigma, *v);
+        for (i, (s_i, y_i)) in self.pares.iter().enumerate() {
+            resultado = subtracao_pontos(resultado, produto_escalar(sigma * p[i], *s_i));
+            resultado = subtracao_pontos(resultado, produto_escalar(p[m + i], *y_i));
+        }
+        resultado
+    }
+}
+
+// Calcula a hessiana do proximo ponto usando as informações de atualização
+// Usando o método BFGS, se usa o grandiete do proximo ponto e do ponto atual
+// junto com a informação de atualização do ponto (x_k+1 = x_k + a*d) onde a*d
+// é a informação.
+// Logo
+// y = ∇f(x_k+1) - ∇f(x_k)
+// s = (x_k+1) - (x_k)
+// Quando sᵀy < 0.2 sᵀBs, y é trocado por r = θy + (1 - θ)Bs, com θ
+// escolhido para que sᵀr = 0.2 sᵀBs. Assim a atualização sempre mantém B
+// definida positiva, o que o BFGS puro não garante quando sᵀy ≤ 0
+// (Nocedal e Wright, 2006, Numerical Optimization, pg 537)
+fn bfgs_amortecido(hessiana: Vec<Vec<NumReal>>, s: Ponto, y: Ponto) -> Vec<Vec<NumReal>> {
+    // Calcula Bs, visto que é usado mais de uma vez
+    let b_s = produto_matriz_vetor(&hessiana, &s);
+    let s_b_s = produto_interno(&s, &b_s);
+    let s_y = produto_interno(&s, &y);
+
+    // Passo nulo, nada a atualizar
+    if s_b_s <= 0.0 {
+        return hessiana;
+    }
+
+    let theta = if s_y >= FRACAO_AMORTECIMENTO * s_b_s {
+        1.0
+    } else {
+        (1.0 - FRACAO_AMORTECIMENTO) * s_b_s / (s_b_s - s_y)
+    };
+    let r = soma_pontos(produto_escalar(theta, y), produto_escalar(1.0 - theta, b_s));
+
+    // Calculo da aproximação da hessiana com o passo de atualização:
+    // B = B + alpha*rrᵀ + beta*vvᵀ
+    // v = (Bs)
+    // alpha = 1/rᵀs
+    // beta = -1/(sᵀ(Bs))
+    let alpha = 1.0 / produto_interno(&r, &s);
+    let beta = -1.0 / s_b_s;
+
+    // Caso aconteça alguma divisão por 0 não atualiza a hessiana
+    if !(alpha.is_finite() && beta.is_finite()) {
+        return hessiana;
+    }
+
+    let alpha_rrt = matriz_por_escalar(alpha, produto_externo(r, r));
+    let beta_vvt = matriz_por_escalar(beta, produto_externo(b_s, b_s));
+
+    soma_matriz(&hessiana, &soma_matriz(&alpha_rrt, &beta_vvt))
+}
+
+// Atualização simetrica de posto 1
+// B = B + vvᵀ/sᵀv, v = y - Bs
+// que não mantém B definida positiva, mas costuma aproximar melhor a
+// hessiana verdadeira, inclusive quando ela é indefinida. Quando sᵀv é
+// pequeno a atualização explode, e é pulada
+// (Nocedal e Wright, 2006, Numerical Optimization, pg 145)
+fn sr1(hessiana: Vec<Vec<NumReal>>, s: Ponto, y: Ponto) -> Vec<Vec<NumReal>> {
+    let v = subtracao_pontos(y, produto_matriz_vetor(&hessiana, &s));
+    let s_v = produto_interno(&s, &v);
+
+    if s_v.abs() < TOL_PULO_SR1 * norma(&s) * norma(&v) || s_v == 0.0 {
+        return hessiana;
+    }
+
+    soma_matriz(
+        &hessiana,
+        &matriz_por_escalar(1.0 / s_v, produto_externo(v, v)),
+    )
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    const TOL_TESTE: NumReal = 1e-10;
+
+    // Pares (s, As) de uma quadratica de hessiana A definida positiva
+    fn pares_quadratica() -> Vec<(Ponto, Ponto)> {
+        let a = vec![vec![2.0, 0.5], vec![0.5, 1.0]];
+        [[1.0, 0.0], [0.3, 1.0], [-0.5, 0.7]]
+            .iter()
+            .map(|&s| (s, produto_matriz_vetor(&a, &s)))
+            .collect()
+    }
+
+    fn aproximacao(tipo: TipoHessiana) -> AproximacaoHessiana {
+        AproximacaoHessiana::nova(&Configuracao {
+            tipo_hessiana: tipo,
+            memoria_lbfgs: 5,
+            ..Configuracao::padrao()
+        })
+    }
+
+    fn atualizar(aproximacao: &mut AproximacaoHessiana, s: Ponto, y: Ponto) {
+        aproximacao.atualizar(s, y, |_| unreachable!(), [0.0; DIM]);
+    }
+
+    // Matriz da aproximação, pelos produtos com a base canonica
+    fn densa_de(aproximacao: &AproximacaoHessiana) -> Vec<Vec<NumReal>> {
+        let colunas: Vec<Ponto> = (0..DIM)
+            .map(|j| {
+                let mut e = [0.0; DIM];
+                e[j] = 1.0;
+                aproximacao.produto(&e)
+            })
+            .collect();
+        (0..DIM)
+            .map(|i| colunas.iter().map(|coluna| coluna[i]).collect())
+            .collect()
+    }
+
+    fn proximas(a: &[Vec<NumReal>], b: &[Vec<NumReal>]) -> bool {
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .all(|(x, y)| (x - y).abs() <= TOL_TESTE * (1.0 + x.abs()))
+    }
+
+    // O L-BFGS compacto é o BFGS denso que parte de σI, com σ do ultimo
+    // par, e aplica os mesmos pares na mesma ordem
+    #[test]
+    fn lbfgs_confere_com_bfgs_denso() {
+        let pares = pares_quadratica();
+        let mut lbfgs = aproximacao(TipoHessiana::LBfgs);
+        for &(s, y) in &pares {
+            atualizar(&mut lbfgs, s, y);
+        }
+
+        let (s_ult, y_ult) = pares[pares.len() - 1];
+        let sigma = produto_interno(&y_ult, &y_ult) / produto_interno(&s_ult, &y_ult);
+        let mut densa = vec![vec![sigma, 0.0], vec![0.0, sigma]];
+        for &(s, y) in &pares {
+            // Pares de uma quadratica convexa, sem amortecimento
+            let b_s = produto_matriz_vetor(&densa, &s);
+            assert!(produto_interno(&s, &y) >= FRACAO_AMORTECIMENTO * produto_interno(&s, &b_s));
+            densa = bfgs_amortecido(densa, s, y);
+        }
+
+        assert!(proximas(&densa_de(&lbfgs), &densa));
+    }
+
+    // O L-BFGS guarda só os ultimos pares da memoria, e descarta os de
+    // curvatura não positiva
+    #[test]
+    fn lbfgs_respeita_memoria_e_curvatura() {
+        let mut lbfgs = AproximacaoHessiana::nova(&Configuracao {
+            tipo_hessiana: TipoHessiana::LBfgs,
+            memoria_lbfgs: 2,
+            ..Configuracao::padrao()
+        });
+        for (s, y) in pares_quadratica() {
+            atualizar(&mut lbfgs, s, y);
+        }
+        assert_eq!(lbfgs.pares.len(), 2);
+
+        atualizar(&mut lbfgs, [1.0, 0.0], [-1.0, 0.0]);
+        assert_eq!(lbfgs.pares.len(), 2);
+    }
+
+    // O BFGS satisfaz a equação secante Bs = y sem amortecimento, e
+    // continua definido positivo com sᵀy < 0, quando é amortecido
+    #[test]
+    fn bfgs_amortecido_secante_e_definido_positivo() {
+        let mut bfgs = aproximacao(TipoHessiana::BfgsAmortecido);
+        for (s, y) in pares_quadratica() {
+            atualizar(&mut bfgs, s, y);
+            let b_s = bfgs.produto(&s);
+            for i in 0..DIM {
+                assert!((b_s[i] - y[i]).abs() <= TOL_TESTE);
+            }
+        }
+
+        atualizar(&mut bfgs, [1.0, 1.0], [-1.0, -0.5]);
+        let (autovalores, _) = Matriz::de_linhas(&bfgs.densa, DIM).autodecomposicao_simetrica();
+        assert!(autovalores[0] > 0.0, "{:?}", autovalores);
+    }
+
+    // O SR1 satisfaz a equação secante, mesmo com curvatura negativa, e
+    // pula a atualização quando sᵀ(y - Bs) é desprezivel
+    #[test]
+    fn sr1_secante_e_pulo() {
+        let mut sr1 = aproximacao(TipoHessiana::Sr1);
+        let (s, y) = ([1.0, 0.5], [-1.0, 0.5]);
+        atualizar(&mut sr1, s, y);
+        let b_s = sr1.produto(&s);
+        for i in 0..DIM {
+            assert!((b_s[i] - y[i]).abs() <= TOL_TESTE);
+        }
+
+        // y - Bs ortogonal a s
+        let antes = sr1.densa.clone();
+        let b_s = sr1.produto(&[1.0, 0.0]);
+        atualizar(&mut sr1, [1.0, 0.0], [b_s[0], b_s[1] + 1.0]);
+        assert_eq!(sr1.densa, antes);
+    }
+}
@@ -0,0 +1,435 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+use crate::defs::{Caixa, Configuracao, NumReal, Ponto, Problema, DIM};
+use crate::intervalo::Intervalo;
+use crate::resolucao::resolver;
+
+// Folga das restrições na caixa e nos pontos, já que as igualdades só
+// são satisfeitas até o arredondamento
+pub const TOL_VIABILIDADE: NumReal = 1e-6;
+
+// Caixas com todas as larguras abaixo dessa fração das larguras da caixa
+// inicial não são mais divididas
+const LARGURA_MINIMA: NumReal = 1e-9;
+
+// A cada tantas caixas, uma busca local do SCP parte do meio da caixa de
+// menor cota inferior, para melhorar a cota superior
+const INTERVALO_BUSCA_LOCAL: usize = 500;
+
+// Caixa ainda não descartada, com a cota inferior do objetivo nela
+#[derive(Debug, Clone)]
+struct NoCaixa {
+    caixa: Caixa,
+    cota: NumReal,
+}
+
+// O BinaryHeap tira o maior, então a ordem é invertida para tirar a caixa
+// de menor cota
+impl Ord for NoCaixa {
+    fn cmp(&self, outro: &Self) -> Ordering {
+        outro.cota.total_cmp(&self.cota)
+    }
+}
+
+impl PartialOrd for NoCaixa {
+    fn partial_cmp(&self, outro: &Self) -> Option<Ordering> {
+        Some(self.cmp(outro))
+    }
+}
+
+impl PartialEq for NoCaixa {
+    fn eq(&self, outro: &Self) -> bool {
+        self.cmp(outro) == Ordering::Equal
+    }
+}
+
+impl Eq for NoCaixa {}
+
+// Resultado da busca global de um problema
+#[derive(Debug, Clone)]
+pub struct ResultadoRamificacao {
+    pub nome: String,
+
+    // Caixa das variaveis onde a busca foi feita, a unica coberta pelo
+    // certificado
+    pub caixa: Caixa,
+
+    // Melhor ponto viavel encontrado e a cota inferior do otimo global na
+    // caixa inicial
+    pub incumbente: Option<(Ponto, NumReal)>,
+    pub cota_inferior: NumReal,
+    pub tolerancia: NumReal,
+
+    // Caixas que ainda podem conter o otimo global, vazia quando a busca
+    // terminou
+    pub envoltorio: Vec<Caixa>,
+
+    pub caixas_examinadas: usize,
+    pub buscas_locais: usize,
+    pub tempo: Duration,
+}
+
+impl ResultadoRamificacao {
+    // Lacuna aceita para a cota superior dada, relativa a max(1, |f|)
+    fn lacuna_aceita(tolerancia: NumReal, cota_superior: NumReal) -> NumReal {
+        tolerancia * cota_superior.abs().max(1.0)
+    }
+
+    // O incumbente é ε-otimo quando a lacuna até a cota inferior é aceita
+    pub fn certificado(&self) -> bool {
+        match self.incumbente {
+            Some((_, valor)) => {
+                valor - self.cota_inferior
+                    <= ResultadoRamificacao::lacuna_aceita(self.tolerancia, valor)
+            }
+            None => false,
+        }
+    }
+
+    // Menor caixa que contém todo o envoltorio
+    pub fn caixa_envoltorio(&self) -> Option<Caixa> {
+        let primeira = *self.envoltorio.first()?;
+        Some(self.envoltorio.iter().fold(primeira, |mut uniao, caixa| {
+            for i in 0..DIM {
+                uniao[i] = Intervalo::novo(
+                    uniao[i].inf.min(caixa[i].inf),
+                    uniao[i].sup.max(caixa[i].sup),
+                );
+            }
+            uniao
+        }))
+    }
+
+    pub fn mostrar(&self) {
+        println!(
+            "Busca global {}: {} caixas, {} buscas locais, {:.3}s",
+            self.nome,
+            self.caixas_examinadas,
+            self.buscas_locais,
+            self.tempo.as_secs_f64()
+        );
+        let intervalos: Vec<[NumReal; 2]> = self
+            .caixa
+            .iter()
+            .map(|intervalo| [intervalo.inf, intervalo.sup])
+            .collect();
+        println!("Caixa das variaveis: {:?}", intervalos);
+
+        match self.incumbente {
+            Some((x, valor)) if self.certificado() => {
+                println!("Otimo global certificado");
+                println!("x* = {:?}", x);
+                println!("f(x*) = {:?}", valor);
+                println!(
+                    "f* ∈ [{:?}, {:?}], lacuna {:e}",
+                    self.cota_inferior,
+                    valor,
+                    valor - self.cota_inferior
+                );
+            }
+            Some((x, valor)) => {
+                println!("Otimo global não certificado");
+                println!("x = {:?}", x);
+                println!("f(x) = {:?}", valor);
+                println!("f* ∈ [{:?}, {:?}]", self.cota_inferior, valor);
+            }
+            None if self.envoltorio.is_empty() => println!("Problema inviavel na caixa"),
+            None => {
+                println!("Nenhum ponto viavel encontrado");
+                println!("f* ≥ {:?}", self.cota_inferior);
+            }
+        }
+
+        if let Some(uniao) = self.caixa_envoltorio() {
+            let intervalos: Vec<[NumReal; 2]> = uniao
+                .iter()
+                .map(|intervalo| [intervalo.inf, intervalo.sup])
+                .collect();
+            println!(
+                "Envoltorio do otimo: {} caixas em {:?}",
+                self.envoltorio.len(),
+                intervalos
+            );
+        }
+    }
+}
+
+// Cota inferior do objetivo na caixa, ou None quando alguma restrição não
+// pode ser satisfeita em nenhum ponto dela
+pub fn cota_da_caixa(problema: &Problema, caixa: Caixa) -> Option<NumReal> {
+    if let Some(desigualdades) = &problema.desigualdades_intervalares {
+        if desigualdades(caixa).iter().any(|g| g.inf > TOL_VIABILIDADE) {
+            return None;
+        }
+    }
+    if let Some(igualdades) = &problema.igualdades_intervalares {
+        if igualdades(caixa)
+            .iter()
+            .any(|h| h.inf > TOL_VIABILIDADE || h.sup < -TOL_VIABILIDADE)
+        {
+            return None;
+        }
+    }
+
+    // Sem cota, como quando a função não está definida em parte da caixa
+    let objetivo = (problema.objetivo_intervalar.as_ref()?)(caixa);
+    if objetivo.inf.is_nan() {
+        Some(NumReal::NEG_INFINITY)
+    } else {
+        Some(objetivo.inf)
+    }
+}
+
+fn dentro(caixa: &Caixa, x: &Ponto) -> bool {
+    (0..DIM).all(|i| caixa[i].contem(x[i]))
+}
+
+// Valor do objetivo em x, quando x é viavel
+pub fn valor_viavel(problema: &Problema, x: Ponto) -> Option<NumReal> {
+    let (desigualdades, igualdades) = problema.avaliar_restricoes(x);
+    let viavel = desigualdades.iter().all(|&g| g <= TOL_VIABILIDADE)
+        && igualdades.iter().all(|&h| h.abs() <= TOL_VIABILIDADE);
+    let valor = problema.avaliar_objetivo(x);
+    if viavel && !valor.is_nan() {
+        Some(valor)
+    } else {
+        None
+    }
+}
+
+// Ponto encontrado pelo SCP partindo de x, quando existe
+fn busca_local(problema: &Problema, configuracao: &Configuracao, x: Ponto) -> Option<Ponto> {
+    let mut p = problema.copia_independente();
+    p.x_inicial = x;
+    p.diferencas = configuracao.diferencas;
+    p.definir_orcamento_avaliacoes(configuracao.orcamento_avaliacoes);
+    resolver(&mut p, configuracao, None)
+        .ok()?
+        .otimo
+        .map(|(ponto, _)| ponto)
+}
+
+// Divide a caixa ao meio na variavel mais larga, relativa à caixa inicial
+fn dividir(caixa: &Caixa, larguras: &Ponto) -> Option<(Caixa, Caixa)> {
+    let relativa = |i: usize| caixa[i].largura() / larguras[i].max(NumReal::MIN_POSITIVE);
+    let i = (0..DIM).max_by(|&a, &b| relativa(a).total_cmp(&relativa(b)))?;
+    if relativa(i) <= LARGURA_MINIMA {
+        return None;
+    }
+
+    let (esquerda, direita) = caixa[i].dividir();
+    let mut primeira = *caixa;
+    let mut segunda = *caixa;
+    primeira[i] = esquerda;
+    segunda[i] = direita;
+    Some((primeira, segunda))
+}
+
+// Caixa das variaveis do problema, onde a busca global é feita: os limites
+// das variaveis onde são finitos, e a caixa d_l..d_u nas variaveis livres
+pub fn caixa_das_variaveis(problema: &Problema) -> Caixa {
+    let (inferior, superior) = problema.caixa_variaveis();
+    let mut caixa = [Intervalo::ponto(0.0); DIM];
+    for i in 0..DIM {
+        caixa[i] = Intervalo::novo(inferior[i], superior[i]);
+    }
+    caixa
+}
+
+// Ramificação espacial sobre a caixa dada das variaveis. As caixas são
+// examinadas pela menor cota inferior do objetivo, dada pelas formas
+// intervalares, e descartadas quando uma restrição não pode ser satisfeita
+// nelas ou quando a cota passa do melhor valor viavel. Esse valor vem dos
+// pontos do meio das caixas e das buscas locais do SCP. A busca para
+// quando a lacuna entre as cotas é aceita, ou no maximo de caixas
+pub fn busca_global(
+    problema: &Problema,
+    raiz: Caixa,
+    configuracao: &Configuracao,
+) -> Result<ResultadoRamificacao, String> {
+    if !problema.tem_formas_intervalares() {
+        return Err(format!(
+            "O problema {} não tem formas intervalares de todas as funções",
+            problema.nome
+        ));
+    }
+    if let Some(i) = (0..DIM).find(|&i| {
+        !(raiz[i].inf.is_finite() && raiz[i].sup.is_finite()) || raiz[i].inf > raiz[i].sup
+    }) {
+        return Err(format!(
+            "A caixa da variavel {} do problema {} não é limitada",
+            i, problema.nome
+        ));
+    }
+
+    let inicio = Instant::now();
+    let problema = problema.copia_independente();
+    let tolerancia = configuracao.tolerancia_global;
+
+    let larguras: Ponto = raiz.map(|intervalo| intervalo.largura());
+
+    let mut incumbente: Option<(Ponto, NumReal)> = None;
+    let mut buscas_locais = 0;
+
+    // Guarda x se é viavel, está na caixa e melhora o incumbente
+    let considerar = |x: Ponto, incumbente: &mut Option<(Ponto, NumReal)>| {
+        if !dentro(&raiz, &x) {
+            return;
+        }
+        if let Some(valor) = valor_viavel(&problema, x) {
+            if incumbente.is_none_or(|(_, melhor)| valor < melhor) {
+                *incumbente = Some((x, valor));
+            }
+        }
+    };
+
+    if let Some(x) = busca_local(&problema, configuracao, problema.x_inicial) {
+        considerar(x, &mut incumbente);
+    }
+    buscas_locais += 1;
+
+    let mut abertas = BinaryHeap::new();
+    let mut indivisiveis: Vec<NoCaixa> = Vec::new();
+    if let Some(cota) = cota_da_caixa(&problema, raiz) {
+        abertas.push(NoCaixa { caixa: raiz, cota });
+    }
+
+    let mut caixas_examinadas = 0;
+    while let Some(no) = abertas.pop() {
+        // Descartada depois de entrar, por um incumbente melhor
+        if let Some((_, melhor)) = incumbente {
+            if no.cota > melhor {
+                continue;
+            }
+        }
+
+        // Como as caixas saem pela menor cota, a cota desta é a cota
+        // inferior de todas as abertas
+        let cota_inferior = indivisiveis
+            .iter()
+            .map(|indivisivel| indivisivel.cota)
+            .fold(no.cota, NumReal::min);
+        let lacuna_fechada = match incumbente {
+            Some((_, melhor)) => {
+                melhor - cota_inferior <= ResultadoRamificacao::lacuna_aceita(tolerancia, melhor)
+            }
+            None => false,
+        };
+        if lacuna_fechada || caixas_examinadas >= configuracao.maximo_caixas {
+            abertas.push(no);
+            break;
+        }
+        caixas_examinadas += 1;
+
+        let meio = no.caixa.map(|intervalo| intervalo.meio());
+        considerar(meio, &mut incumbente);
+
+        if caixas_examinadas % INTERVALO_BUSCA_LOCAL == 0 {
+            if let Some(x) = busca_local(&problema, configuracao, meio) {
+                considerar(x, &mut incumbente);
+            }
+            buscas_locais += 1;
+        }
+
+        match dividir(&no.caixa, &larguras) {
+            Some((primeira, segunda)) => {
+                for caixa in [primeira, segunda] {
+                    if let Some(cota) = cota_da_caixa(&problema, caixa) {
+                        if incumbente.is_none_or(|(_, melhor)| cota <= melhor) {
+                            abertas.push(NoCaixa { caixa, cota });
+                        }
+                    }
+                }
+            }
+            None => indivisiveis.push(no),
+        }
+    }
+
+    // O envoltorio são as caixas que ainda podem ter valores abaixo do
+    // incumbente
+    let mut restantes: Vec<NoCaixa> = abertas.into_sorted_vec();
+    restantes.extend(indivisiveis);
+    restantes.retain(|no| incumbente.is_none_or(|(_, melhor)| no.cota <= melhor));
+
+    let cota_inferior = restantes.iter().map(|no| no.cota).fold(
+        incumbente.map_or(NumReal::INFINITY, |(_, melhor)| melhor),
+        NumReal::min,
+    );
+
+    Ok(ResultadoRamificacao {
+        nome: problema.nome.clone(),
+        caixa: raiz,
+        incumbente,
+        cota_inferior,
+        tolerancia,
+        envoltorio: restantes.into_iter().map(|no| no.caixa).collect(),
+        caixas_examinadas,
+        buscas_locais,
+        tempo: inicio.elapsed(),
+    })
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::construtor_problema::ConstrutorProblema;
+    use std::sync::Arc;
+
+    const TOL_TESTE: NumReal = 1e-6;
+
+    // Poço duplo (x² - 1)² - 1 + y², com o poço em x = -1 cortado por
+    // x + y ≥ 0.5, então o otimo global é (1, 0), com f* = -1. A forma
+    // intervalar usa o quadrado de x² - 1, que dá a imagem exata em cada
+    // caixa, e não x⁴ - 2x², que perde a dependencia entre os termos
+    fn poco_duplo_cortado() -> Problema {
+        ConstrutorProblema::novo("poço duplo")
+            .variavel("x", -2.0, 2.0, -1.0)
+            .variavel("y", -2.0, 2.0, 1.6)
+            .objetivo(
+                "f",
+                Arc::new(|x: Ponto| x[0].powi(4) - 2.0 * x[0].powi(2) + x[1].powi(2)),
+            )
+            .intervalar(
+                "f",
+                Arc::new(|x: Caixa| (x[0].powi(2) - 1.0).powi(2) - 1.0 + x[1].powi(2)),
+            )
+            .desigualdade("corte", Arc::new(|x: Ponto| 0.5 - x[0] - x[1]))
+            .intervalar("corte", Arc::new(|x: Caixa| 0.5 - x[0] - x[1]))
+            .solucao([1.0, 0.0])
+            .construir()
+            .unwrap()
+    }
+
+    #[test]
+    fn busca_global_certifica_o_otimo_conhecido() {
+        let problema = poco_duplo_cortado();
+        let configuracao = Configuracao::padrao();
+        let raiz = caixa_das_variaveis(&problema);
+        let resultado = busca_global(&problema, raiz, &configuracao).unwrap();
+
+        let otimo = [1.0, 0.0];
+        let valor_otimo = -1.0;
+        assert!(resultado.certificado(), "{:?}", resultado);
+        assert!(dentro(&resultado.caixa, &otimo));
+
+        // A cota inferior não passa do otimo, e a lacuna até o incumbente
+        // é a aceita
+        let (x, valor) = resultado.incumbente.unwrap();
+        assert!(resultado.cota_inferior <= valor_otimo + TOL_TESTE);
+        assert!(valor >= valor_otimo - TOL_TESTE);
+        assert!(valor - resultado.cota_inferior <= configuracao.tolerancia_global);
+        assert!(
+            (x[0] - otimo[0]).abs() < 1e-2 && x[1].abs() < 1e-2,
+            "{:?}",
+            x
+        );
+
+        // Alguma caixa do envoltorio ainda contém o otimo
+        assert!(resultado
+            .envoltorio
+            .iter()
+            .any(|caixa| dentro(caixa, &otimo)));
+    }
+}
@@ -0,0 +1,349 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::defs::{Caixa, Configuracao, NumReal, Ponto, Problema, DIM};
+use crate::intervalo::Intervalo;
+use crate::ramificacao_espacial::{cota_da_caixa, valor_viavel};
+use crate::resolucao::resolver;
+
+// Distancia ao inteiro mais proximo abaixo da qual um valor é inteiro
+const TOL_INTEIRO: NumReal = 1e-6;
+
+// Ordem em que os nós abertos são resolvidos
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelecaoNos {
+    MelhorCota,   // O de menor cota, que mais sobe a cota inferior
+    Profundidade, // O ultimo criado, que chega antes a pontos inteiros
+}
+
+impl SelecaoNos {
+    // Nome usado para escolher a seleção
+    pub fn nome(&self) -> &'static str {
+        match self {
+            SelecaoNos::MelhorCota => "melhor-cota",
+            SelecaoNos::Profundidade => "profundidade",
+        }
+    }
+
+    // Encontra a seleção pelo nome
+    pub fn por_nome(nome: &str) -> Option<SelecaoNos> {
+        [SelecaoNos::MelhorCota, SelecaoNos::Profundidade]
+            .iter()
+            .find(|selecao| selecao.nome() == nome)
+            .copied()
+    }
+}
+
+// Nó da arvore: os limites das variaveis, a cota herdada da relaxação do
+// pai e o ponto do pai, de onde a relaxação parte
+#[derive(Debug, Clone)]
+struct No {
+    inferior: Ponto,
+    superior: Ponto,
+    cota: NumReal,
+    x_inicial: Ponto,
+}
+
+// Tira o proximo nó a resolver
+fn escolher(abertos: &mut Vec<No>, selecao: SelecaoNos) -> Option<No> {
+    match selecao {
+        SelecaoNos::Profundidade => abertos.pop(),
+        SelecaoNos::MelhorCota => {
+            // No empate, o mais recente, que é o mais fundo
+            let indice = (0..abertos.len())
+                .rev()
+                .min_by(|&a, &b| abertos[a].cota.total_cmp(&abertos[b].cota))?;
+            Some(abertos.remove(indice))
+        }
+    }
+}
+
+// Variavel inteira de valor mais longe de um inteiro, se alguma não é inteira
+fn variavel_fracionaria(problema: &Problema, x: &Ponto) -> Option<usize> {
+    (0..DIM)
+        .filter(|&i| problema.variaveis_inteiras[i])
+        .map(|i| (i, (x[i] - x[i].round()).abs()))
+        .filter(|&(_, distancia)| distancia > TOL_INTEIRO)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+// Cota do objetivo dentro dos limites do nó dada pelas formas
+// intervalares, ou None quando elas provam que nenhum ponto do nó satisfaz
+// as restrições. Sem elas, nada é provado e a cota é -∞
+fn cota_intervalar_do_no(problema: &Problema, no: &No) -> Option<NumReal> {
+    if !problema.tem_formas_intervalares() {
+        return Some(NumReal::NEG_INFINITY);
+    }
+    let caixa: Caixa = std::array::from_fn(|i| {
+        Intervalo::novo(
+            no.inferior[i].max(problema.limites_inferiores[i]),
+            no.superior[i].min(problema.limites_superiores[i]),
+        )
+    });
+    if caixa.iter().any(|intervalo| intervalo.inf > intervalo.sup) {
+        return None;
+    }
+    cota_da_caixa(problema, caixa)
+}
+
+// Resultado da ramificação inteira de um problema
+#[derive(Debug, Clone)]
+pub struct ResultadoInteiro {
+    pub nome: String,
+    pub selecao: SelecaoNos,
+
+    // Melhor ponto viavel com as variaveis inteiras inteiras, e a menor
+    // cota dos nós que ainda podem ter pontos melhores
+    pub incumbente: Option<(Ponto, NumReal)>,
+    pub cota_inferior: NumReal,
+
+    pub nos: usize,
+    pub nos_abertos: usize,
+
+    // Nós cuja relaxação o SCP não resolveu, ou parou em um ponto
+    // inviavel, e nós de relaxação inteira cujo ponto arredondado é
+    // inviavel. Ficam entre os abertos, com a cota do pai, a intervalar ou
+    // a da relaxação, já que não se sabe se têm pontos inteiros viaveis
+    pub nos_sem_relaxacao: usize,
+    pub tempo: Duration,
+}
+
+impl ResultadoInteiro {
+    // Diferença entre o incumbente e a cota inferior
+    pub fn lacuna(&self) -> Option<NumReal> {
+        self.incumbente.map(|(_, valor)| valor - self.cota_inferior)
+    }
+
+    pub fn mostrar(&self) {
+        println!(
+            "Ramificação inteira {}: {} nós, {} abertos, seleção {}, {:.3}s",
+            self.nome,
+            self.nos,
+            self.nos_abertos,
+            self.selecao.nome(),
+            self.tempo.as_secs_f64()
+        );
+        if self.nos_sem_relaxacao > 0 {
+            println!(
+                "{} nós sem relaxação resolvida ou com o ponto arredondado inviavel, ainda abertos",
+                self.nos_sem_relaxacao
+            );
+        }
+
+        match (self.incumbente, self.lacuna()) {
+            (Some((x, valor)), Some(lacuna)) => {
+                println!("x* = {:?}", x);
+                println!("f(x*) = {:?}", valor);
+                println!(
+                    "cota inferior = {:?}, lacuna = {:e} ({:.4}%)",
+                    self.cota_inferior,
+                    lacuna,
+                    100.0 * lacuna / valor.abs().max(1.0)
+                );
+            }
+            _ if self.nos_abertos == 0 => println!("Nenhum ponto inteiro viavel"),
+            _ => {
+                println!("Nenhum ponto inteiro viavel encontrado");
+                println!("f* ≥ {:?}", self.cota_inferior);
+            }
+        }
+    }
+}
+
+// Ramificação sobre as variaveis inteiras. Cada nó resolve pelo SCP a
+// relaxação continua com os limites das variaveis do nó, e o valor da
+// relaxação é a cota dos filhos, criados dividindo a variavel inteira
+// mais fracionaria em xᵢ ≤ ⌊vᵢ⌋ e xᵢ ≥ ⌊vᵢ⌋ + 1. Nos problemas convexos a
+// cota é valida; nos outros, o SCP pode parar em um minimo local e a
+// ramificação não garante o otimo. Os nós sem relaxação resolvida seguem
+// com a cota do pai, ou a intervalar do nó quando é maior, na cota
+// inferior, e os de relaxação inteira cujo ponto
+// arredondado é inviavel, com a cota da relaxação. A busca para quando a
+// lacuna até a cota inferior é aceita, ou no maximo de nós
+pub fn ramificacao_inteira(problema: &Problema, configuracao: &Configuracao) -> ResultadoInteiro {
+    let inicio = Instant::now();
+    let lacuna_aceita = |valor: NumReal| configuracao.tolerancia_global * valor.abs().max(1.0);
+
+    let mut abertos = vec![No {
+        inferior: [NumReal::NEG_INFINITY; DIM],
+        superior: [NumReal::INFINITY; DIM],
+        cota: NumReal::NEG_INFINITY,
+        x_inicial: problema.x_inicial,
+    }];
+    let mut incumbente: Option<(Ponto, NumReal)> = None;
+    let mut sem_relaxacao: Vec<No> = Vec::new();
+    let mut nos = 0;
+
+    while let Some(no) = escolher(&mut abertos, configuracao.selecao_nos) {
+        // Descartado por um incumbente encontrado depois que entrou
+        if let Some((_, melhor)) = incumbente {
+            if no.cota >= melhor {
+                continue;
+            }
+        }
+
+        // A cota inferior é a menor entre as dos nós abertos, contando os
+        // que ficaram sem relaxação
+        let cota_inferior = abertos
+            .iter()
+            .chain(&sem_relaxacao)
+            .map(|aberto| aberto.cota)
+            .fold(no.cota, NumReal::min);
+        let lacuna_fechada = match incumbente {
+            Some((_, melhor)) => melhor - cota_inferior <= lacuna_aceita(melhor),
+            None => false,
+        };
+        if lacuna_fechada || nos >= configuracao.maximo_nos {
+            abertos.push(no);
+            break;
+        }
+        nos += 1;
+
+        // Relaxação do nó, partindo do ponto do pai trazido para os limites
+        let mut p = problema.com_limites_variaveis(&no.inferior, &no.superior);
+        for i in 0..DIM {
+            p.x_inicial[i] = no.x_inicial[i].max(no.inferior[i]).min(no.superior[i]);
+        }
+        p.diferencas = configuracao.diferencas;
+        p.definir_orcamento_avaliacoes(configuracao.orcamento_avaliacoes);
+        let x = resolver(&mut p, configuracao, None)
+            .ok()
+            .and_then(|resolucao| resolucao.otimo)
+            .map(|(x, _)| x);
+
+        // Sem ponto viavel, o nó só é descartado quando as formas
+        // intervalares provam que é inviavel. O SCP pode falhar ou parar
+        // em um ponto inviavel de um nó viavel, que então fica com a cota
+        // do pai, ou com a intervalar do nó, se for maior
+        let (x, valor) = match x.and_then(|x| Some((x, valor_viavel(&p, x)?))) {
+            Some(viavel) => viavel,
+            None => {
+                if let Some(cota) = cota_intervalar_do_no(problema, &no) {
+                    sem_relaxacao.push(No {
+                        cota: no.cota.max(cota),
+                        ..no
+                    });
+                }
+                continue;
+            }
+        };
+        if let Some((_, melhor)) = incumbente {
+            if valor >= melhor {
+                continue;
+            }
+        }
+
+        match variavel_fracionaria(problema, &x) {
+            // Ponto inteiro, que vira incumbente depois de arredondado
+            None => {
+                let mut arredondado = x;
+                for i in 0..DIM {
+                    if problema.variaveis_inteiras[i] {
+                        arredondado[i] = x[i].round();
+                    }
+                }
+                match valor_viavel(problema, arredondado) {
+                    Some(valor_arredondado) => {
+                        if incumbente.is_none_or(|(_, melhor)| valor_arredondado < melhor) {
+                            incumbente = Some((arredondado, valor_arredondado));
+                        }
+                    }
+                    // O arredondamento saiu da região viavel, o que não prova
+                    // que o nó não tem pontos inteiros viaveis, então ele fica
+                    // aberto com a cota da relaxação
+                    None => sem_relaxacao.push(No {
+                        cota: valor,
+                        x_inicial: x,
+                        ..no
+                    }),
+                }
+            }
+            Some(i) => {
+                let piso = x[i].floor();
+                let mut abaixo = No {
+                    inferior: no.inferior,
+                    superior: no.superior,
+                    cota: valor,
+                    x_inicial: x,
+                };
+                let mut acima = abaixo.clone();
+                abaixo.superior[i] = piso;
+                acima.inferior[i] = piso + 1.0;
+
+                // O lado do arredondamento de xᵢ entra por ultimo, para ser
+                // o primeiro na busca em profundidade
+                if x[i] - piso < 0.5 {
+                    abertos.push(acima);
+                    abertos.push(abaixo);
+                } else {
+                    abertos.push(abaixo);
+                    abertos.push(acima);
+                }
+            }
+        }
+    }
+
+    abertos.retain(|no| incumbente.is_none_or(|(_, melhor)| no.cota < melhor));
+    sem_relaxacao.retain(|no| incumbente.is_none_or(|(_, melhor)| no.cota < melhor));
+    let nos_sem_relaxacao = sem_relaxacao.len();
+    abertos.extend(sem_relaxacao);
+    let cota_inferior = abertos.iter().map(|no| no.cota).fold(
+        incumbente.map_or(NumReal::INFINITY, |(_, melhor)| melhor),
+        NumReal::min,
+    );
+
+    ResultadoInteiro {
+        nome: problema.nome.clone(),
+        selecao: configuracao.selecao_nos,
+        incumbente,
+        cota_inferior,
+        nos,
+        nos_abertos: abertos.len(),
+        nos_sem_relaxacao,
+        tempo: inicio.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use crate::instancias::todas_as_instancias;
+
+    const TOL_TESTE: NumReal = 1e-4;
+
+    // O disco tem otimo inteiro (2, 1.4), longe da relaxação (2.4, 1.4), e
+    // as duas seleções de nós terminam nele sem nós abertos, com o nó
+    // x ≥ 3 descartado pela cota intervalar
+    #[test]
+    fn disco_inteiro_chega_ao_otimo_nas_duas_selecoes() {
+        let problema = todas_as_instancias()
+            .into_iter()
+            .find(|problema| problema.nome == "Inteiro disco")
+            .unwrap();
+
+        for selecao_nos in [SelecaoNos::MelhorCota, SelecaoNos::Profundidade] {
+            let configuracao = Configuracao {
+                selecao_nos,
+                ..Configuracao::padrao()
+            };
+            let resultado = ramificacao_inteira(&problema, &configuracao);
+
+            let (x, valor) = resultado.incumbente.unwrap();
+            assert_eq!(x[0], 2.0, "{:?}", resultado);
+            assert!((x[1] - 1.4).abs() < TOL_TESTE, "{:?}", resultado);
+            assert!((valor - 0.16).abs() < TOL_TESTE, "{:?}", resultado);
+
+            let lacuna = resultado.lacuna().unwrap();
+            assert!(
+                lacuna <= configuracao.tolerancia_global * valor.abs().max(1.0),
+                "{:?}",
+                resultado
+            );
+            assert_eq!(resultado.nos_abertos, 0, "{:?}", resultado);
+            assert_eq!(resultado.nos_sem_relaxacao, 0, "{:?}", resultado);
+        }
+    }
+}
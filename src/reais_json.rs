@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::convert::TryInto;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::defs::{NumReal, DIM};
+
+// O JSON não tem NaN nem infinitos, que o serde_json grava como null e
+// depois não consegue ler de volta. Os campos marcados com
+// #[serde(with = "crate::reais_json")] gravam os reais finitos como
+// numeros, e os outros como os textos "NaN", "inf" e "-inf", para que
+// um ponto de controle volte exatamente como foi gravado
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RealJson {
+    Numero(NumReal),
+    Texto(String),
+}
+
+// Tipos feitos de reais, com a forma em que são gravados
+pub trait Reais: Sized {
+    type Gravado: Serialize + DeserializeOwned;
+
+    fn gravado(&self) -> Self::Gravado;
+    fn lido(gravado: Self::Gravado) -> Result<Self, String>;
+}
+
+impl Reais for NumReal {
+    type Gravado = RealJson;
+
+    fn gravado(&self) -> RealJson {
+        if self.is_finite() {
+            RealJson::Numero(*self)
+        } else {
+            RealJson::Texto(self.to_string())
+        }
+    }
+
+    fn lido(gravado: RealJson) -> Result<Self, String> {
+        match gravado {
+            RealJson::Numero(valor) => Ok(valor),
+            RealJson::Texto(texto) => texto
+                .parse()
+                .map_err(|_| format!("real invalido: {}", texto)),
+        }
+    }
+}
+
+impl<T: Reais> Reais for Option<T> {
+    type Gravado = Option<T::Gravado>;
+
+    fn gravado(&self) -> Self::Gravado {
+        self.as_ref().map(T::gravado)
+    }
+
+    fn lido(gravado: Self::Gravado) -> Result<Self, String> {
+        gravado.map(T::lido).transpose()
+    }
+}
+
+impl<T: Reais> Reais for Vec<T> {
+    type Gravado = Vec<T::Gravado>;
+
+    fn gravado(&self) -> Self::Gravado {
+        self.iter().map(T::gravado).collect()
+    }
+
+    fn lido(gravado: Self::Gravado) -> Result<Self, String> {
+        gravado.into_iter().map(T::lido).collect()
+    }
+}
+
+impl<T: Reais> Reais for VecDeque<T> {
+    type Gravado = Vec<T::Gravado>;
+
+    fn gravado(&self) -> Self::Gravado {
+        self.iter().map(T::gravado).collect()
+    }
+
+    fn lido(gravado: Self::Gravado) -> Result<Self, String> {
+        gravado.into_iter().map(T::lido).collect()
+    }
+}
+
+impl<T: Reais> Reais for [T; DIM] {
+    type Gravado = [T::Gravado; DIM];
+
+    fn gravado(&self) -> Self::Gravado {
+        std::array::from_fn(|i| self[i].gravado())
+    }
+
+    fn lido(gravado: Self::Gravado) -> Result<Self, String> {
+        let valores = IntoIterator::into_iter(gravado)
+            .map(T::lido)
+            .collect::<Result<Vec<T>, String>>()?;
+        valores
+            .try_into()
+            .map_err(|_| "tamanho invalido".to_string())
+    }
+}
+
+impl<A: Reais, B: Reais> Reais for (A, B) {
+    type Gravado = (A::Gravado, B::Gravado);
+
+    fn gravado(&self) -> Self::Gravado {
+        (self.0.gravado(), self.1.gravado())
+    }
+
+    fn lido(gravado: Self::Gravado) -> Result<Self, String> {
+        Ok((A::lido(gravado.0)?, B::lido(gravado.1)?))
+    }
+}
+
+pub fn serialize<T: Reais, S: Serializer>(valor: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    valor.gravado().serialize(serializer)
+}
+
+pub fn deserialize<'de, T: Reais, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    T::lido(T::Gravado::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use crate::algebra_linear::Matriz;
+use crate::defs::*;
+
+// Menor autovalor aceito depois da regularização por autovalores,
+// relativo ao maior autovalor em modulo
+const TOL_AUTOVALOR_MINIMO: NumReal = 1e-8;
+
+// Menor multiplo da identidade tentado na regularização τI
+// (Nocedal e Wright, 2006, Numerical Optimization, pg 51)
+const BETA_IDENTIDADE: NumReal = 1e-3;
+
+// Forma de tornar a hessiana definida positiva antes da restrição 1c, que
+// a usa como produto interno para gerar direções conjugadas
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RegularizacaoHessiana {
+    #[serde(rename = "nenhuma")]
+    Nenhuma, // Usa a hessiana como está
+    #[serde(rename = "cholesky")]
+    CholeskyModificado, // Cholesky modificado de Gill e Murray
+    #[serde(rename = "inverter")]
+    InverterAutovalores, // Troca cada autovalor λ por max(|λ|, δ)
+    #[serde(rename = "cortar")]
+    CortarAutovalores, // Troca cada autovalor λ por max(λ, δ)
+    #[serde(rename = "identidade")]
+    MultiploIdentidade, // Soma τI, com τ adaptado entre as iterações
+}
+
+impl RegularizacaoHessiana {
+    // Nome usado para escolher a regularização
+    pub fn nome(&self) -> &'static str {
+        match self {
+            RegularizacaoHessiana::Nenhuma => "nenhuma",
+            RegularizacaoHessiana::CholeskyModificado => "cholesky",
+            RegularizacaoHessiana::InverterAutovalores => "inverter",
+            RegularizacaoHessiana::CortarAutovalores => "cortar",
+            RegularizacaoHessiana::MultiploIdentidade => "identidade",
+        }
+    }
+
+    // Encontra a regularização pelo nome
+    pub fn por_nome(nome: &str) -> Option<RegularizacaoHessiana> {
+        [
+            RegularizacaoHessiana::Nenhuma,
+            RegularizacaoHessiana::CholeskyModificado,
+            RegularizacaoHessiana::InverterAutovalores,
+            RegularizacaoHessiana::CortarAutovalores,
+            RegularizacaoHessiana::MultiploIdentidade,
+        ]
+        .iter()
+        .find(|regularizacao| regularizacao.nome() == nome)
+        .copied()
+    }
+}
+
+// Regulariza a hessiana da forma escolhida, retornando a matriz definida
+// positiva e o maior deslocamento aplicado a um autovalor ou elemento da
+// diagonal, que é nulo quando a hessiana já era definida positiva.
+// tau guarda o multiplo da identidade usado na ultima chamada, de onde a
+// regularização τI começa a busca
+pub fn regularizar_hessiana(
+    hessiana: &[Vec<NumReal>],
+    regularizacao: RegularizacaoHessiana,
+    tau: &mut NumReal,
+) -> (Vec<Vec<NumReal>>, NumReal) {
+    let n = hessiana.len();
+    let matriz = Matriz::de_linhas(hessiana, n);
+
+    match regularizacao {
+        RegularizacaoHessiana::Nenhuma => (hessiana.to_vec(), 0.0),
+        RegularizacaoHessiana::CholeskyModificado => {
+            // A + E = LDLᵀ, com E diagonal
+            let cholesky = matriz.cholesky_modificada();
+            let deslocamento = cholesky.e.iter().fold(0.0, |acc: NumReal, &e| acc.max(e));
+            (cholesky.matriz_modificada().para_linhas(), deslocamento)
+        }
+        RegularizacaoHessiana::InverterAutovalores => {
+            modificar_autovalores(&matriz, |autovalor, delta| autovalor.abs().max(delta))
+        }
+        RegularizacaoHessiana::CortarAutovalores => {
+            modificar_autovalores(&matriz, |autovalor, delta| autovalor.max(delta))
+        }
+        RegularizacaoHessiana::MultiploIdentidade => {
+            let deslocamento = multiplo_identidade(&matriz, tau);
+            let mut regularizada = hessiana.to_vec();
+            for (i, linha) in regularizada.iter_mut().enumerate() {
+                linha[i] += deslocamento;
+            }
+            (regularizada, deslocamento)
+        }
+    }
+}
+
+// Recompõe A = VΛVᵀ depois de trocar cada autovalor λ por modificar(λ, δ)
+fn modificar_autovalores(
+    matriz: &Matriz,
+    modificar: impl Fn(NumReal, NumReal) -> NumReal,
+) -> (Vec<Vec<NumReal>>, NumReal) {
+    let n = matriz.n_linhas;
+    let (autovalores, autovetores) = matriz.autodecomposicao_simetrica();
+
+    let escala = autovalores
+        .iter()
+        .fold(1.0, |acc: NumReal, autovalor| acc.max(autovalor.abs()));
+    let delta = TOL_AUTOVALOR_MINIMO * escala;
+
+    // VΛ', multiplicando cada coluna de V pelo seu novo autovalor
+    let mut deslocamento: NumReal = 0.0;
+    let mut v_lambda = autovetores.clone();
+    for (j, &autovalor) in autovalores.iter().enumerate() {
+        let novo = modificar(autovalor, delta);
+        deslocamento = deslocamento.max(novo - autovalor);
+        for i in 0..n {
+            v_lambda[(i, j)] *= novo;
+        }
+    }
+
+    (
+        v_lambda.produto(&autovetores.transposta()).para_linhas(),
+        deslocamento,
+    )
+}
+
+// Encontra τ ≥ 0 tal que A + τI tem decomposição de Cholesky, dobrando τ
+// até que ela exista. Se a diagonal de A é positiva, começa por τ = 0, do
+// contrario começa pelo necessario para tornar a diagonal positiva, ou
+// pela metade do τ da chamada anterior, se for maior
+// (Nocedal e Wright, 2006, Numerical Optimization, pg 51)
+fn multiplo_identidade(matriz: &Matriz, tau_anterior: &mut NumReal) -> NumReal {
+    let n = matriz.n_linhas;
+    let menor_diagonal = (0..n).fold(NumReal::INFINITY, |acc, i| acc.min(matriz[(i, i)]));
+
+    let mut tau = if menor_diagonal > 0.0 {
+        0.0
+    } else {
+        (BETA_IDENTIDADE - menor_diagonal).max(*tau_anterior / 2.0)
+    };
+
+    loop {
+        let mut deslocada = matriz.clone();
+        for i in 0..n {
+            deslocada[(i, i)] += tau;
+        }
+        if deslocada.cholesky().is_some() {
+            break;
+        }
+        tau = (2.0 * tau).max(BETA_IDENTIDADE);
+    }
+
+    *tau_anterior = tau;
+    tau
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    const TOL_TESTE: NumReal = 1e-9;
+
+    const REGULARIZACOES: [RegularizacaoHessiana; 4] = [
+        RegularizacaoHessiana::CholeskyModificado,
+        RegularizacaoHessiana::InverterAutovalores,
+        RegularizacaoHessiana::CortarAutovalores,
+        RegularizacaoHessiana::MultiploIdentidade,
+    ];
+
+    // Autovalores -1 e 3
+    fn indefinida() -> Vec<Vec<NumReal>> {
+        vec![vec![1.0, 2.0], vec![2.0, 1.0]]
+    }
+
+    fn autovalores(matriz: &[Vec<NumReal>]) -> Vec<NumReal> {
+        Matriz::de_linhas(matriz, matriz.len())
+            .autodecomposicao_simetrica()
+            .0
+    }
+
+    fn proximas(a: &[Vec<NumReal>], b: &[Vec<NumReal>]) -> bool {
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .all(|(x, y)| (x - y).abs() <= TOL_TESTE * (1.0 + x.abs()))
+    }
+
+    #[test]
+    fn regularizadas_sao_definidas_positivas_com_o_deslocamento() {
+        for regularizacao in REGULARIZACOES {
+            let mut tau = 0.0;
+            let (regularizada, deslocamento) =
+                regularizar_hessiana(&indefinida(), regularizacao, &mut tau);
+
+            assert!(
+                Matriz::de_linhas(&regularizada, 2).cholesky().is_some(),
+                "{}",
+                regularizacao.nome()
+            );
+            assert!(
+                (regularizada[0][1] - regularizada[1][0]).abs() <= TOL_TESTE,
+                "{}",
+                regularizacao.nome()
+            );
+
+            let menor = autovalores(&regularizada)[0];
+            match regularizacao {
+                // A + E, com o maior elemento de E como deslocamento
+                RegularizacaoHessiana::CholeskyModificado => {
+                    let e = [regularizada[0][0] - 1.0, regularizada[1][1] - 1.0];
+                    assert!(e.iter().all(|&ei| ei >= 0.0));
+                    assert!((deslocamento - e[0].max(e[1])).abs() <= TOL_TESTE);
+                    assert!((regularizada[0][1] - 2.0).abs() <= TOL_TESTE);
+                }
+                // -1 vira 1
+                RegularizacaoHessiana::InverterAutovalores => {
+                    assert!((menor - 1.0).abs() <= TOL_TESTE);
+                    assert!((deslocamento - 2.0).abs() <= TOL_TESTE);
+                }
+                // -1 vira δ
+                RegularizacaoHessiana::CortarAutovalores => {
+                    let delta = TOL_AUTOVALOR_MINIMO * 3.0;
+                    assert!((menor - delta).abs() <= TOL_TESTE);
+                    assert!((deslocamento - 1.0 - delta).abs() <= TOL_TESTE);
+                }
+                // A + τI, com τ guardado para a proxima chamada
+                RegularizacaoHessiana::MultiploIdentidade => {
+                    assert!(deslocamento > 1.0);
+                    assert_eq!(tau, deslocamento);
+                    let mut esperada = indefinida();
+                    esperada[0][0] += deslocamento;
+                    esperada[1][1] += deslocamento;
+                    assert_eq!(regularizada, esperada);
+                }
+                RegularizacaoHessiana::Nenhuma => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn definida_positiva_fica_como_esta() {
+        let definida_positiva = vec![vec![2.0, 0.5], vec![0.5, 1.0]];
+        for regularizacao in REGULARIZACOES {
+            let mut tau = 0.0;
+            let (regularizada, deslocamento) =
+                regularizar_hessiana(&definida_positiva, regularizacao, &mut tau);
+            assert_eq!(deslocamento, 0.0, "{}", regularizacao.nome());
+            assert!(
+                proximas(&regularizada, &definida_positiva),
+                "{}",
+                regularizacao.nome()
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::time::Instant;
+
+use crate::{
+    backend_linear::EstadoProblemaLinear,
+    defs::{Configuracao, MultiplicadoresDeLagrange, NumReal, Ponto, Problema, DIM},
+    estimativa_mul_lagrange::{
+        estimar_multiplicadores_minimos_quadrados, EstimadorMultiplicadores,
+    },
+    funcao_merito::verificacao_funcao_merito,
+    historico::{Historico, RegistroIteracao},
+    iter_linear::iteracoes_lineares,
+    ponto_de_controle::{EstadoIteracoes, PontoDeControle},
+    ponto_estacionario::checar_ponto_estacionario,
+    quase_newton::AproximacaoHessiana,
+    regiao_de_confianca::verificar_regiao_de_confianca,
+    resultado::MotivoParada,
+    utils::_iguais,
+};
+
+// Resultado das iterações não lineares de um problema
+#[derive(Debug, Clone)]
+pub struct Resolucao {
+    // Possivel otimo, junto da ultima estimativa dos multiplicadores
+    pub otimo: Option<(Ponto, MultiplicadoresDeLagrange)>,
+    pub parada: MotivoParada,
+    pub iteracoes: usize,
+    pub historico: Historico,
+}
+
+// Resolve o problema pelas iterações não lineares, a partir do ponto
+// inicial ou do estado retomado de um ponto de controle. A região de
+// confiança do problema é atualizada ao longo das iterações. Um
+// subproblema linear sem solução interrompe a resolução, com o estado
+// retornado pelo backend
+pub fn resolver(
+    p: &mut Problema,
+    configuracao: &Configuracao,
+    estado_inicial: Option<EstadoIteracoes>,
+) -> Result<Resolucao, EstadoProblemaLinear> {
+    let inicio = Instant::now();
+
+    // Lista de pontos em cada iteção não linear
+    let mut passos_tomados: Vec<Vec<NumReal>> = Vec::new();
+
+    // Historico das iterações não lineares
+    let mut historico = Historico::novo();
+
+    // Contador de iterações não lineares
+    let mut k = 1;
+
+    // Variavel de armazenamento do ponto corrente
+    let mut x = p.x_inicial;
+
+    // Estado de cada iteração não linear
+
+    // Ponto que possivelmente é melhor que o atual
+    let mut x_novo;
+
+    // Direção tomada para encontrar o ponto x_novo e tamanho do passo na direção
+    let mut d;
+    let mut alpha;
+
+    // Armazenamento do pedido de parada nas subiterções lineares
+    let mut verificacao_ponto_estacionario;
+
+    // Armazenamento para os multiplicadores_de e matriz aproximada da função lagrangiana
+    // Os métodos quase Newton fazem aproximações iteradas à hessiana, começando
+    // pela identidade, como é o recomendado
+    let mut multiplicadores_de_lagrange;
+    let mut hessiana_lagrangiana = AproximacaoHessiana::nova(configuracao);
+
+    // Armazenamento do possivel otimo, junto da ultima estimativa dos multiplicadores
+    let mut otimo: Option<(Ponto, MultiplicadoresDeLagrange)> = None;
+    let mut parada = MotivoParada::LimiteIteracoes;
+
+    // Retoma do estado de um ponto de controle, se houver
+    if let Some(estado) = estado_inicial {
+        k = estado.k;
+        x = estado.x;
+        p.atualizar_regiao_de_confianca(estado.d_l, estado.d_u);
+        hessiana_lagrangiana = estado.hessiana_lagrangiana;
+        passos_tomados = estado.passos_tomados;
+        historico = estado.historico;
+        p.restaurar_avaliacoes(estado.avaliacoes);
+    }
+
+    // Limite de 100 iterações não lineares
+    while k < 100 {
+        // Calcula e extrai as informações das subiterações lineares
+        let resultado_iteracoes_lineares =
+            iteracoes_lineares(p, x, hessiana_lagrangiana.clone(), k, configuracao)?;
+
+        x_novo = resultado_iteracoes_lineares.0;
+        d = resultado_iteracoes_lineares.1;
+        multiplicadores_de_lagrange = resultado_iteracoes_lineares.5;
+        hessiana_lagrangiana = resultado_iteracoes_lineares.6;
+        verificacao_ponto_estacionario = resultado_iteracoes_lineares.7;
+        alpha = resultado_iteracoes_lineares.8;
+
+        historico.registrar(RegistroIteracao {
+            k,
+            x: x_novo,
+            valor_objetivo: p.objetivo_em(x_novo),
+            alpha,
+            deslocamento_hessiana: resultado_iteracoes_lineares.9,
+        });
+
+        // Se foi encontrado um ponto kkt estacionario nas iterações lineares
+        if verificacao_ponto_estacionario {
+            otimo = Some((x_novo, multiplicadores_de_lagrange.clone()));
+            parada = MotivoParada::PontoEstacionarioLinear;
+            break;
+        }
+
+        // Verifica se o proximo ponto reduz suficientemente a função de mérido
+        // Caso não reduza, um ponto diferente é retornado
+        // Caso reduza, retorna o mesmo ponto entregue para a verificação
+        x_novo = verificacao_funcao_merito(p, x_novo, x, alpha, d, &multiplicadores_de_lagrange);
+
+        // Comparando o movimento do ponto observado entre iteraões e
+        // atualizando as regiões de confiança para a busca das direções
+        // de acordo com esse movimento
+        let (d_l, d_u) = verificar_regiao_de_confianca(p, &x_novo, &x);
+        p.atualizar_regiao_de_confianca(d_l, d_u);
+
+        // Por minimos quadrados os multiplicadores não dependem do
+        // subproblema linear, e podem ser estimados no novo ponto
+        if configuracao.estimador_multiplicadores == EstimadorMultiplicadores::MinimosQuadrados {
+            multiplicadores_de_lagrange = estimar_multiplicadores_minimos_quadrados(p, x_novo);
+        }
+
+        // Verifica se o novo ponto encontrado é um kkt estacionario
+        if checar_ponto_estacionario(
+            p,
+            &x_novo,
+            &multiplicadores_de_lagrange,
+            &configuracao.tolerancias_kkt,
+        ) {
+            otimo = Some((x_novo, multiplicadores_de_lagrange.clone()));
+            parada = MotivoParada::PontoEstacionario;
+            break;
+        }
+
+        // Para quando o tempo limite passa, com o ultimo ponto
+        if let Some(tempo_limite) = configuracao.tempo_limite {
+            if inicio.elapsed().as_secs_f64() >= tempo_limite {
+                otimo = Some((x_novo, multiplicadores_de_lagrange.clone()));
+                parada = MotivoParada::TempoEsgotado;
+                break;
+            }
+        }
+
+        // Para quando o orçamento de avaliações acaba, com o ultimo ponto
+        if p.orcamento_esgotado() {
+            otimo = Some((x_novo, multiplicadores_de_lagrange.clone()));
+            parada = MotivoParada::OrcamentoEsgotado;
+            break;
+        }
+
+        // Verifica se passou-se duas iterações não lineares e o ponto não se moveu
+        // Para o algoritmo, mesmo que não seja um ponto kkt estacionario
+        if k > 2 {
+            let x_teste = Vec::from(x_novo);
+            let x_ant = passos_tomados[k - 2].clone().clone();
+            let x_ant2 = passos_tomados[k - 3].clone().clone();
+
+            // Verifica se o proximo, o atual e o anterior são iguais
+            if _iguais(&x_teste, &x_ant, DIM) && _iguais(&x_teste, &x_ant2, DIM) {
+                otimo = Some((x_novo, multiplicadores_de_lagrange.clone()));
+                parada = MotivoParada::PassosRepetidos;
+                break;
+            }
+        }
+
+        if false {
+            println!("vvvvvvvvvvvvv");
+            println!("Iterção NLP");
+            println!("x =      {:?}", x);
+            println!("f(x) =   {:?}", p.objetivo_em(x));
+            println!("d =      {:?}", d);
+            println!("x_novo = {:?}", x_novo);
+            println!("^^^^^^^^^^^^^");
+        }
+
+        // Atualiza o ponto
+        x = x_novo;
+
+        // Armazena esse novo
+        passos_tomados.push(Vec::from(x));
+
+        // Proxima iteração
+        k += 1;
+
+        // Grava o estado do inicio da proxima iteração
+        if let Some(diretorio) = &configuracao.diretorio_ponto_de_controle {
+            if (k - 1) % configuracao.intervalo_ponto_de_controle == 0 {
+                let estado = EstadoIteracoes {
+                    k,
+                    x,
+                    d_l: p.d_l,
+                    d_u: p.d_u,
+                    hessiana_lagrangiana: hessiana_lagrangiana.clone(),
+                    multiplicadores_de_lagrange: Some(multiplicadores_de_lagrange.clone()),
+                    passos_tomados: passos_tomados.clone(),
+                    historico: historico.clone(),
+                    avaliacoes: p.estado_avaliacoes(),
+                };
+                if let Err(erro) =
+                    PontoDeControle::novo(&p.nome, configuracao, estado).gravar(diretorio)
+                {
+                    println!("Falha ao gravar o ponto de controle ... {}", erro);
+                }
+            }
+        }
+    }
+
+    Ok(Resolucao {
+        otimo,
+        parada,
+        iteracoes: k,
+        historico,
+    })
+}
@@ -0,0 +1,234 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::avaliacoes::ContadoresAvaliacao;
+use crate::defs::{Configuracao, MultiplicadoresDeLagrange, NumReal, Ponto, Problema, DIM};
+use crate::historico::Historico;
+use crate::ponto_estacionario::{estados_restricoes, residuo_kkt, EstadoRestricao, ResiduoKkt};
+
+// Versão do esquema dos resultados gravados. Muda quando um campo é
+// removido, renomeado ou muda de significado; campos novos não a mudam
+pub const VERSAO_ESQUEMA: u32 = 1;
+
+// Formato dos arquivos de resultados
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatoResultados {
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl FormatoResultados {
+    // Todos os formatos compilados
+    pub fn disponiveis() -> Vec<FormatoResultados> {
+        vec![
+            FormatoResultados::Json,
+            #[cfg(feature = "toml")]
+            FormatoResultados::Toml,
+        ]
+    }
+
+    // Nome usado para escolher o formato, que é também a extensão dos arquivos
+    pub fn nome(&self) -> &'static str {
+        match self {
+            FormatoResultados::Json => "json",
+            #[cfg(feature = "toml")]
+            FormatoResultados::Toml => "toml",
+        }
+    }
+
+    // Encontra o formato pelo nome, entre os compilados
+    pub fn por_nome(nome: &str) -> Option<FormatoResultados> {
+        FormatoResultados::disponiveis()
+            .into_iter()
+            .find(|formato| formato.nome() == nome)
+    }
+}
+
+// Motivo de parada das iterações não lineares
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MotivoParada {
+    PontoEstacionarioLinear, // Ponto KKT encontrado nas subiterações lineares
+    PontoEstacionario,       // Ponto KKT encontrado na iteração não linear
+    OrcamentoEsgotado,       // Orçamento de avaliações esgotado
+    PassosRepetidos,         // O ponto não se moveu por duas iterações
+    TempoEsgotado,           // Tempo limite da instancia atingido
+    LimiteIteracoes,         // Limite de iterações não lineares atingido
+}
+
+impl MotivoParada {
+    // O mesmo nome usado na serialização
+    pub fn nome(&self) -> &'static str {
+        match self {
+            MotivoParada::PontoEstacionarioLinear => "ponto-estacionario-linear",
+            MotivoParada::PontoEstacionario => "ponto-estacionario",
+            MotivoParada::OrcamentoEsgotado => "orcamento-esgotado",
+            MotivoParada::PassosRepetidos => "passos-repetidos",
+            MotivoParada::TempoEsgotado => "tempo-esgotado",
+            MotivoParada::LimiteIteracoes => "limite-iteracoes",
+        }
+    }
+
+    // Mensagem mostrada ao fim das iterações
+    pub fn mensagem(&self) -> Option<&'static str> {
+        match self {
+            MotivoParada::PontoEstacionarioLinear => Some("Parada subiteração linear"),
+            MotivoParada::PontoEstacionario => Some("Parada iterção não linear"),
+            MotivoParada::OrcamentoEsgotado => Some("Parada por orçamento de avaliações esgotado"),
+            MotivoParada::PassosRepetidos => Some("Parada por passos repetidos--"),
+            MotivoParada::TempoEsgotado => Some("Parada por tempo esgotado"),
+            MotivoParada::LimiteIteracoes => None,
+        }
+    }
+}
+
+// Dados de um problema que não são funções
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadadosProblema {
+    pub nome: String,
+    pub nome_objetivo: String,
+    pub nomes_variaveis: Vec<String>,
+    pub nomes_desigualdades: Vec<String>,
+    pub nomes_igualdades: Vec<String>,
+    pub d_l: Ponto,
+    pub d_u: Ponto,
+    pub x_inicial: Ponto,
+    pub solucao: Option<Ponto>,
+    pub derivadas_analiticas: bool,
+    #[serde(default)]
+    pub variaveis_inteiras: [bool; DIM],
+}
+
+impl MetadadosProblema {
+    // Os nomes são sempre completos, com os nomes padrão no lugar dos que faltam
+    pub fn do_problema(problema: &Problema) -> Self {
+        Self {
+            nome: problema.nome.clone(),
+            nome_objetivo: problema.nome_objetivo.clone(),
+            nomes_variaveis: (0..DIM).map(|i| problema.nome_variavel(i)).collect(),
+            nomes_desigualdades: (0..problema.mi())
+                .map(|j| problema.nome_desigualdade(j))
+                .collect(),
+            nomes_igualdades: (0..problema.me())
+                .map(|r| problema.nome_igualdade(r))
+                .collect(),
+            d_l: problema.d_l,
+            d_u: problema.d_u,
+            x_inicial: problema.x_inicial,
+            solucao: problema.solucao,
+            derivadas_analiticas: problema.tem_derivadas_analiticas(),
+            variaveis_inteiras: problema.variaveis_inteiras,
+        }
+    }
+}
+
+// Ponto encontrado e o que se sabe dele
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolucaoEncontrada {
+    pub x: Ponto,
+    pub valor_objetivo: NumReal,
+    pub multiplicadores: MultiplicadoresDeLagrange,
+    pub residuo_kkt: ResiduoKkt,
+    pub restricoes: Vec<EstadoRestricao>,
+}
+
+// Resultado da resolução de um problema, como é gravado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultadoSolucao {
+    pub versao_esquema: u32,
+    pub problema: MetadadosProblema,
+    pub configuracao: Configuracao,
+    pub parada: MotivoParada,
+    pub iteracoes: usize,
+    pub solucao: Option<SolucaoEncontrada>,
+    pub avaliacoes: ContadoresAvaliacao,
+    pub historico: Historico,
+}
+
+impl ResultadoSolucao {
+    // Os metadados vêm do problema como foi dado, já que a resolução
+    // muda a região de confiança d_l..d_u da copia, que é a avaliada
+    #[allow(clippy::too_many_arguments)]
+    pub fn novo(
+        metadados: MetadadosProblema,
+        problema: &Problema,
+        configuracao: &Configuracao,
+        parada: MotivoParada,
+        iteracoes: usize,
+        otimo: &Option<(Ponto, MultiplicadoresDeLagrange)>,
+        avaliacoes: ContadoresAvaliacao,
+        historico: Historico,
+    ) -> Self {
+        let solucao = otimo
+            .as_ref()
+            .map(|(x, multiplicadores)| SolucaoEncontrada {
+                x: *x,
+                valor_objetivo: problema.objetivo_em(*x),
+                multiplicadores: multiplicadores.clone(),
+                residuo_kkt: residuo_kkt(problema, x, multiplicadores),
+                restricoes: estados_restricoes(problema, x, multiplicadores),
+            });
+
+        Self {
+            versao_esquema: VERSAO_ESQUEMA,
+            problema: metadados,
+            configuracao: configuracao.clone(),
+            parada,
+            iteracoes,
+            solucao,
+            avaliacoes,
+            historico,
+        }
+    }
+
+    pub fn serializar(&self, formato: FormatoResultados) -> Result<String, String> {
+        match formato {
+            FormatoResultados::Json => {
+                serde_json::to_string_pretty(self).map_err(|erro| erro.to_string())
+            }
+            #[cfg(feature = "toml")]
+            FormatoResultados::Toml => {
+                toml::to_string_pretty(self).map_err(|erro| erro.to_string())
+            }
+        }
+    }
+
+    // Lê um resultado gravado, recusando outras versões do esquema
+    #[allow(dead_code)]
+    pub fn ler(texto: &str, formato: FormatoResultados) -> Result<Self, String> {
+        let resultado: ResultadoSolucao = match formato {
+            FormatoResultados::Json => {
+                serde_json::from_str(texto).map_err(|erro| erro.to_string())?
+            }
+            #[cfg(feature = "toml")]
+            FormatoResultados::Toml => toml::from_str(texto).map_err(|erro| erro.to_string())?,
+        };
+
+        if resultado.versao_esquema != VERSAO_ESQUEMA {
+            return Err(format!(
+                "versão do esquema {} não suportada (esperada {})",
+                resultado.versao_esquema, VERSAO_ESQUEMA
+            ));
+        }
+
+        Ok(resultado)
+    }
+
+    // Grava o resultado em <diretorio>/<nome>.<formato>, criando o
+    // diretorio se necessario
+    pub fn exportar(&self, diretorio: &str, formato: FormatoResultados) -> Result<(), String> {
+        fs::create_dir_all(diretorio).map_err(|erro| format!("{}: {}", diretorio, erro))?;
+
+        let caminho = format!(
+            "{}/{}.{}",
+            diretorio,
+            self.problema.nome.replace(' ', "_"),
+            formato.nome()
+        );
+        let texto = self.serializar(formato)?;
+        fs::write(&caminho, texto).map_err(|erro| format!("{}: {}", caminho, erro))
+    }
+}
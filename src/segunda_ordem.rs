@@ -0,0 +1,161 @@
+use crate::defs::*;
+use crate::lagrangianas::hessiana_lagrangiana;
+use crate::ponto_estacionario::residuo_kkt;
+use crate::qualificacoes::desigualdades_ativas;
+use crate::utils::*;
+
+// Multiplicador minimo para uma desigualdade ativa ser considerada
+// fortemente ativa, isto é, não degenerada
+const TOL_MULTIPLICADOR: NumReal = 1e-6;
+
+// Tolerancia relativa dos autovalores da hessiana reduzida
+const TOL_CURVATURA: NumReal = 1e-6;
+
+// Classificação de um ponto KKT pelas condições de segunda ordem
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassificacaoSegundaOrdem {
+    MinimoLocalEstrito, // Condição suficiente de segunda ordem satisfeita
+    PossivelMinimo,     // Condição necessaria satisfeita, mas não a suficiente
+    PontoDeSela,        // Condição necessaria violada, existe direção de curvatura negativa
+    NaoKkt,             // Os residuos KKT não foram aceitos, e as condições não se aplicam
+}
+
+// Resultado da verificação de segunda ordem
+#[derive(Debug, Clone)]
+pub struct ResultadoSegundaOrdem {
+    pub classificacao: ClassificacaoSegundaOrdem,
+
+    // Menor autovalor da hessiana da lagrangiana reduzida ao nucleo dos
+    // gradientes das igualdades e das desigualdades fortemente ativas,
+    // espaço que contém o cone critico. None se o nucleo é {0}
+    pub menor_autovalor: Option<NumReal>,
+
+    // Menor autovalor reduzido ao nucleo de todos os gradientes ativos,
+    // espaço contido no cone critico. None se o nucleo é {0}
+    pub menor_autovalor_todas_ativas: Option<NumReal>,
+}
+
+impl ResultadoSegundaOrdem {
+    // Mostra o resultado de forma legivel
+    pub fn mostrar(&self) {
+        let classificacao = match self.classificacao {
+            ClassificacaoSegundaOrdem::MinimoLocalEstrito => "minimo local estrito",
+            ClassificacaoSegundaOrdem::PossivelMinimo => "possivel minimo",
+            ClassificacaoSegundaOrdem::PontoDeSela => "ponto de sela",
+            ClassificacaoSegundaOrdem::NaoKkt => {
+                println!("Segunda ordem: não verificada, o ponto não é KKT");
+                return;
+            }
+        };
+        match self.menor_autovalor {
+            Some(autovalor) => println!(
+                "Segunda ordem: {}, menor autovalor reduzido = {:?}",
+                classificacao, autovalor
+            ),
+            None => println!(
+                "Segunda ordem: {}, gradientes ativos geram todo o espaço",
+                classificacao
+            ),
+        }
+
+        // Com desigualdades degeneradas os dois subespaços diferem
+        if self.menor_autovalor_todas_ativas != self.menor_autovalor {
+            match self.menor_autovalor_todas_ativas {
+                Some(autovalor) => println!(
+                    "Menor autovalor reduzido a todas as restrições ativas = {:?}",
+                    autovalor
+                ),
+                None => println!("Todas as restrições ativas geram todo o espaço"),
+            }
+        }
+    }
+}
+
+// Menor autovalor da hessiana reduzida ZᵀHZ, com as colunas de Z sendo
+// uma base ortonormal do nucleo dos gradientes dados
+fn menor_autovalor_reduzido(hessiana: &[Vec<NumReal>], gradientes: &Vec<Ponto>) -> Option<NumReal> {
+    let base = base_nucleo(gradientes);
+    if base.is_empty() {
+        return None;
+    }
+
+    // (ZᵀHZ)ₖₗ = zₖᵀHzₗ
+    let hz: Vec<Ponto> = base
+        .iter()
+        .map(|z| produto_matriz_vetor(hessiana, z))
+        .collect();
+    let reduzida: Vec<Vec<NumReal>> = base
+        .iter()
+        .map(|zk| hz.iter().map(|hzl| produto_interno(zk, hzl)).collect())
+        .collect();
+
+    autovalores_simetrica(reduzida).first().cloned()
+}
+
+// Verifica as condições de segunda ordem no ponto KKT x com os
+// multiplicadores dados. A hessiana da lagrangiana é calculada por
+// diferenças finitas e reduzida a dois subespaços:
+// - o nucleo dos gradientes das igualdades e das desigualdades com λⱼ > 0,
+//   que contém o cone critico. Se a hessiana é definida positiva nele, a
+//   condição suficiente vale e x é minimo local estrito;
+// - o nucleo de todos os gradientes ativos, contido no cone critico. Se a
+//   hessiana tem curvatura negativa nele, a condição necessaria é violada
+//   e x é ponto de sela.
+// Nos demais casos, que só acontecem com multiplicadores degenerados ou
+// curvatura nula, x é um possivel minimo.
+// As condições só valem em pontos KKT, então quando os residuos KKT não
+// são aceitos pelas tolerancias, como nas paradas por tempo, orçamento ou
+// passos repetidos longe da solução, o ponto não é classificado
+pub fn verificar_segunda_ordem(
+    problema: &Problema,
+    x: Ponto,
+    multiplicadores: &MultiplicadoresDeLagrange,
+    configuracao: &Configuracao,
+) -> ResultadoSegundaOrdem {
+    if !residuo_kkt(problema, &x, multiplicadores).convergiu(&configuracao.tolerancias_kkt) {
+        return ResultadoSegundaOrdem {
+            classificacao: ClassificacaoSegundaOrdem::NaoKkt,
+            menor_autovalor: None,
+            menor_autovalor_todas_ativas: None,
+        };
+    }
+
+    let hessiana = hessiana_lagrangiana(problema, multiplicadores, x);
+
+    let (_, _, _, _, grads_g, grads_h) = problema.avaliar_em(x);
+    let ativas = desigualdades_ativas(problema, x);
+
+    // Gradientes das restrições fortemente ativas e de todas as ativas
+    let mut fortemente_ativas: Vec<Ponto> = grads_h.clone();
+    let mut todas_ativas: Vec<Ponto> = grads_h;
+    for &j in &ativas {
+        if multiplicadores.lambdas[j] > TOL_MULTIPLICADOR {
+            fortemente_ativas.push(grads_g[j]);
+        }
+        todas_ativas.push(grads_g[j]);
+    }
+
+    let menor_autovalor = menor_autovalor_reduzido(&hessiana, &fortemente_ativas);
+    let menor_autovalor_todas_ativas = menor_autovalor_reduzido(&hessiana, &todas_ativas);
+
+    // Tolerancia relativa à escala da hessiana
+    let escala = hessiana
+        .iter()
+        .flat_map(|linha| linha.iter())
+        .fold(1.0, |acc: NumReal, v| acc.max(v.abs()));
+    let tol = TOL_CURVATURA * escala;
+
+    let classificacao = if menor_autovalor.is_none_or(|autovalor| autovalor > tol) {
+        ClassificacaoSegundaOrdem::MinimoLocalEstrito
+    } else if menor_autovalor_todas_ativas.is_some_and(|autovalor| autovalor < -tol) {
+        ClassificacaoSegundaOrdem::PontoDeSela
+    } else {
+        ClassificacaoSegundaOrdem::PossivelMinimo
+    };
+
+    ResultadoSegundaOrdem {
+        classificacao,
+        menor_autovalor,
+        menor_autovalor_todas_ativas,
+    }
+}
@@ -0,0 +1,242 @@
+use crate::algebra_linear::{DecomposicaoLu, Matriz};
+use crate::defs::NumReal;
+use crate::matriz_esparsa::MatrizEsparsa;
+
+// Tolerancia de viabilidade das linhas e dos valores duais
+const TOL_SIMPLEX: NumReal = 1e-9;
+
+// Simplex dual para problemas lineares da forma
+// min cᵀx
+// s.a.: Ax ≥ b
+// com todas as variaveis livres, como é o caso do subproblema linear.
+// Uma base é um conjunto de tantas linhas de A quanto variaveis, com A_B
+// inversivel. O vértice da base é x = A_B⁻¹b_B e os valores duais das linhas
+// da base são y_B = A_B⁻ᵀc. A base é dual viavel quando y_B ≥ 0, e otima
+// quando, além disso, x satisfaz todas as linhas fora da base.
+// O metodo parte de uma base dual viavel, normalmente a base otima do
+// problema anterior, e a cada iteração troca uma linha da base pela linha
+// mais violada, mantendo a viabilidade dual, até que x seja viavel.
+// É o mesmo que o simplex primal aplicado ao dual max bᵀy, s.a.: Aᵀy = c, y ≥ 0.
+// A cada iteração A_B é montada das linhas esparsas da base e decomposta
+// uma só vez, e a mesma decomposição dá x, y_B e a direção dos duais.
+// Retorna o ponto otimo, os valores duais de todas as linhas e a base otima, ou
+// None quando a base inicial não serve (singular ou não dual viavel), quando o
+// problema é inviavel ou quando não converge, e então o problema deve ser
+// resolvido do zero.
+pub fn simplex_dual(
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    c: &[NumReal],
+    base_inicial: Vec<usize>,
+) -> Option<(Vec<NumReal>, Vec<NumReal>, Vec<usize>)> {
+    let n_vars = a.n_colunas;
+    let mut base = base_inicial;
+
+    if base.len() != n_vars {
+        return None;
+    }
+
+    // Limite de iterações, evitando ciclagem em problemas degenerados
+    for _ in 0..(10 * a.n_linhas() + 10) {
+        let lu = decompor_base(a, &base)?;
+
+        // Vértice da base, x = A_B⁻¹b_B
+        let b_b: Vec<NumReal> = base.iter().map(|&i| b[i]).collect();
+        let x = lu.resolver(&b_b);
+
+        // Valores duais da base, y_B = A_B⁻ᵀc
+        let y_b = lu.resolver_transposta(c);
+        if y_b.iter().any(|&y| y < -TOL_SIMPLEX * (1.0 + y.abs())) {
+            return None;
+        }
+
+        // Procura a linha fora da base mais violada por x
+        let mut na_base = vec![false; a.n_linhas()];
+        for &i in &base {
+            na_base[i] = true;
+        }
+        let mut linha_entrando = None;
+        let mut maior_violacao = 0.0;
+        for (i, &bi) in b.iter().enumerate().take(a.n_linhas()) {
+            if na_base[i] {
+                continue;
+            }
+            let ax: NumReal = a.linha(i).map(|(j, cof)| cof * x[j]).sum();
+            let violacao = (bi - ax) / (1.0 + bi.abs());
+            if violacao > TOL_SIMPLEX && violacao > maior_violacao {
+                maior_violacao = violacao;
+                linha_entrando = Some(i);
+            }
+        }
+
+        // Se nenhuma linha é violada, a base é otima
+        let r = match linha_entrando {
+            Some(r) => r,
+            None => {
+                let mut duais = vec![0.0; a.n_linhas()];
+                for (k, &i) in base.iter().enumerate() {
+                    duais[i] = y_b[k].max(0.0);
+                }
+                return Some((x, duais, base));
+            }
+        };
+
+        // Como os duais da base variam quando a linha r entra, y_B(θ) = y_B - θw,
+        // com w = A_B⁻ᵀa_r
+        let w = lu.resolver_transposta(&a.linha_densa(r));
+
+        // Teste da razão, a linha da base que sai é a primeira a zerar seu dual
+        let mut sai = None;
+        let mut menor_razao = NumReal::INFINITY;
+        for k in 0..n_vars {
+            if w[k] > TOL_SIMPLEX {
+                let razao = y_b[k].max(0.0) / w[k];
+                if razao < menor_razao {
+                    menor_razao = razao;
+                    sai = Some(k);
+                }
+            }
+        }
+
+        // Se nenhuma sai, o dual é ilimitado e o problema é inviavel
+        let k = sai?;
+        base[k] = r;
+    }
+
+    None
+}
+
+// Decompõe A_B, a matriz quadrada das linhas da base, montada direto das
+// entradas não nulas de cada linha. None se A_B é singular
+fn decompor_base(a: &MatrizEsparsa, base: &[usize]) -> Option<DecomposicaoLu> {
+    let mut a_b = Matriz::nula(base.len(), a.n_colunas);
+    for (k, &i) in base.iter().enumerate() {
+        for (j, valor) in a.linha(i) {
+            a_b[(k, j)] = valor;
+        }
+    }
+    a_b.lu()
+}
+
+// Escolhe uma base a partir de uma solução já conhecida (x, y).
+// Entram primeiro as linhas com dual positivo, e depois as demais
+// linhas ativas em x, desde que linearmente independentes das que
+// já foram escolhidas. Retorna None se não for possivel completar
+// uma base só com linhas ativas.
+pub fn base_da_solucao(
+    a: &MatrizEsparsa,
+    b: &[NumReal],
+    x: &[NumReal],
+    duais: &[NumReal],
+) -> Option<Vec<usize>> {
+    let n_vars = a.n_colunas;
+
+    // Linhas ativas ordenadas por valor dual decrescente
+    let mut ativas: Vec<usize> = (0..a.n_linhas())
+        .filter(|&i| {
+            let ax: NumReal = a.linha(i).map(|(j, cof)| cof * x[j]).sum();
+            (ax - b[i]).abs() <= 1e-7 * (1.0 + b[i].abs())
+        })
+        .collect();
+    ativas.sort_by(|&i, &k| duais[k].total_cmp(&duais[i]));
+
+    // Eliminação de Gauss incremental, guardando as linhas já reduzidas
+    // junto do indice da coluna do pivô
+    let mut reduzidas: Vec<(usize, Vec<NumReal>)> = Vec::new();
+    let mut base = Vec::new();
+    for i in ativas {
+        let mut linha = a.linha_densa(i);
+        for (pivo, reduzida) in &reduzidas {
+            let fator = linha[*pivo] / reduzida[*pivo];
+            for j in 0..n_vars {
+                linha[j] -= fator * reduzida[j];
+            }
+        }
+
+        // Escolhe o maior elemento restante como pivô
+        let mut pivo = 0;
+        for j in 0..n_vars {
+            if linha[j].abs() > linha[pivo].abs() {
+                pivo = j;
+            }
+        }
+
+        if linha[pivo].abs() > 1e-9 {
+            reduzidas.push((pivo, linha));
+            base.push(i);
+            if base.len() == n_vars {
+                return Some(base);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod testes {
+    use crate::defs::{Configuracao, NumReal};
+    use crate::matriz_esparsa::MatrizEsparsa;
+    use crate::prob_linear::SessaoProblemaLinear;
+
+    // min -x₀ - x₁ s.a.: -1 ≤ x₀ ≤ 1 e -1 ≤ x₁ ≤ 1, com otimo em (1, 1)
+    fn problema_inicial() -> (MatrizEsparsa, Vec<NumReal>, Vec<NumReal>, Vec<String>) {
+        let mut a = MatrizEsparsa::nova(2);
+        a.adicionar_linha(&[(0, 1.0)]);
+        a.adicionar_linha(&[(0, -1.0)]);
+        a.adicionar_linha(&[(1, 1.0)]);
+        a.adicionar_linha(&[(1, -1.0)]);
+        let b = vec![-1.0, -1.0, -1.0, -1.0];
+        let c = vec![-1.0, -1.0];
+        let nomes = ["x₀ ≥ -1", "x₀ ≤ 1", "x₁ ≥ -1", "x₁ ≤ 1"];
+        (a, b, c, nomes.iter().map(|nome| nome.to_string()).collect())
+    }
+
+    // O mesmo com as duas linhas da restrição 1c de uma direção,
+    // -2 ≤ x₀ + 2x₁ ≤ 2, que cortam o otimo anterior. O novo otimo é
+    // (1, 0.5), com duais unicos 0.5 em x₀ ≤ 1 e em x₀ + 2x₁ ≤ 2
+    fn problema_com_direcao() -> (MatrizEsparsa, Vec<NumReal>, Vec<NumReal>, Vec<String>) {
+        let (mut a, mut b, c, mut nomes) = problema_inicial();
+        a.adicionar_linha(&[(0, 1.0), (1, 2.0)]);
+        a.adicionar_linha(&[(0, -1.0), (1, -2.0)]);
+        b.extend([-2.0, -2.0]);
+        nomes.extend(["1c+".to_string(), "1c-".to_string()]);
+        (a, b, c, nomes)
+    }
+
+    #[test]
+    fn base_anterior_resolve_igual_ao_problema_do_zero() {
+        let configuracao = Configuracao::padrao();
+        let valor = |c: &[NumReal], x: &[NumReal]| -> NumReal {
+            c.iter().zip(x).map(|(ci, xi)| ci * xi).sum()
+        };
+
+        // Resolve o primeiro do zero, e o segundo a partir da sua base
+        let mut sessao = SessaoProblemaLinear::nova(&configuracao);
+        let (a, b, c, nomes) = problema_inicial();
+        sessao.resolver_matriz(&a, &b, &c, &nomes).unwrap();
+        let (a, b, c, nomes) = problema_com_direcao();
+        let (x, duais) = sessao.resolver_matriz(&a, &b, &c, &nomes).unwrap();
+        assert_eq!(sessao.resolucoes_completas, 1);
+        assert_eq!(sessao.resolucoes_incrementais, 1);
+
+        let (x_zero, duais_zero) = SessaoProblemaLinear::nova(&configuracao)
+            .resolver_matriz(&a, &b, &c, &nomes)
+            .unwrap();
+
+        assert!((valor(&c, &x) - valor(&c, &x_zero)).abs() <= 1e-9);
+        assert!((valor(&c, &x) + 1.5).abs() <= 1e-9);
+        for (y, y_zero) in duais.iter().zip(&duais_zero) {
+            assert!(
+                (y - y_zero).abs() <= 1e-7,
+                "{:?} contra {:?}",
+                duais,
+                duais_zero
+            );
+        }
+        let esperados = [0.0, 0.5, 0.0, 0.0, 0.0, 0.5];
+        for (y, esperado) in duais.iter().zip(esperados) {
+            assert!((y - esperado).abs() <= 1e-9, "{:?}", duais);
+        }
+    }
+}
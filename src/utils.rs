@@ -1,5 +1,7 @@
+use crate::algebra_linear::Matriz;
 use crate::defs::OP;
 use crate::defs::*;
+use crate::diferencas_finitas::{gradiente_sem_erro, hessiana, OpcoesDiferencas};
 use std::convert::TryInto;
 
 // Função que toma um vetor de tamanho variavel e devolve um array de tamanho fixo contiguo na memoria
@@ -14,8 +16,8 @@ where
         panic!("Vetor muito pequeno para transformar em array");
     }
 
-    for i in 0..DIM {
-        v.push(v_in[i]); // copia os DIM elementos
+    for &valor in v_in.iter().take(DIM) {
+        v.push(valor); // copia os DIM elementos
     }
 
     // Tenta converter no array de mesmo tamanho, caso não de, algo deu errado, não tem memoria ram suficiente?
@@ -29,32 +31,10 @@ where
 }
 
 // Função que retorna o gradiente da função f em um ponto x
-// Gradiente sendo o vetor de derivadas parciais da função avaliadas no ponto
-// Usando metodo central de derivada: D(f(x)) = (f(x-h) - f(x+h)) / (2*h)
-pub fn auto_grad(x: Ponto, f: impl Fn(Ponto) -> NumReal) -> Ponto {
-    // Vetor onde vai ser guardado as derivadas parciais avaliadas em x
-    let mut xr = Vec::new();
-
-    // Considera a derivada parcial à respeito a i por cada vez
-    for i in 0..x.len() {
-        // Cria duas copias, uma para a subitração de h e outra para a soma
-        let mut x1 = x.clone();
-        let mut x2 = x.clone();
-
-        // Aplica a diferença só na componente em questão
-        x1[i] -= DBL_EPS;
-        x2[i] += DBL_EPS;
-
-        // Avalia a função nos dois pontos
-        let y1 = f(x1.clone());
-        let y2 = f(x2.clone());
-
-        // Calcula a variação pelo método central
-        xr.push((y2 - y1) / (DDBL_EPS)); // x2 - x1 é o mesmo que 2*h
-    }
-
-    // Retorna o vetor como um array (contiguo na memoria)
-    return vec_arr_fixo(xr);
+// Gradiente sendo o vetor de derivadas parciais da função avaliadas no ponto,
+// por diferenças finitas com as opções dadas
+pub fn auto_grad(x: Ponto, f: impl Fn(Ponto) -> NumReal, opcoes: &OpcoesDiferencas) -> Ponto {
+    gradiente_sem_erro(x, f, opcoes)
 }
 
 // Calcula o produto interno da forma padrão
@@ -63,16 +43,16 @@ pub fn produto_interno(a: &Ponto, b: &Ponto) -> NumReal {
     for idx in 0..DIM {
         acc += a[idx] * b[idx];
     }
-    return acc;
+    acc
 }
 
 // Calcula o produto interno da forma padrão para vetores de qualquer tamanaho
-pub fn _produto_interno_generico(a: &Vec<NumReal>, b: &Vec<NumReal>) -> NumReal {
+pub fn _produto_interno_generico(a: &[NumReal], b: &[NumReal]) -> NumReal {
     let mut acc = 0.0;
     for idx in 0..(b.len().min(a.len())) {
         acc += a[idx] * b[idx];
     }
-    return acc;
+    acc
 }
 
 // Calcula a norma na forma padrão
@@ -85,7 +65,7 @@ pub fn norma(x: &Ponto) -> NumReal {
 }
 
 // Verifica se um número é proximo o suficiente de 0 pra ser considerado zero
-// É considerado 0 se a distancia desse número pra 0 for menor que a quantidade "infinitesimal" usada na diferenciação
+// É considerado 0 se a distancia desse número pra 0 for menor que a quantidade "infinitesimal" DBL_EPS
 pub fn prox_o_suficiente_de_zero(n: NumReal) -> bool {
     (n - 0.0).abs() < DBL_EPS
 }
@@ -93,16 +73,16 @@ pub fn prox_o_suficiente_de_zero(n: NumReal) -> bool {
 // Calcula o vetor normalizado (magnetude 1) de um dado vetor
 // Calcula a normal escalando o vetor pelo inverso de sua magnetude
 // vetor_normal = (1/magnetude(vetor)) * vetor
-pub fn normalizar(vetor: &Ponto) -> Ponto {
+pub fn _normalizar(vetor: &Ponto) -> Ponto {
     // Calcula a norma do vetor (ou ponto, já que compartilham a mesma estrutura fisica)
     let norma = norma(vetor);
 
     // Cria uma copia do vetor pra ser retornado mais tarde
-    let mut novo_vetor = vetor.clone();
+    let mut novo_vetor = *vetor;
 
     // Divide-se cada componente do vetor pela norma (ou magnetude, mesma coisa nesse caso)
-    for idx in 0..DIM {
-        novo_vetor[idx] /= norma;
+    for componente in novo_vetor.iter_mut() {
+        *componente /= norma;
     }
 
     // Retorna
@@ -112,7 +92,7 @@ pub fn normalizar(vetor: &Ponto) -> Ponto {
 // Calcula a distancia entre dois pontos ou dois vetores
 // Poderia usar o mesmo codigo da norma? sim, mas melhor deixar aberto à outras formas de mensurar distancias
 // Deixando não restrito à normal euclidiana
-pub fn dist(p: Ponto, q: Ponto) -> NumReal {
+pub fn _dist(p: Ponto, q: Ponto) -> NumReal {
     let mut acc: f64 = 0.0;
     for i in 0..DIM {
         acc += (p[i] - q[i]).powi(2);
@@ -120,115 +100,81 @@ pub fn dist(p: Ponto, q: Ponto) -> NumReal {
     acc.sqrt()
 }
 
-// Verifica se pelo menos dois vetores de uma lista são linearmente dependentes
+// Verifica se os vetores de uma lista são linearmente dependentes,
+// isto é, se algum deles é combinação linear dos outros, e não apenas
+// se existe um par de vetores paralelos
 pub fn sao_linearmente_dependentes(pontos: &Vec<Ponto>) -> bool {
-    // Pra cada vetor, calcula os normais
-    let normais: Vec<Ponto> = pontos.iter().map(|ponto| normalizar(ponto)).collect();
-
-    // Dois vetores, a e b, são linearmente dependentes se e somente se
-    // puderem ser escritos como a = k*b, para algum k real.
-    // Então se a e b forem normalizados, eles deve ser iguals
-
-    // Para cada normal
-    for normal_idx in 0..normais.len() {
-        // Para cada normal de novo
-        for normal_idx_2 in 0..normais.len() {
-            // Caso as normais escolhidas sejam diferentes
-            if normal_idx != normal_idx_2 {
-                // Se a distancia entre os dois vetores for desconsideravel
-                if prox_o_suficiente_de_zero(dist(normais[normal_idx], normais[normal_idx_2])) {
-                    return true; // Então existem pelo menos dois vetores linearmente dependentes na lista
-                }
-            }
-        }
-    }
-
-    // Foi olhada todos os pares de vetores possiveis, e nenhum par era linearmente dependente
-    false
+    combinacao_nula(pontos).is_some()
 }
 
-// Função simples que retorna o maximo entre dois numeros, só pra
-// ficar mais legivel daqui pra frente
-pub fn max(a: NumReal, b: NumReal) -> NumReal {
-    a.max(b)
+// Posto de uma lista de vetores, isto é, o numero maximo de vetores
+// linearmente independentes entre eles
+pub fn posto(pontos: &Vec<Ponto>) -> usize {
+    matriz_de_pontos(pontos).posto()
 }
 
-// Retorna o proximo ponto do espaço limitado do problema
-// Melhor assim do que fazer loops pra cada dimensão, o que nem dinamico fica
-pub fn prox_ponto(
-    mut x: Vec<NumReal>,
-    x_min: &Vec<NumReal>,
-    x_max: &Vec<NumReal>,
-    dim: usize,
-    passo: NumReal,
-) -> Vec<NumReal> {
-    x[dim - 1] += passo;
+// Base ortonormal do nucleo de uma lista de vetores, isto é, do
+// conjunto dos d com pᵢᵀd = 0 para todo i
+pub fn base_nucleo(pontos: &Vec<Ponto>) -> Vec<Ponto> {
+    matriz_de_pontos(pontos)
+        .base_nucleo()
+        .into_iter()
+        .map(vec_arr_fixo)
+        .collect()
+}
 
-    for idx in (0..dim).rev() {
-        if x[idx] > x_max[idx] {
-            x[idx] = x_min[idx];
+// Autovalores de uma matriz simetrica, em ordem crescente
+pub fn autovalores_simetrica(a: Vec<Vec<NumReal>>) -> Vec<NumReal> {
+    let n = a.len();
+    Matriz::de_linhas(&a, n).autodecomposicao_simetrica().0
+}
 
-            if idx > 0 {
-                x[idx - 1] += passo;
-            }
-        }
+// Procura coeficientes v, não todos nulos, tais que Σ vᵢpᵢ = 0, isto é,
+// um vetor do nucleo da matriz DIM × m com os vetores nas colunas.
+// Retorna None quando os vetores são linearmente independentes
+pub fn combinacao_nula(pontos: &Vec<Ponto>) -> Option<Vec<NumReal>> {
+    if pontos.is_empty() {
+        return None;
     }
+    let colunas: Vec<Vec<NumReal>> = pontos.iter().map(|p| p.to_vec()).collect();
+    Matriz::de_colunas(&colunas, DIM)
+        .base_nucleo()
+        .into_iter()
+        .next()
+}
 
-    return x;
+// Matriz com os vetores da lista nas linhas
+fn matriz_de_pontos(pontos: &Vec<Ponto>) -> Matriz {
+    let linhas: Vec<Vec<NumReal>> = pontos.iter().map(|p| p.to_vec()).collect();
+    Matriz::de_linhas(&linhas, DIM)
+}
+
+// Função simples que retorna o maximo entre dois numeros, só pra
+// ficar mais legivel daqui pra frente
+pub fn max(a: NumReal, b: NumReal) -> NumReal {
+    a.max(b)
 }
 
 // Verifica de dois vetores são iguais
-pub fn _iguais(a: &Vec<NumReal>, b: &Vec<NumReal>, dim: usize) -> bool {
+pub fn _iguais(a: &[NumReal], b: &[NumReal], dim: usize) -> bool {
     for idx in 0..dim {
         if a[idx] != b[idx] {
             // Se alguma componente não é a mesma, não são
             return false;
         }
     }
-    return true;
+    true
 }
 
 // Verifica de dois vetores são iguais, considerando o erro da maquina
-pub fn quase_iguais(a: &Vec<NumReal>, b: &Vec<NumReal>, dim: usize) -> bool {
+pub fn _quase_iguais(a: &[NumReal], b: &[NumReal], dim: usize) -> bool {
     for idx in 0..dim {
         if (a[idx] - b[idx]).abs() > 0.01 {
             // Se alguma componente não é a mesma, não são
             return false;
         }
     }
-    return true;
-}
-
-// Transposição de matirz
-pub fn transpor_matriz(m: &Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
-    let mut t = vec![Vec::with_capacity(m.len()); m[0].len()];
-    for r in m {
-        for i in 0..r.len() {
-            t[i].push(r[i]);
-        }
-    }
-    t
-}
-
-// Gera um vetor nulo, exceto na entrada determinada com o valor determinado
-// Usado inicialmente para "selecionar" um unico valor de um vetor
-pub fn fn_aux_vetor_nulo_exceto(idx: usize, dim: usize, val: NumReal) -> Vec<NumReal> {
-    let mut ret = vec![0.0; dim];
-    ret[idx] = val;
-    ret
-}
-
-// Funções auxiliares para selecionar apenas um componente
-// dos vetores tᵍ, tʰ⁺ e tʰ⁻, selecionanando o proprio valor,
-// ou o simetrico
-pub fn fn_aux_vtgj(idx: usize, mi: usize) -> Vec<NumReal> {
-    fn_aux_vetor_nulo_exceto(idx, mi, -1.0)
-}
-pub fn fn_aux_vrh_p(idx: usize, me: usize) -> Vec<NumReal> {
-    fn_aux_vetor_nulo_exceto(idx, me, 1.0)
-}
-pub fn fn_aux_vrh_n(idx: usize, me: usize) -> Vec<NumReal> {
-    fn_aux_vetor_nulo_exceto(idx, me, -1.0)
+    true
 }
 
 // Calcula o produto por escalar de um numero de um ponto
@@ -282,7 +228,7 @@ pub fn line_search(x: Ponto, direcao: Ponto, f: &impl Fn(Ponto) -> NumReal) -> f
 
     while a_atual < 1.0 {
         // Incrementa ɑ
-        a_atual = a_atual + incremento;
+        a_atual += incremento;
 
         // Calcula x = x + ɑ*d
         let x_novo = soma_pontos(x, produto_escalar(a_atual, direcao));
@@ -301,7 +247,7 @@ pub fn line_search(x: Ponto, direcao: Ponto, f: &impl Fn(Ponto) -> NumReal) -> f
 }
 
 // Calcula uma operação indice a indice de uma matriz
-fn op_direta_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>, op: OP) -> Vec<Vec<NumReal>> {
+fn op_direta_matriz(a: &[Vec<NumReal>], b: &[Vec<NumReal>], op: OP) -> Vec<Vec<NumReal>> {
     // Tem que ter o mesmo numero de linhas
     assert_eq!(a.len(), b.len());
 
@@ -319,8 +265,8 @@ fn op_direta_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>, op: OP) -> Vec
         for j in 0..a[i].len() {
             // Se for soma, soma, se não, subtrai
             let val = match op {
-                OP::ADD => a[i][j] + b[i][j],
-                OP::SUB => a[i][j] - b[i][j],
+                OP::Add => a[i][j] + b[i][j],
+                OP::Sub => a[i][j] - b[i][j],
             };
 
             // Adiciona a coluna operada
@@ -335,23 +281,23 @@ fn op_direta_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>, op: OP) -> Vec
 }
 
 // Faz uma operação indice a indice usando a operação de subtração
-fn _subtracao_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
-    op_direta_matriz(a, b, OP::SUB)
+fn _subtracao_matriz(a: &[Vec<NumReal>], b: &[Vec<NumReal>]) -> Vec<Vec<NumReal>> {
+    op_direta_matriz(a, b, OP::Sub)
 }
 
 // Faz uma operação indice a indice usando a operação de adição
-fn soma_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
-    op_direta_matriz(a, b, OP::ADD)
+pub fn soma_matriz(a: &[Vec<NumReal>], b: &[Vec<NumReal>]) -> Vec<Vec<NumReal>> {
+    op_direta_matriz(a, b, OP::Add)
 }
 
 // Multiplica todos os indices de uma matriz por um numero
-fn matriz_por_escalar(a: NumReal, mut b: Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
+pub fn matriz_por_escalar(a: NumReal, mut b: Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
     // Para cada linha i
-    for i in 0..b.len() {
+    for linha in b.iter_mut() {
         // Para cada coluna j da linha i
-        for j in 0..b[i].len() {
+        for entrada in linha.iter_mut() {
             // Multiplica a entrada pelo escalar
-            b[i][j] = a * b[i][j];
+            *entrada *= a;
         }
     }
 
@@ -360,18 +306,18 @@ fn matriz_por_escalar(a: NumReal, mut b: Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>>
 
 // Calcula o produto externo entre dois vetores
 // (abᵀ)ᵢⱼ = aᵢ * bⱼ
-fn produto_externo(a: Ponto, b: Ponto) -> Vec<Vec<NumReal>> {
+pub fn produto_externo(a: Ponto, b: Ponto) -> Vec<Vec<NumReal>> {
     let mut resultante = Vec::new();
 
     //Para cada item i de a
-    for i in 0..a.len() {
+    for ai in a.iter() {
         // Linha da matriz resultante
         let mut linha = Vec::new();
 
         // Para cada item j de b
-        for j in 0..b.len() {
+        for bj in b.iter() {
             // A entrada ij da matriz vai ser aᵢ * bⱼ
-            linha.push(a[i] * b[j]);
+            linha.push(ai * bj);
         }
 
         // Adiciona a linha na matriz resultante
@@ -381,31 +327,20 @@ fn produto_externo(a: Ponto, b: Ponto) -> Vec<Vec<NumReal>> {
 }
 
 // Calcula a matriz produto de duas outras matrizes
-pub fn prod_matriz(a: &Vec<Vec<NumReal>>, b: &Vec<Vec<NumReal>>) -> Vec<Vec<NumReal>> {
+pub fn prod_matriz(a: &[Vec<NumReal>], b: &[Vec<NumReal>]) -> Vec<Vec<NumReal>> {
     // O produto tem o numero de linhas de a e o numero de colunas de b
-    let mut resultante = vec![vec![0.0; b[0].len()]; a.len()];
-
-    // Faz o calculo padrão, (AB)ᵢⱼ = soma de  aᵢₖ * bₖⱼ, com k=1, ...(numero de linhas de b)
-    for i in 0..resultante.len() {
-        for j in 0..resultante[i].len() {
-            let mut soma = 0.0;
-            for k in 0..b.len() {
-                soma += a[i][k] * b[k][j]
-            }
-            resultante[i][j] = soma;
-        }
-    }
-
-    resultante
+    let matriz_a = Matriz::de_linhas(a, b.len());
+    let matriz_b = Matriz::de_linhas(b, b[0].len());
+    matriz_a.produto(&matriz_b).para_linhas()
 }
 
 // Aplica uma matriz em um ponto, Ab, A matriz, b ponto
-pub fn produto_matriz_vetor(matriz: &Vec<Vec<NumReal>>, vetor: &Ponto) -> Ponto {
+pub fn produto_matriz_vetor(matriz: &[Vec<NumReal>], vetor: &Ponto) -> Ponto {
     let mut vetor_coluna = Vec::new();
 
     // Transforma o ponto em uma matriz coluna
-    for i in 0..DIM {
-        vetor_coluna.push(vec![vetor[i]]);
+    for &componente in vetor.iter() {
+        vetor_coluna.push(vec![componente]);
     }
 
     // Calcula um produto de matriz normal
@@ -416,158 +351,173 @@ pub fn produto_matriz_vetor(matriz: &Vec<Vec<NumReal>>, vetor: &Ponto) -> Ponto
     vec_arr_fixo(vetor_plano)
 }
 
-// Calcula a hessiana do proximo ponto usando as informações de atualização
-// Usando o método BFGS, se usa o grandiete do proximo ponto e do ponto atual
-// junto com a informação de atualização do ponto (x_k+1 = x_k + a*d) onde a*d
-// é a informação.
-// Logo
-// y = ∇f(x_k+1) - ∇f(x_k)
-// s = (x_k+1) - (x_k)
-// Toma como entrada a hessiana em x_k, H(x_k), s e y
-// Retornando a hessiana em x_k+1, H(x_k+1)
-pub fn bfgs(hessiana: Vec<Vec<NumReal>>, s: Ponto, y: Ponto) -> Vec<Vec<NumReal>> {
-    /*
-       // Calculo da inversa da aproximação da hessiana (não usado), considerando que a entrada também é a inversa
-       // B_K+1 = B_k-1
-       // https://gist.github.com/rmcgibbo/4735287#file-bfgs_only_fprime-cpp-L198
-       // {\displaystyle B_{att}^{-1}=\left(I-{\frac {\mathbf {s} \mathbf {y} ^{T}}{\mathbf {y} ^{T}\mathbf {s} }}\right)B^{-1}\left(I-{\frac {\mathbf {y}\mathbf {s}^{T}}{\mathbf {y} ^{T}\mathbf {s}}}\right)+{\frac {\mathbf {s}\mathbf {s}^{T}}{\mathbf {y} ^{T}\mathbf {s}}}.}
-
-       // Matriz Identidade
-       let mut identidade = vec![vec![0.0; DIM]; DIM];
-       for i in 0..DIM {
-           identidade[i][i] = 1.0;
-       }
-
-       // rho é o termo comum yᵀs
-       let rho: NumReal = 1.0 / produto_interno(&s, &y);
-
-       // Calcula a matriz da esquerda de B⁻¹
-       let a1 = _subtracao_matriz(&identidade, &matriz_por_escalar(rho, produto_externo(s, y)));
-
-       // Calcula a matriz da direita de B⁻¹
-       let a2 = _subtracao_matriz(&identidade, &matriz_por_escalar(rho, produto_externo(y, s)));
-
-       // Calcula a1(Ba2)
-       let b_a2 = prod_matriz(&hessiana, &a2);
-       let a1_b_a2 = prod_matriz(&a1, &b_a2);
-
-       // Calcula o termo somado
-       let r_s_s = matriz_por_escalar(rho, produto_externo(s, s));
-
-       // Por fim, soma
-       let inversa = soma_matriz(&a1_b_a2, &r_s_s);
-    */
-
-    // Calculo da aproximação da hessiana com o passo de atualização:
-    // B = B + alpha*uuᵀ + beta*vvᵀ
-    // u = y
-    // v = (Bs)
-    // alpha = 1/yᵀs
-    // beta = -1/(sᵀ(Bs))
-    // abᵀ é o produto externo (outer product) dos vetores a e b
-    // aᵀb é o produto interno euclidiano dos vetores a e b
-
-    // Calcula Bs, visto que é usado mais de uma vez
-    let b_s = produto_matriz_vetor(&hessiana, &s);
-
-    // Calcula alpha e beta com o produto interno
-    let alpha = 1.0 / produto_interno(&y, &s);
-    let beta = -1.0 / produto_interno(&s, &b_s);
-
-    // Caso aconteça alguma divisão por 0 não atualiza a hessiana
-    if !(alpha.is_finite() && beta.is_finite()) {
-        return hessiana;
+// Calculo da hessiana por diferenças finitas de segunda ordem
+pub fn hessiana_exata(
+    f: impl Fn(Ponto) -> NumReal,
+    x: Ponto,
+    opcoes: &OpcoesDiferencas,
+) -> Vec<Vec<NumReal>> {
+    hessiana(x, f, opcoes)
+}
+
+// Resolve min ‖My - alvo‖ sem restrições considerando apenas as colunas de M
+// em idxs, pela decomposição QR de M_P. Retorna None se as colunas de M_P
+// forem linearmente dependentes
+fn minimos_quadrados_subconjunto(
+    colunas: &[Vec<NumReal>],
+    alvo: &[NumReal],
+    idxs: &[usize],
+) -> Option<Vec<NumReal>> {
+    let selecionadas: Vec<Vec<NumReal>> = idxs.iter().map(|&i| colunas[i].clone()).collect();
+    let qr = Matriz::de_colunas(&selecionadas, alvo.len()).qr();
+    if qr.posto() < idxs.len() {
+        return None;
     }
+    Some(qr.minimos_quadrados(alvo))
+}
 
-    // Calcula os produtos externos (que resultam em matrizes) e multiplicam pelos seus escalares
-    let alpha_uut = matriz_por_escalar(alpha, produto_externo(y, y));
-    let beta_vvt = matriz_por_escalar(beta, produto_externo(b_s, b_s));
+// Minimos quadrados não negativos (NNLS), pelo metodo de Lawson-Hanson:
+// min ‖My - alvo‖, y ≥ 0
+// M é dada como uma lista de colunas, todas do tamanho de alvo
+// (Lawson e Hanson, 1974, Solving Least Squares Problems, cap. 23)
+pub fn minimos_quadrados_nao_negativos(colunas: &[Vec<NumReal>], alvo: &[NumReal]) -> Vec<NumReal> {
+    let n = colunas.len();
+    let mut y = vec![0.0; n];
+
+    // Conjunto passivo, das variaveis que podem ser positivas
+    let mut passivas: Vec<usize> = Vec::new();
+
+    // Colunas descartadas por serem dependentes das passivas
+    let mut descartadas: Vec<usize> = Vec::new();
+
+    let tol = 1e-10 * (1.0 + _produto_interno_generico(alvo, alvo).sqrt());
+
+    // Limite de iterações, o metodo termina em um numero finito de passos,
+    // mas erros numericos podem fazê-lo ciclar
+    for _ in 0..(3 * n + 10) {
+        // Gradiente negativo w = Mᵀ(alvo - My)
+        let mut residuo = alvo.to_vec();
+        for j in 0..n {
+            for k in 0..residuo.len() {
+                residuo[k] -= colunas[j][k] * y[j];
+            }
+        }
+        let w: Vec<NumReal> = colunas
+            .iter()
+            .map(|coluna| _produto_interno_generico(coluna, &residuo))
+            .collect();
+
+        // Escolhe a variavel fora do conjunto passivo com maior w
+        let mut escolhida = None;
+        for j in 0..n {
+            if !passivas.contains(&j) && !descartadas.contains(&j) && w[j] > tol {
+                match escolhida {
+                    Some(e) if w[e] >= w[j] => {}
+                    _ => escolhida = Some(j),
+                }
+            }
+        }
 
-    // Soma as 3 matrizes
-    let hessiana_atualizada = soma_matriz(&hessiana, &soma_matriz(&alpha_uut, &beta_vvt));
+        // Se nenhuma pode melhorar o residuo, y é otimo
+        let j = match escolhida {
+            Some(j) => j,
+            None => break,
+        };
+        passivas.push(j);
+
+        loop {
+            // Solução sem restrições no conjunto passivo
+            let s = match minimos_quadrados_subconjunto(colunas, alvo, &passivas) {
+                Some(s) => s,
+                None => {
+                    // Coluna dependente das demais, não entra no conjunto
+                    passivas.retain(|&p| p != j);
+                    descartadas.push(j);
+                    break;
+                }
+            };
 
-    hessiana_atualizada
-}
+            // Se a solução é viavel, aceita
+            if s.iter().all(|&el| el > 0.0) {
+                for (k, &p) in passivas.iter().enumerate() {
+                    y[p] = s[k];
+                }
+                break;
+            }
 
-// http://v8doc.sas.com/sashtml/ormp/chap5/sect28.htm
-// Calculo da hessiana exata pelo método central
-pub fn hessiana_exata(f: impl Fn(Ponto) -> NumReal + Copy, x: Ponto) -> Vec<Vec<NumReal>> {
-    let mut hessiana = vec![vec![0.0; DIM]; DIM];
-    let mut encontrados = vec![vec![false; DIM]; DIM];
+            // Do contrario anda de y até s o maximo possivel mantendo y ≥ 0
+            let mut alpha: NumReal = 1.0;
+            for (k, &p) in passivas.iter().enumerate() {
+                if s[k] <= 0.0 {
+                    alpha = alpha.min(y[p] / (y[p] - s[k]));
+                }
+            }
+            for (k, &p) in passivas.iter().enumerate() {
+                y[p] += alpha * (s[k] - y[p]);
+            }
 
-    for i in 0..DIM {
-        for j in 0..DIM {
-            if i == j {
-                let val1 = {
-                    let mut ponto = x.clone();
-                    ponto[i] += DDBL_EPS;
-
-                    -f(ponto)
-                };
-
-                let val2 = {
-                    let mut ponto = x.clone();
-                    ponto[i] += DBL_EPS;
-                    16.0 * f(ponto)
-                };
-
-                let val3 = -30.0 * f(x);
-
-                let val4 = {
-                    let mut ponto = x.clone();
-                    ponto[i] -= DBL_EPS;
-                    16.0 * f(ponto)
-                };
-
-                let val5 = {
-                    let mut ponto = x.clone();
-                    ponto[i] -= DDBL_EPS;
-
-                    -f(ponto)
-                };
-
-                let val = (val1 + val2 + val3 + val4 + val5) / (12.0 * DBL_EPS2);
-                hessiana[i][j] = val;
-                encontrados[i][j] = true;
-            } else {
-                if !encontrados[i][j] {
-                    let val1 = {
-                        let mut ponto = x.clone();
-                        ponto[i] += DBL_EPS;
-                        ponto[j] += DBL_EPS;
-                        f(ponto)
-                    };
-
-                    let val2 = {
-                        let mut ponto = x.clone();
-                        ponto[i] += DBL_EPS;
-                        ponto[j] -= DBL_EPS;
-                        -f(ponto)
-                    };
-
-                    let val3 = {
-                        let mut ponto = x.clone();
-                        ponto[i] -= DBL_EPS;
-                        ponto[j] += DBL_EPS;
-                        -f(ponto)
-                    };
-
-                    let val4 = {
-                        let mut ponto = x.clone();
-                        ponto[i] -= DBL_EPS;
-                        ponto[j] -= DBL_EPS;
-                        f(ponto)
-                    };
-
-                    let val = (val1 + val2 + val3 + val4) / (4.0 * DBL_EPS2);
-                    hessiana[i][j] = val;
-                    hessiana[j][i] = val;
-                    encontrados[i][j] = true;
-                    encontrados[j][i] = true;
+            // E remove do conjunto passivo as variaveis que zeraram
+            passivas.retain(|&p| y[p] > tol);
+            for (p, yp) in y.iter_mut().enumerate() {
+                if !passivas.contains(&p) {
+                    *yp = 0.0;
                 }
             }
         }
     }
 
-    hessiana
+    y
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    const TOL_TESTE: NumReal = 1e-10;
+
+    // Colunas e₁, e₂ e e₁ + e₂ com alvo (1, -1). Nenhuma coluna tem a
+    // segunda componente negativa, então o otimo é y = (1, 0, 0)
+    #[test]
+    fn minimos_quadrados_nao_negativos_satisfaz_kkt() {
+        let colunas = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]];
+        let alvo = [1.0, -1.0];
+        let y = minimos_quadrados_nao_negativos(&colunas, &alvo);
+
+        for (yj, esperado) in y.iter().zip([1.0, 0.0, 0.0]) {
+            assert!((yj - esperado).abs() <= TOL_TESTE, "{:?}", y);
+        }
+
+        // KKT: y ≥ 0, w = Mᵀ(alvo - My) ≤ 0 e yⱼwⱼ = 0
+        let mut residuo = alvo.to_vec();
+        for (coluna, yj) in colunas.iter().zip(&y) {
+            for k in 0..residuo.len() {
+                residuo[k] -= coluna[k] * yj;
+            }
+        }
+        for (coluna, &yj) in colunas.iter().zip(&y) {
+            let wj = _produto_interno_generico(coluna, &residuo);
+            assert!(yj >= 0.0);
+            assert!(wj <= TOL_TESTE);
+            assert!((yj * wj).abs() <= TOL_TESTE);
+        }
+    }
+
+    // Com alvo no cone das colunas, o residuo se anula, mesmo com uma
+    // coluna dependente das outras
+    #[test]
+    fn minimos_quadrados_nao_negativos_alvo_no_cone() {
+        let colunas = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]];
+        let alvo = [2.0, 3.0];
+        let y = minimos_quadrados_nao_negativos(&colunas, &alvo);
+
+        assert!(y.iter().all(|&yj| yj >= 0.0));
+        for k in 0..2 {
+            let my: NumReal = colunas
+                .iter()
+                .zip(&y)
+                .map(|(coluna, yj)| coluna[k] * yj)
+                .sum();
+            assert!((my - alvo[k]).abs() <= TOL_TESTE, "{:?}", y);
+        }
+    }
 }
@@ -0,0 +1,335 @@
+use crate::aleatorio::GeradorAleatorio;
+use crate::defs::*;
+use crate::diferencas_finitas::{
+    gradiente_passo_complexo, gradiente_sem_erro, jacobiana, ModoDiferenca, OpcoesDiferencas,
+};
+
+// Numero de pontos sorteados na caixa das variaveis, além do ponto inicial
+const PONTOS_VERIFICACAO: usize = 5;
+
+// Semente fixa, para que a verificação seja a mesma em toda execução
+const SEMENTE_VERIFICACAO: u64 = 2022;
+
+// Maior erro relativo aceito entre a derivada analitica e a numerica.
+// As diferenças centrais com extrapolação têm erro perto de 1e-10, então
+// acima disso o erro é da derivada analitica
+const TOL_ERRO_DERIVADA: NumReal = 1e-6;
+
+// Erro provavel de uma derivada analitica que não confere
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuspeitaDerivada {
+    SinalTrocado,                    // A derivada analitica é -∇
+    ComponenteTrocada(usize, usize), // A componente i tem o valor da componente k
+    RestricaoTrocada(usize),         // A linha tem o gradiente de outra restrição
+    TamanhoErrado(usize, usize),     // A jacobiana tem um numero errado de linhas
+}
+
+// Comparação do gradiente analitico de uma função com o numerico em um ponto
+#[derive(Debug, Clone)]
+pub struct ComparacaoDerivada {
+    pub nome: String,
+    pub ponto: Ponto,
+    pub analitica: Ponto,
+    pub numerica: Ponto,
+
+    // |analiticaᵢ - numericaᵢ| / max(|numericaᵢ|, 1), absoluto perto de zero
+    pub erros_relativos: Ponto,
+
+    pub suspeita: Option<SuspeitaDerivada>,
+}
+
+impl ComparacaoDerivada {
+    // Se a derivada conferiu em todas as componentes. Um erro NaN, de uma
+    // derivada analitica não finita, também reprova
+    pub fn aprovada(&self) -> bool {
+        self.suspeita.is_none()
+            && self
+                .erros_relativos
+                .iter()
+                .all(|&erro| erro <= TOL_ERRO_DERIVADA)
+    }
+}
+
+// Resultado da verificação de todas as derivadas analiticas de um problema
+#[derive(Debug, Clone)]
+pub struct RelatorioDerivadas {
+    pub comparacoes: Vec<ComparacaoDerivada>,
+}
+
+impl RelatorioDerivadas {
+    pub fn aprovado(&self) -> bool {
+        self.comparacoes
+            .iter()
+            .all(|comparacao| comparacao.aprovada())
+    }
+
+    // Mostra o maior erro relativo de cada componente de cada função, e
+    // depois cada comparação reprovada com o erro provavel. Sem detalhes,
+    // mostra só as reprovadas
+    pub fn mostrar(&self, detalhado: bool) {
+        println!(
+            "Verificação das derivadas analiticas: {}",
+            if self.aprovado() {
+                "conferem"
+            } else {
+                "NÃO CONFEREM"
+            }
+        );
+
+        if detalhado {
+            let mut nomes: Vec<&String> = Vec::new();
+            for comparacao in &self.comparacoes {
+                if !nomes.contains(&&comparacao.nome) {
+                    nomes.push(&comparacao.nome);
+                }
+            }
+
+            for nome in nomes {
+                let mut maiores = [0.0; DIM];
+                for comparacao in self.comparacoes.iter().filter(|c| &c.nome == nome) {
+                    for (maior, &erro) in maiores.iter_mut().zip(&comparacao.erros_relativos) {
+                        *maior = NumReal::max(*maior, erro);
+                    }
+                }
+                println!("{}: maiores erros relativos = {:?}", nome, maiores);
+            }
+        }
+
+        for comparacao in self.comparacoes.iter().filter(|c| !c.aprovada()) {
+            let suspeita = match &comparacao.suspeita {
+                Some(SuspeitaDerivada::SinalTrocado) => "sinal trocado".to_string(),
+                Some(SuspeitaDerivada::ComponenteTrocada(i, k)) => {
+                    format!("componente {} tem o valor da componente {}", i, k)
+                }
+                Some(SuspeitaDerivada::RestricaoTrocada(l)) => {
+                    format!("parece o gradiente da restrição {}", l)
+                }
+                Some(SuspeitaDerivada::TamanhoErrado(linhas, esperadas)) => {
+                    format!("jacobiana com {} linhas, esperadas {}", linhas, esperadas)
+                }
+                None => "erro sem padrão".to_string(),
+            };
+            println!(
+                "{} em x = {:?}: analitica = {:?}, numerica = {:?}, erros relativos = {:?} ({})",
+                comparacao.nome,
+                comparacao.ponto,
+                comparacao.analitica,
+                comparacao.numerica,
+                comparacao.erros_relativos,
+                suspeita
+            );
+        }
+    }
+}
+
+// Compara as derivadas analiticas do problema com diferenças centrais
+// extrapoladas, ou com o passo complexo nas funções com forma complexa,
+// no ponto inicial e em pontos sorteados na caixa das variaveis
+pub fn verificar_derivadas(problema: &Problema) -> RelatorioDerivadas {
+    let opcoes = OpcoesDiferencas {
+        modo: ModoDiferenca::Central,
+        richardson: true,
+    };
+
+    let mut gerador = GeradorAleatorio::novo(SEMENTE_VERIFICACAO);
+    let (inferior, superior) = problema.caixa_variaveis();
+    let mut pontos = vec![problema.x_inicial];
+    for _ in 0..PONTOS_VERIFICACAO {
+        pontos.push(gerador.ponto_na_caixa(&inferior, &superior));
+    }
+
+    // As linhas de ∇c são as desigualdades seguidas das igualdades
+    let nomes_desigualdades: Vec<String> = (0..problema.mi())
+        .map(|j| problema.nome_desigualdade(j))
+        .collect();
+    let nomes_igualdades: Vec<String> = (0..problema.me())
+        .map(|r| problema.nome_igualdade(r))
+        .collect();
+    let mut nomes_restricoes = nomes_desigualdades.clone();
+    nomes_restricoes.extend(nomes_igualdades.iter().cloned());
+
+    let mut comparacoes = Vec::new();
+
+    for &x in &pontos {
+        if let Some(gradiente_objetivo) = &problema.gradiente_objetivo {
+            let numerica = match &problema.objetivo_complexo {
+                Some(forma) => gradiente_passo_complexo(x, &**forma),
+                None => gradiente_sem_erro(x, &*problema.funcao_objetivo, &opcoes),
+            };
+            comparar_familia(
+                "∇f",
+                std::slice::from_ref(&problema.nome_objetivo),
+                x,
+                &[gradiente_objetivo(x)],
+                &[numerica],
+                &mut comparacoes,
+            );
+        }
+
+        if let Some(jacobiana) = &problema.jacobiana_desigualdades {
+            let numericas = gradientes_numericos(
+                &problema.restricoes_desigualdades,
+                &problema.desigualdades_complexas,
+                x,
+                &opcoes,
+            );
+            comparar_familia(
+                "∇g",
+                &nomes_desigualdades,
+                x,
+                &jacobiana(x),
+                &numericas,
+                &mut comparacoes,
+            );
+        }
+
+        if let Some(jacobiana) = &problema.jacobiana_igualdades {
+            let numericas = gradientes_numericos(
+                &problema.restricoes_igualdades,
+                &problema.igualdades_complexas,
+                x,
+                &opcoes,
+            );
+            comparar_familia(
+                "∇h",
+                &nomes_igualdades,
+                x,
+                &jacobiana(x),
+                &numericas,
+                &mut comparacoes,
+            );
+        }
+
+        if let Some(restricoes) = &problema.restricoes_vetoriais {
+            if let Some(jacobiana_analitica) = &restricoes.jacobiana {
+                let numericas = jacobiana(x, &*restricoes.funcao, &opcoes);
+                comparar_familia(
+                    "∇c",
+                    &nomes_restricoes,
+                    x,
+                    &jacobiana_analitica(x),
+                    &numericas,
+                    &mut comparacoes,
+                );
+            }
+        }
+    }
+
+    RelatorioDerivadas { comparacoes }
+}
+
+// Gradientes pelo passo complexo nas funções com forma complexa, e por
+// diferenças nas outras
+fn gradientes_numericos(
+    funcoes: &[Funcao],
+    complexas: &[Option<FuncaoComplexa>],
+    x: Ponto,
+    opcoes: &OpcoesDiferencas,
+) -> Vec<Ponto> {
+    funcoes
+        .iter()
+        .enumerate()
+        .map(|(j, f)| match complexas.get(j) {
+            Some(Some(forma)) => gradiente_passo_complexo(x, &**forma),
+            _ => gradiente_sem_erro(x, &**f, opcoes),
+        })
+        .collect()
+}
+
+fn erro_relativo(analitica: NumReal, numerica: NumReal) -> NumReal {
+    (analitica - numerica).abs() / numerica.abs().max(1.0)
+}
+
+fn confere(analitica: &Ponto, numerica: &Ponto) -> bool {
+    (0..DIM).all(|i| erro_relativo(analitica[i], numerica[i]) <= TOL_ERRO_DERIVADA)
+}
+
+// Compara as linhas de uma jacobiana com os gradientes numericos das
+// mesmas funções, e procura o erro provavel das linhas que não conferem.
+// Cada comparação leva o nome da função da linha
+fn comparar_familia(
+    familia: &str,
+    nomes: &[String],
+    x: Ponto,
+    analiticas: &[Ponto],
+    numericas: &[Ponto],
+    comparacoes: &mut Vec<ComparacaoDerivada>,
+) {
+    if analiticas.len() != numericas.len() {
+        comparacoes.push(ComparacaoDerivada {
+            nome: familia.to_string(),
+            ponto: x,
+            analitica: [NumReal::NAN; DIM],
+            numerica: [NumReal::NAN; DIM],
+            erros_relativos: [NumReal::NAN; DIM],
+            suspeita: Some(SuspeitaDerivada::TamanhoErrado(
+                analiticas.len(),
+                numericas.len(),
+            )),
+        });
+        return;
+    }
+
+    for j in 0..numericas.len() {
+        // Fora do dominio da função não há o que comparar
+        if numericas[j].iter().any(|valor| !valor.is_finite()) {
+            continue;
+        }
+
+        let mut erros_relativos = [0.0; DIM];
+        for i in 0..DIM {
+            erros_relativos[i] = erro_relativo(analiticas[j][i], numericas[j][i]);
+        }
+
+        let suspeita = if confere(&analiticas[j], &numericas[j]) {
+            None
+        } else {
+            procurar_suspeita(j, analiticas, numericas)
+        };
+
+        comparacoes.push(ComparacaoDerivada {
+            nome: format!("∇{}", nomes[j]),
+            ponto: x,
+            analitica: analiticas[j],
+            numerica: numericas[j],
+            erros_relativos,
+            suspeita,
+        });
+    }
+}
+
+// Procura, na ordem, um sinal trocado, uma linha de outra restrição, ou
+// uma componente com o valor de outra componente
+fn procurar_suspeita(
+    j: usize,
+    analiticas: &[Ponto],
+    numericas: &[Ponto],
+) -> Option<SuspeitaDerivada> {
+    let analitica = analiticas[j];
+    let mut oposta = [0.0; DIM];
+    for i in 0..DIM {
+        oposta[i] = -analitica[i];
+    }
+    if confere(&oposta, &numericas[j]) {
+        return Some(SuspeitaDerivada::SinalTrocado);
+    }
+
+    for (l, numerica) in numericas.iter().enumerate() {
+        if l != j && confere(&analitica, numerica) {
+            return Some(SuspeitaDerivada::RestricaoTrocada(l));
+        }
+    }
+
+    for i in 0..DIM {
+        if erro_relativo(analitica[i], numericas[j][i]) <= TOL_ERRO_DERIVADA {
+            continue;
+        }
+        for (k, &numerica) in numericas[j].iter().enumerate() {
+            if k != i && erro_relativo(analitica[i], numerica) <= TOL_ERRO_DERIVADA {
+                return Some(SuspeitaDerivada::ComponenteTrocada(i, k));
+            }
+        }
+    }
+
+    None
+}